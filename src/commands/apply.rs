@@ -1,46 +1,263 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::commands::find;
-use crate::git;
-use crate::profile::{ProfileContext, apply_profile, resolve_profile_with_context};
-use crate::term::{print_header, print_success, print_warning};
+use crate::config::ProfileMapping;
+use crate::git::{self, ConfigTarget};
+use crate::profile::{
+    Profile, ProfileContext, ProfileSelection, apply_profile, apply_profile_to, discover_profiles,
+    find_profile_by_name, local_values, pending_changes, pending_changes_in,
+    resolve_profile_with_context, restore_local_values, revert_profile, validate_selection,
+};
+use crate::term::{
+    SilentExit, TerminalPrompter, confirm_each as confirm_each_item, format_home_path,
+    is_interactive, print_field_diff, print_header, print_hint, print_success, print_warning,
+    prompt_confirm,
+};
 
-/// Executes the apply command flow
-pub fn run(name: Option<&str>, profile_name: Option<&str>, pool: Option<&str>) -> Result<()> {
+/// Where `apply` writes the profile
+#[derive(Debug, Clone, Copy)]
+pub enum Scope<'a> {
+    /// A repository by name or path (current directory if `None`)
+    Repo(Option<&'a str>),
+    /// Every repository in a pool; with `auto_map`, `profiles.map` rules choose per repository,
+    /// with `confirm_each`, every repository is confirmed individually, and with `atomic`,
+    /// a failure restores the repositories already modified
+    Pool {
+        name: &'a str,
+        auto_map: bool,
+        confirm_each: bool,
+        atomic: bool,
+    },
+    /// The global git config
+    Global,
+}
+
+/// Executes the apply command flow.
+/// With `revert`, the repository's local identity config is removed instead.
+/// With `yes`, confirmations before overwriting or removing config are skipped.
+pub fn run(
+    scope: Scope<'_>,
+    selection: ProfileSelection<'_>,
+    revert: bool,
+    yes: bool,
+) -> Result<()> {
     git::ensure_available()?;
+    validate_selection(selection)?;
+
+    let name = match scope {
+        Scope::Global => return apply_global(selection, yes),
+        Scope::Pool {
+            name,
+            auto_map,
+            confirm_each,
+            atomic,
+        } => return run_pool(name, selection, auto_map, confirm_each, atomic),
+        Scope::Repo(name) => name,
+    };
+
+    let Some(target) = resolve_repo_target(name)? else {
+        return Ok(());
+    };
 
-    if let Some(pool_name) = pool {
-        return run_pool(pool_name, profile_name);
+    if revert {
+        return revert_repo(&target, yes);
     }
 
-    let target = match name {
-        Some(name) => find::resolve_repo(name)?,
-        None => PathBuf::from("."),
+    apply_to_repo(&target, selection, yes)
+}
+
+/// Verifies that the targets already use the selected profile, without writing anything.
+/// Prints the differences per target and exits with status 1 if any target does not match.
+pub fn check(scope: Scope<'_>, selection: ProfileSelection<'_>) -> Result<()> {
+    git::ensure_available()?;
+    validate_selection(selection)?;
+    if selection == ProfileSelection::Interactive {
+        anyhow::bail!("--check needs --profile, --profile-index, or --auto");
+    }
+
+    let targets: Vec<(String, ConfigTarget)> = match scope {
+        Scope::Global => vec![("Global git config".to_string(), ConfigTarget::Global)],
+        Scope::Repo(name) => {
+            let Some(repo) = resolve_repo_target(name)? else {
+                return Ok(());
+            };
+            vec![(display_name(&repo), ConfigTarget::Local(repo))]
+        }
+        Scope::Pool { name, .. } => {
+            let (_, repos) = pool_repos(name)?;
+            if repos.is_empty() {
+                print_warning(format!("No repositories found in pool '{name}'"));
+                return Ok(());
+            }
+            repos
+                .into_iter()
+                .map(|repo| (pool_display(&repo), ConfigTarget::Local(repo)))
+                .collect()
+        }
+    };
+
+    // Automatic selection depends on each repository's includeIf rules
+    let fixed = if selection == ProfileSelection::Auto {
+        None
+    } else {
+        resolve_profile_with_context(selection, &ProfileContext::new(PathBuf::from("."), None))?
     };
 
-    apply_to_repo(&target, profile_name)
+    let mut matching = 0;
+    for (label, target) in &targets {
+        let profile = if let Some(profile) = &fixed {
+            profile.clone()
+        } else {
+            let context = match target {
+                ConfigTarget::Local(repo) => repo_context(repo),
+                _ => ProfileContext::new(PathBuf::from("."), None),
+            };
+            let Some(profile) = resolve_profile_with_context(selection, &context)? else {
+                return Ok(());
+            };
+            profile
+        };
+        if check_target(label, target, &profile)? {
+            matching += 1;
+        }
+    }
+
+    if targets.len() > 1 {
+        println!();
+        let summary = format!(
+            "{matching} of {} {} match",
+            targets.len(),
+            plural_repositories(targets.len())
+        );
+        if matching == targets.len() {
+            print_success(summary);
+        } else {
+            print_warning(summary);
+        }
+    }
+
+    if matching < targets.len() {
+        return Err(SilentExit(1).into());
+    }
+    Ok(())
 }
 
-fn apply_to_repo(target: &Path, profile_name: Option<&str>) -> Result<()> {
-    let display_path = target
+/// Compares a target's config with the profile and prints the result with any differences.
+/// Returns whether the target already matches.
+fn check_target(label: &str, target: &ConfigTarget, profile: &Profile) -> Result<bool> {
+    let changes = pending_changes_in(target, profile)?;
+    if changes.is_empty() {
+        print_success(format!("{label} matches '{}'", profile.name));
+        return Ok(true);
+    }
+
+    print_warning(format!("{label} does not match '{}'", profile.name));
+    for change in &changes {
+        print_field_diff(change.key, change.old.as_deref(), Some(&change.new));
+    }
+    Ok(false)
+}
+
+/// Resolves the repository to operate on. Without a name, this is the root of the working
+/// tree containing the current directory (also from a subdirectory or linked worktree);
+/// outside a repository, an interactive terminal offers a picker.
+/// Returns `None` if the picker was cancelled.
+fn resolve_repo_target(name: Option<&str>) -> Result<Option<PathBuf>> {
+    if let Some(name) = name {
+        return find::resolve_repo(name).map(Some);
+    }
+    if let Some(root) = git::toplevel(Path::new(".")) {
+        return Ok(Some(root));
+    }
+    if !is_interactive() {
+        return Ok(Some(PathBuf::from(".")));
+    }
+
+    print_warning("Not a git repository, select one instead");
+    super::pick_repo()
+}
+
+/// Resolves a pool by name and returns its path with the scanned repositories inside it
+fn pool_repos(pool_name: &str) -> Result<(PathBuf, Vec<PathBuf>)> {
+    let pool_path = find::resolve_pool(pool_name)?;
+    let pool_path = pool_path.canonicalize().unwrap_or(pool_path);
+
+    let repos = crate::state::load()?
+        .repositories
+        .into_iter()
+        .filter(|r| r.starts_with(&pool_path))
+        .collect();
+    Ok((pool_path, repos))
+}
+
+/// Display name of a repository target (directory basename)
+fn display_name(target: &Path) -> String {
+    target
         .canonicalize()
         .ok()
         .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
-        .unwrap_or_else(|| target.display().to_string());
+        .unwrap_or_else(|| target.display().to_string())
+}
 
-    if !target.join(".git").exists() {
+fn revert_repo(target: &Path, yes: bool) -> Result<()> {
+    if !git::is_repository(target) {
+        anyhow::bail!("Not a git repository: {}", target.display());
+    }
+
+    print_header("Repository:", display_name(target));
+    println!();
+
+    if !confirm(
+        "Remove the local identity config and fall back to inherited values?",
+        yes,
+        is_interactive(),
+    )? {
+        return Ok(());
+    }
+
+    let cleared = revert_profile(target)?;
+    if cleared.is_empty() {
+        print_warning("No local identity config to remove");
+        return Ok(());
+    }
+
+    print_success("Removed local identity config");
+    for key in cleared {
+        println!("    - {key}");
+    }
+
+    Ok(())
+}
+
+fn apply_to_repo(target: &Path, selection: ProfileSelection<'_>, yes: bool) -> Result<()> {
+    let display_path = display_name(target);
+
+    if !git::is_repository(target) {
         anyhow::bail!("Not a git repository: {}", target.display());
     }
 
     print_header("Repository:", &display_path);
     println!();
 
-    let context = ProfileContext::new(target.to_path_buf(), None);
-    let Some(selected) = resolve_profile_with_context(profile_name, &context)? else {
+    // A profile named by .yarm-profile or pinned by a .yarm marker stands in for --profile
+    let repo_profile = match selection {
+        ProfileSelection::Interactive => crate::profile::repo_profile_name(target)?,
+        _ => None,
+    };
+    let selection = repo_selection(selection, repo_profile.as_deref());
+
+    let context = repo_context(target);
+    let Some(selected) = resolve_profile_with_context(selection, &context)? else {
         return Ok(());
     };
 
+    if !confirm_changes(target, &selected, yes)? {
+        return Ok(());
+    }
+
     apply_profile(target, &selected)?;
 
     print_success(format!(
@@ -52,53 +269,533 @@ fn apply_to_repo(target: &Path, profile_name: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-fn run_pool(pool_name: &str, profile_name: Option<&str>) -> Result<()> {
-    let pool_path = find::resolve_pool(pool_name)?;
-    let pool_path = pool_path.canonicalize().unwrap_or(pool_path);
+/// Uses the profile a repository names for itself unless a selection was given explicitly
+fn repo_selection<'a>(
+    selection: ProfileSelection<'a>,
+    repo_profile: Option<&'a str>,
+) -> ProfileSelection<'a> {
+    match (selection, repo_profile) {
+        (ProfileSelection::Interactive, Some(name)) => ProfileSelection::Named(name),
+        _ => selection,
+    }
+}
 
-    let state = crate::state::load()?;
-    let repos: Vec<_> = state
-        .repositories
-        .iter()
-        .filter(|r| r.starts_with(&pool_path))
-        .collect();
+/// Previews the local config changes and asks for confirmation if existing values
+/// would be overwritten. Returns `false` if the user declined.
+fn confirm_changes(target: &Path, profile: &Profile, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    let changes = pending_changes(target, profile)?;
+    if !changes.iter().any(|c| c.old.is_some()) {
+        return Ok(true);
+    }
+
+    println!("  Local config changes:");
+    for change in &changes {
+        print_field_diff(change.key, change.old.as_deref(), Some(&change.new));
+    }
+    println!();
+
+    confirm(
+        "Overwrite the local identity config?",
+        yes,
+        is_interactive(),
+    )
+}
+
+/// Asks for confirmation unless `yes` is given. Without a terminal there is nobody
+/// to ask, so this fails instead of prompting and points at `--yes`.
+fn confirm(prompt: &str, yes: bool, interactive: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !interactive {
+        anyhow::bail!("Cannot confirm without a terminal: {prompt} Pass --yes to proceed");
+    }
+    Ok(prompt_confirm(prompt, false)? == Some(true))
+}
+
+/// Writes a profile's identity to the global git config, reporting the identity it replaces
+fn apply_global(selection: ProfileSelection<'_>, yes: bool) -> Result<()> {
+    print_header("Scope:", "global git config");
+    println!();
+
+    let context = ProfileContext::new(PathBuf::from("."), None);
+    let Some(selected) = resolve_profile_with_context(selection, &context)? else {
+        return Ok(());
+    };
+
+    let target = ConfigTarget::Global;
+    let previous = global_identity();
+    let changes = pending_changes_in(&target, &selected)?;
+    if changes.is_empty() {
+        print_success(format!(
+            "Profile '{}' already is the global identity",
+            selected.name
+        ));
+        return Ok(());
+    }
+
+    println!("  Global config changes:");
+    for change in &changes {
+        print_field_diff(change.key, change.old.as_deref(), Some(&change.new));
+    }
+    println!();
+
+    if !confirm(
+        "Change the global identity for all repositories?",
+        yes,
+        is_interactive(),
+    )? {
+        return Ok(());
+    }
+
+    apply_profile_to(&target, &selected)?;
+
+    print_success(format!(
+        "Set profile '{}' ({}) as global identity",
+        selected.name,
+        selected.config_summary()
+    ));
+    if let Some(previous) = previous {
+        print_hint(format!("Previous global identity: {previous}"));
+    }
+
+    Ok(())
+}
+
+/// Formats the current global `user.name <user.email>`, if any part is set
+fn global_identity() -> Option<String> {
+    let name = git::get_config_scoped(&ConfigTarget::Global, "user.name");
+    let email = git::get_config_scoped(&ConfigTarget::Global, "user.email");
+    match (name, email) {
+        (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+        (Some(name), None) => Some(name),
+        (None, Some(email)) => Some(format!("<{email}>")),
+        (None, None) => None,
+    }
+}
+
+fn run_pool(
+    pool_name: &str,
+    selection: ProfileSelection<'_>,
+    auto_map: bool,
+    confirm_each: bool,
+    atomic: bool,
+) -> Result<()> {
+    if confirm_each && !is_interactive() {
+        anyhow::bail!("--confirm-each needs an interactive terminal");
+    }
+
+    let (pool_path, repos) = pool_repos(pool_name)?;
 
     if repos.is_empty() {
         print_warning(format!("No repositories found in pool '{pool_name}'"));
         return Ok(());
     }
 
+    let rules = if auto_map {
+        compile_map(&crate::config::load()?.profiles.map)?
+    } else {
+        Vec::new()
+    };
+    let profiles = discover_profiles()?;
+    let mut mapped: Vec<Option<Profile>> = repos
+        .iter()
+        .map(|repo| {
+            mapped_profile(repo, &rules)
+                .map(|name| find_profile_by_name(&profiles, name))
+                .transpose()
+        })
+        .collect::<Result<_>>()?;
+
     print_header("Pool:", pool_name);
     println!();
 
-    let context = ProfileContext::new(pool_path, None);
-    let Some(selected) = resolve_profile_with_context(profile_name, &context)? else {
+    // The selected profile is only needed for repositories no mapping rule covers;
+    // automatic selection depends on each repository's own includeIf rules
+    let auto = selection == ProfileSelection::Auto;
+    let fallback = if !auto && mapped.iter().any(Option::is_none) {
+        let context = ProfileContext::new(pool_path, None);
+        let Some(selected) = resolve_profile_with_context(selection, &context)? else {
+            return Ok(());
+        };
+        Some(selected)
+    } else {
+        None
+    };
+    if auto {
+        for (repo, profile) in repos.iter().zip(mapped.iter_mut()) {
+            if profile.is_none() {
+                *profile = resolve_profile_with_context(selection, &repo_context(repo))
+                    .with_context(|| format!("No profile for {}", pool_display(repo)))?;
+            }
+        }
+    }
+
+    let mut targets: Vec<(&PathBuf, &Profile)> = repos
+        .iter()
+        .zip(&mapped)
+        .filter_map(|(repo, mapped)| Some((repo, mapped.as_ref().or(fallback.as_ref())?)))
+        .collect();
+    if confirm_each {
+        targets = confirm_each_item(
+            targets,
+            |(repo, profile)| format!("Apply '{}' to {}?", profile.name, format_home_path(repo)),
+            &mut TerminalPrompter,
+        )?;
+        println!();
+    }
+
+    let run = apply_each(
+        targets,
+        atomic,
+        |(repo, profile)| {
+            apply_profile(repo, profile)?;
+            if auto_map || auto {
+                print_success(format!(
+                    "Applied '{}' to {}",
+                    profile.name,
+                    pool_display(repo)
+                ));
+            } else {
+                print_success(format!("Applied to {}", pool_display(repo)));
+            }
+            Ok(())
+        },
+        |(repo, _)| local_values(repo),
+        |(repo, _), values| restore_local_values(repo, values),
+    );
+
+    report_pool_run(&run)
+}
+
+/// Resolution context of a repository: its path for `gitdir:` rules and its
+/// `origin` URL for `hasconfig:remote.*.url:` rules
+fn repo_context(repo: &Path) -> ProfileContext {
+    ProfileContext::new(repo.to_path_buf(), git::origin_url(repo))
+}
+
+/// Prints how many repositories got each profile, then any failures
+fn report_pool_run(run: &PoolRun<(&PathBuf, &Profile)>) -> Result<()> {
+    let mut distribution: BTreeMap<String, (String, usize)> = BTreeMap::new();
+    for (_, profile) in &run.applied {
+        distribution
+            .entry(profile.name.clone())
+            .or_insert_with(|| (profile.config_summary(), 0))
+            .1 += 1;
+    }
+
+    println!();
+    for (name, (summary, applied)) in &distribution {
+        print_success(format!(
+            "Applied profile '{name}' ({summary}) to {applied} {}",
+            plural_repositories(*applied)
+        ));
+    }
+
+    if run.failed.is_empty() {
         return Ok(());
+    }
+
+    if !run.restored.is_empty() {
+        print_warning(format!(
+            "Restored the previous identity in {} {}",
+            run.restored.len(),
+            plural_repositories(run.restored.len())
+        ));
+    }
+    for ((repo, _), err) in &run.failed {
+        print_warning(format!("{}: {err:#}", pool_display(repo)));
+    }
+
+    anyhow::bail!(
+        "Failed to apply the profile to {} {}",
+        run.failed.len(),
+        plural_repositories(run.failed.len())
+    )
+}
+
+/// Display name of a pool repository (directory basename)
+fn pool_display(repo: &Path) -> String {
+    repo.file_name().map_or_else(
+        || repo.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
+
+pub(crate) fn plural_repositories(count: usize) -> &'static str {
+    if count == 1 {
+        "repository"
+    } else {
+        "repositories"
+    }
+}
+
+/// Outcome of applying profiles to several repositories
+struct PoolRun<T> {
+    applied: Vec<T>,
+    failed: Vec<(T, anyhow::Error)>,
+    /// Targets that were modified and then restored after an atomic run failed
+    restored: Vec<T>,
+}
+
+/// Applies `apply` to each target. Without `atomic`, failures are collected and the
+/// remaining targets still applied. With `atomic`, each target's prior state is recorded
+/// via `record` first, and the first failure restores every already modified target
+/// (most recent first) and stops.
+fn apply_each<T, S>(
+    targets: Vec<T>,
+    atomic: bool,
+    mut apply: impl FnMut(&T) -> Result<()>,
+    record: impl Fn(&T) -> S,
+    restore: impl Fn(&T, &S) -> Result<()>,
+) -> PoolRun<T> {
+    let mut run = PoolRun {
+        applied: Vec::new(),
+        failed: Vec::new(),
+        restored: Vec::new(),
     };
+    let mut recorded = Vec::new();
+
+    for target in targets {
+        let prior = atomic.then(|| record(&target));
+        let Err(err) = apply(&target) else {
+            run.applied.push(target);
+            recorded.extend(prior);
+            continue;
+        };
+
+        let Some(prior) = prior else {
+            run.failed.push((target, err));
+            continue;
+        };
+
+        // The failing target may have been partially written before the error
+        let err = match restore(&target, &prior) {
+            Ok(()) => err,
+            Err(restore_err) => {
+                anyhow::anyhow!("{err:#}; restoring the previous identity failed: {restore_err:#}")
+            }
+        };
+        run.failed.push((target, err));
+
+        for (target, prior) in run.applied.drain(..).zip(recorded.drain(..)).rev() {
+            match restore(&target, &prior) {
+                Ok(()) => run.restored.push(target),
+                Err(err) => run.failed.push((
+                    target,
+                    err.context("Failed to restore the previous identity"),
+                )),
+            }
+        }
+        break;
+    }
 
-    let mut applied = 0;
-    for repo in &repos {
-        let display = repo.file_name().map_or_else(
-            || repo.display().to_string(),
-            |n| n.to_string_lossy().into_owned(),
+    run
+}
+
+/// Compiles `profiles.map` entries into path matchers, in configuration order
+fn compile_map(map: &[ProfileMapping]) -> Result<Vec<(GlobMatcher, String)>> {
+    map.iter()
+        .map(|entry| {
+            let pattern = crate::config::expand_path(&entry.glob);
+            let glob = GlobBuilder::new(&pattern.to_string_lossy())
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid glob in profiles.map: {}", entry.glob))?;
+            Ok((glob.compile_matcher(), entry.profile.clone()))
+        })
+        .collect()
+}
+
+/// Returns the profile name of the first rule matching `repo`
+fn mapped_profile<'a>(repo: &Path, rules: &'a [(GlobMatcher, String)]) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|(matcher, _)| matcher.is_match(repo))
+        .map(|(_, profile)| profile.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_repo_selection_prefers_explicit_choice() {
+        assert_eq!(
+            repo_selection(ProfileSelection::Interactive, Some("work")),
+            ProfileSelection::Named("work")
+        );
+        assert_eq!(
+            repo_selection(ProfileSelection::Interactive, None),
+            ProfileSelection::Interactive
+        );
+        assert_eq!(
+            repo_selection(ProfileSelection::Named("oss"), Some("work")),
+            ProfileSelection::Named("oss")
+        );
+        assert_eq!(
+            repo_selection(ProfileSelection::Auto, Some("work")),
+            ProfileSelection::Auto
+        );
+    }
+
+    #[test]
+    fn test_check_target_matching_and_mismatching() {
+        let root = std::env::temp_dir().join("yarm-test-apply-check");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let profile_file = root.join("work.gitconfig");
+        std::fs::write(&profile_file, "[user]\n\tname = Work\n\temail = w@co.com\n").unwrap();
+        let profile = crate::profile::reload_profile(&profile_file)
+            .unwrap()
+            .unwrap();
+
+        let init = |name: &str| {
+            let repo = root.join(name);
+            std::fs::create_dir_all(&repo).unwrap();
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["init", "-q"])
+                .status()
+                .unwrap();
+            assert!(status.success());
+            repo
+        };
+        let matching = init("matching");
+        apply_profile(&matching, &profile).unwrap();
+        let mismatching = init("mismatching");
+        git::set_config(&mismatching, "user.email", Some("me@home.org")).unwrap();
+
+        assert!(
+            check_target("matching", &ConfigTarget::Local(matching.clone()), &profile).unwrap()
+        );
+        assert!(
+            !check_target(
+                "mismatching",
+                &ConfigTarget::Local(mismatching.clone()),
+                &profile
+            )
+            .unwrap()
         );
+        // Checking writes nothing
+        assert_eq!(
+            git::get_config(&mismatching, "user.email").as_deref(),
+            Some("me@home.org")
+        );
+        assert_eq!(git::get_config(&mismatching, "user.name"), None);
 
-        apply_profile(repo, &selected)?;
-        print_success(format!("Applied to {display}"));
-        applied += 1;
+        let _ = std::fs::remove_dir_all(&root);
     }
 
-    println!();
-    print_success(format!(
-        "Applied profile '{}' ({}) to {applied} {}",
-        selected.name,
-        selected.config_summary(),
-        if applied == 1 {
-            "repository"
-        } else {
-            "repositories"
+    /// Applies `new` to every target in `store`, failing for targets in `failing`
+    fn run_each(
+        store: &RefCell<HashMap<&'static str, &'static str>>,
+        targets: Vec<&'static str>,
+        failing: &[&str],
+        atomic: bool,
+    ) -> PoolRun<&'static str> {
+        apply_each(
+            targets,
+            atomic,
+            |target| {
+                store.borrow_mut().insert(target, "new");
+                if failing.contains(target) {
+                    anyhow::bail!("permission denied");
+                }
+                Ok(())
+            },
+            |target| store.borrow()[target],
+            |target, prior| {
+                store.borrow_mut().insert(target, prior);
+                Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_apply_each_continues_past_failures() {
+        let store = RefCell::new(HashMap::from([("a", "old"), ("b", "old"), ("c", "old")]));
+        let run = run_each(&store, vec!["a", "b", "c"], &["b"], false);
+
+        assert_eq!(run.applied, vec!["a", "c"]);
+        assert_eq!(run.failed.len(), 1);
+        assert_eq!(run.failed[0].0, "b");
+        assert_eq!(run.failed[0].1.to_string(), "permission denied");
+        assert!(run.restored.is_empty());
+        assert_eq!(store.borrow()["c"], "new");
+    }
+
+    #[test]
+    fn test_apply_each_atomic_restores_modified() {
+        let store = RefCell::new(HashMap::from([
+            ("a", "old-a"),
+            ("b", "old-b"),
+            ("c", "old-c"),
+        ]));
+        let run = run_each(&store, vec!["a", "b", "c"], &["b"], true);
+
+        assert!(run.applied.is_empty());
+        assert_eq!(run.restored, vec!["a"]);
+        assert_eq!(run.failed.len(), 1);
+        assert_eq!(run.failed[0].0, "b");
+        // The failing target is restored too, and later targets are never touched
+        assert_eq!(store.borrow()["a"], "old-a");
+        assert_eq!(store.borrow()["b"], "old-b");
+        assert_eq!(store.borrow()["c"], "old-c");
+    }
+
+    #[test]
+    fn test_apply_each_atomic_success() {
+        let store = RefCell::new(HashMap::from([("a", "old"), ("b", "old")]));
+        let run = run_each(&store, vec!["a", "b"], &[], true);
+
+        assert_eq!(run.applied, vec!["a", "b"]);
+        assert!(run.failed.is_empty());
+        assert!(run.restored.is_empty());
+    }
+
+    #[test]
+    fn test_confirm_without_terminal() {
+        assert!(confirm("Overwrite?", true, false).unwrap());
+        let err = confirm("Overwrite?", false, false).unwrap_err();
+        assert!(err.to_string().contains("--yes"));
+    }
+
+    fn mapping(glob: &str, profile: &str) -> ProfileMapping {
+        ProfileMapping {
+            glob: glob.to_string(),
+            profile: profile.to_string(),
         }
-    ));
+    }
 
-    Ok(())
+    #[test]
+    fn test_mapped_profile_first_match_wins() {
+        let rules = compile_map(&[
+            mapping("/work/client-a/*", "client-a"),
+            mapping("/work/client-*/*", "clients"),
+            mapping("/work/**", "work"),
+        ])
+        .unwrap();
+
+        let resolve = |repo: &str| mapped_profile(Path::new(repo), &rules);
+        assert_eq!(resolve("/work/client-a/api"), Some("client-a"));
+        assert_eq!(resolve("/work/client-b/api"), Some("clients"));
+        assert_eq!(resolve("/work/internal/tools"), Some("work"));
+        // `*` does not cross directory boundaries
+        assert_eq!(resolve("/work/client-a/nested/api"), Some("work"));
+        assert_eq!(resolve("/personal/blog"), None);
+    }
+
+    #[test]
+    fn test_compile_map_invalid_glob() {
+        let err = compile_map(&[mapping("/work/[", "work")]).unwrap_err();
+        assert!(err.to_string().contains("profiles.map"));
+    }
 }
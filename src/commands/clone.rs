@@ -1,43 +1,129 @@
-use std::io::Read;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::sync::mpsc;
-use std::thread;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::{Context, Result};
+use signal_hook::consts::SIGINT;
 
+use crate::commands::find;
 use crate::git;
-use crate::profile::{ProfileContext, apply_profile, resolve_profile_with_context};
-use crate::term::{print_header, print_success};
-
-/// Executes the clone command flow
-pub fn run(url: &str, path: Option<PathBuf>, profile_name: Option<&str>) -> Result<()> {
+use crate::profile::{
+    Profile, ProfileContext, apply_profile, resolve_profile_with_context, validate_selection,
+};
+use crate::term::{
+    SilentExit, eprint_debug, eprint_hint, eprint_warning, format_home_path, print_header,
+    print_success,
+};
+
+/// Executes the clone command flow.
+/// An empty `into` pool name (`--into` without a value) selects the default pool.
+/// With `mirror`, a bare mirror clone is created (target defaults to `<repo>.git`).
+/// With `apply_to_existing`, an existing repository with the same origin is profiled instead of cloned.
+/// Unless `force` is set, cloning is refused if a scanned repository already has the same origin.
+pub fn run(
+    url: &str,
+    path: Option<PathBuf>,
+    profile_name: Option<&str>,
+    into: Option<&str>,
+    mirror: bool,
+    apply_to_existing: bool,
+    force: bool,
+) -> Result<()> {
     git::ensure_available()?;
+    validate_selection(profile_name.into())?;
 
-    let target = path.unwrap_or_else(|| derive_target_from_url(url));
-
-    if target.exists() {
-        anyhow::bail!("Target directory '{}' already exists", target.display());
+    let config = crate::config::load()?;
+    let rewritten = apply_url_rewrites(url, &config.clone.rewrite);
+    if rewritten != url {
+        eprint_debug(format!("Rewrote clone URL {url} → {rewritten}"));
     }
+    let url = rewritten.as_str();
+
+    let target = path.unwrap_or_else(|| {
+        let target = derive_target_from_url(url);
+        if mirror {
+            mirror_target(&target)
+        } else {
+            target
+        }
+    });
+    let pool = into.map(find::resolve_pool_or_default).transpose()?;
+    let target = match &pool {
+        Some(pool) => pool.join(target),
+        None => target,
+    };
 
-    print_header("Cloning:", extract_repo_display_name(url));
+    // A planned directory holding only a .yarm marker is cloned into
+    let planned = crate::config::planned_marker(&target);
+    let existing = target.exists() && planned.is_none();
+    if existing {
+        if pool.is_some() && !apply_to_existing && git::is_repository(&target) {
+            return report_pool_collision(&target, url);
+        }
+        if !apply_to_existing || !git::is_repository(&target) {
+            anyhow::bail!("Target directory '{}' already exists", target.display());
+        }
+        ensure_same_origin(&target, url)?;
+        print_header("Existing:", extract_repo_display_name(url));
+    } else {
+        // Local paths and file:// URLs can still be cloned offline
+        if git::parse_git_url(url).is_some() {
+            git::ensure_online("Cloning a remote repository")?;
+        }
+        if !force && !mirror {
+            refuse_existing_clone(url, &target)?;
+        }
+        if let Some(pool) = &pool {
+            warn_same_name_in_pool(pool, &target, url)?;
+        }
+        print_header("Cloning:", extract_repo_display_name(url));
+    }
     println!();
 
-    let context = ProfileContext::new(target.clone(), Some(url.to_string()));
-    let Some(selected) = resolve_profile_with_context(profile_name, &context)? else {
+    let pinned = if profile_name.is_none() {
+        crate::profile::repo_profile_name(&target)?
+    } else {
+        None
+    };
+    let match_url = if config.clone.resolve_ssh_aliases {
+        resolve_ssh_alias(url)
+    } else {
+        url.to_string()
+    };
+    let context = ProfileContext::new(target.clone(), Some(match_url));
+    let Some(selected) =
+        resolve_profile_with_context(profile_name.or(pinned.as_deref()).into(), &context)?
+    else {
         return Ok(());
     };
 
-    clone_repo(url, &target)?;
+    let opts = git::CloneOptions {
+        mirror,
+        env: clone_env(&selected),
+        ..git::CloneOptions::default()
+    };
+    if let Some(marker) = &planned {
+        clone_into_planned(url, &target, &opts, marker)?;
+    } else if !existing {
+        clone_repo(url, &target, &opts)?;
+    }
 
     apply_profile(&target, &selected)?;
 
-    let config = crate::config::load()?;
     if crate::config::is_in_pool(&target, &config.pool_paths()) {
         crate::state::register_repo(&target)?;
     }
 
-    print_success(format!("Cloned to {}", target.display()));
+    if existing {
+        print_success(format!("Already cloned at {}", target.display()));
+    } else if mirror {
+        print_success(format!("Mirrored to {} (bare)", target.display()));
+    } else {
+        print_success(format!("Cloned to {}", target.display()));
+    }
     print_success(format!(
         "Applied profile '{}' ({})",
         selected.name,
@@ -47,6 +133,199 @@ pub fn run(url: &str, path: Option<PathBuf>, profile_name: Option<&str>) -> Resu
     Ok(())
 }
 
+/// Rewrites the URL using the longest matching `[from, to]` prefix rule.
+/// Returns the URL unchanged if no rule matches.
+fn apply_url_rewrites(url: &str, rules: &[(String, String)]) -> String {
+    rules
+        .iter()
+        .filter(|(from, _)| !from.is_empty() && url.starts_with(from.as_str()))
+        .max_by_key(|(from, _)| from.len())
+        .map_or_else(
+            || url.to_string(),
+            |(from, to)| format!("{to}{}", &url[from.len()..]),
+        )
+}
+
+/// Resolves an SSH host alias in `url` from `~/.ssh/config`, for includeIf URL matching only;
+/// the clone itself keeps using the alias so ssh applies its settings.
+fn resolve_ssh_alias(url: &str) -> String {
+    let ssh_config = dirs::home_dir()
+        .and_then(|home| fs::read_to_string(home.join(".ssh/config")).ok())
+        .unwrap_or_default();
+    let resolved = git::resolve_ssh_alias(url, &ssh_config);
+    if resolved != url {
+        eprint_debug(format!("Resolved SSH host alias {url} → {resolved}"));
+    }
+    resolved
+}
+
+/// Explains why cloning into an existing pool repository is refused, depending on its origin
+fn report_pool_collision(target: &Path, url: &str) -> Result<()> {
+    let origin = git::remotes(target)?
+        .into_iter()
+        .find(|r| r.name == "origin")
+        .map(|r| r.fetch_url);
+
+    match origin {
+        Some(origin) if urls_match(&origin, url) => {
+            eprint_warning(format!("Already cloned at {}", format_home_path(target)));
+            eprint_hint("Use --apply-to-existing to only apply a profile");
+        }
+        Some(origin) => {
+            eprint_warning(format!(
+                "{} is a clone of a different repository ({origin})",
+                format_home_path(target)
+            ));
+            eprint_hint(format!(
+                "Pass a different target path, e.g. `yarm clone {url} {} --into`",
+                extract_repo_display_name(url)
+            ));
+        }
+        None => {
+            eprint_warning(format!(
+                "Target directory '{}' already exists",
+                format_home_path(target)
+            ));
+        }
+    }
+
+    Err(SilentExit(1).into())
+}
+
+/// Warns if a repository with the same directory name already exists elsewhere in the pool
+fn warn_same_name_in_pool(pool: &Path, target: &Path, url: &str) -> Result<()> {
+    let Some(name) = target.file_name() else {
+        return Ok(());
+    };
+
+    let state = crate::state::load()?;
+    let pool = pool.canonicalize().unwrap_or_else(|_| pool.to_path_buf());
+    let same_name = repos_named(&state.repositories, &pool, name);
+
+    if let Some(other) = same_name.first() {
+        eprint_warning(format!(
+            "A repository named '{}' already exists in this pool at {}",
+            name.to_string_lossy(),
+            format_home_path(other)
+        ));
+        eprint_hint(format!(
+            "Consider cloning to an owner/repo path, e.g. `yarm clone {url} {} --into`",
+            extract_repo_display_name(url)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Refuses to clone if a scanned repository elsewhere already has `url` as its origin
+fn refuse_existing_clone(url: &str, target: &Path) -> Result<()> {
+    let state = crate::state::load()?;
+    let Some(existing) = find_existing_clone(
+        url,
+        &state.repositories,
+        &state.remote_urls,
+        target,
+        git::origin_url,
+    ) else {
+        return Ok(());
+    };
+
+    eprint_warning(format!("Already cloned at {}", format_home_path(&existing)));
+    let name = existing
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    eprint_hint(format!(
+        "Use `ye {name}` to go there, or pass --force to clone it again"
+    ));
+    Err(SilentExit(1).into())
+}
+
+/// Finds a repository other than `target` whose origin matches `url`.
+/// Origins cached in the state (by `find --remote`) are checked first; otherwise only
+/// repositories named like the clone target are read with `read_origin`, to stay fast.
+fn find_existing_clone(
+    url: &str,
+    repos: &[PathBuf],
+    cached: &BTreeMap<PathBuf, String>,
+    target: &Path,
+    read_origin: impl Fn(&Path) -> Option<String>,
+) -> Option<PathBuf> {
+    let wanted = normalize_clone_url(url);
+    let name = derive_target_from_url(url);
+    let target = target
+        .canonicalize()
+        .unwrap_or_else(|_| target.to_path_buf());
+
+    repos
+        .iter()
+        .filter(|repo| **repo != target)
+        .find(|repo| match cached.get(*repo) {
+            Some(origin) => normalize_clone_url(origin) == wanted,
+            None => {
+                repo.file_name().is_some_and(|n| {
+                    n.to_string_lossy()
+                        .eq_ignore_ascii_case(&name.to_string_lossy())
+                }) && read_origin(repo).is_some_and(|o| normalize_clone_url(&o) == wanted)
+            }
+        })
+        .cloned()
+}
+
+/// Returns the repositories in `pool` whose directory name equals `name`
+fn repos_named<'a>(repos: &'a [PathBuf], pool: &Path, name: &OsStr) -> Vec<&'a PathBuf> {
+    repos
+        .iter()
+        .filter(|r| r.starts_with(pool) && r.file_name() == Some(name))
+        .collect()
+}
+
+/// Errors unless the repository at `target` has an `origin` remote pointing to `url`
+fn ensure_same_origin(target: &Path, url: &str) -> Result<()> {
+    let remotes = git::remotes(target)?;
+    let Some(origin) = remotes.iter().find(|r| r.name == "origin") else {
+        anyhow::bail!(
+            "Target directory '{}' is a repository without an origin remote",
+            target.display()
+        );
+    };
+
+    if !urls_match(&origin.fetch_url, url) {
+        anyhow::bail!(
+            "Target directory '{}' is a clone of a different repository ({})",
+            target.display(),
+            origin.fetch_url
+        );
+    }
+
+    Ok(())
+}
+
+/// Compares two remote URLs, ignoring a trailing slash or `.git` suffix and host case
+fn urls_match(a: &str, b: &str) -> bool {
+    normalize_clone_url(a) == normalize_clone_url(b)
+}
+
+/// Normalizes a remote URL to `host/path` without a trailing slash or `.git` suffix,
+/// so SSH and HTTPS URLs of the same repository compare equal.
+/// Local paths are only trimmed.
+fn normalize_clone_url(url: &str) -> String {
+    let trim = |path: &str| {
+        path.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_string()
+    };
+
+    match git::parse_git_url(url.trim()) {
+        Some(parsed) => format!(
+            "{}/{}",
+            parsed.host,
+            trim(parsed.path.trim_start_matches('/'))
+        ),
+        None => trim(url.trim()),
+    }
+}
+
 /// Extracts repo name from URL for display
 fn extract_repo_display_name(url: &str) -> String {
     let url = url.trim_end_matches(".git");
@@ -72,6 +351,13 @@ fn extract_repo_display_name(url: &str) -> String {
     url.to_string()
 }
 
+/// Appends `.git` to a derived target for bare mirrors, keeping any dots in the name
+fn mirror_target(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".git");
+    target.with_file_name(name)
+}
+
 /// Derives target directory from URL
 fn derive_target_from_url(url: &str) -> PathBuf {
     let url = url.trim_end_matches(".git");
@@ -85,112 +371,87 @@ fn derive_target_from_url(url: &str) -> PathBuf {
     PathBuf::from(repo_name)
 }
 
-/// Clones the repository with progress spinner showing git stages
-fn clone_repo(url: &str, target: &Path) -> Result<()> {
-    let spinner = crate::term::spinner("Cloning repository...");
-
-    let mut child = Command::new("git")
-        .args(["clone", "--progress", url, &target.to_string_lossy()])
-        .stdout(Stdio::null())
-        .stderr(Stdio::piped())
-        .spawn()
-        .context("Failed to execute git clone")?;
-
-    // Channel to collect stderr for error reporting
-    let (tx, rx) = mpsc::channel();
-
-    // Read stderr in a separate thread to avoid blocking
-    // Git uses \r for progress updates (same-line overwrites), so we read raw and split on \r or \n
-    let mut stderr = child.stderr.take().expect("stderr was piped");
-    let spinner_clone = spinner.clone();
-    let reader_thread = thread::spawn(move || {
-        let mut all_output = String::new();
-        let mut buf = [0u8; 256];
-        let mut line_buf = String::new();
-
-        while let Ok(n) = stderr.read(&mut buf) {
-            if n == 0 {
-                break;
-            }
-            let chunk = String::from_utf8_lossy(&buf[..n]);
-            all_output.push_str(&chunk);
-
-            for c in chunk.chars() {
-                if c == '\r' || c == '\n' {
-                    if !line_buf.is_empty() {
-                        // Parse git progress output and update spinner
-                        if let Some((stage, percent)) = parse_git_progress(&line_buf) {
-                            let msg = match percent {
-                                Some(p) => format!("Cloning repository [{stage}: {p}%]..."),
-                                None => format!("Cloning repository [{stage}]..."),
-                            };
-                            spinner_clone.set_message(msg);
-                        }
-                        line_buf.clear();
-                    }
-                } else {
-                    line_buf.push(c);
-                }
-            }
-        }
-
-        let _ = tx.send(all_output);
-    });
-
-    let status = child.wait().context("Failed to wait for git clone")?;
-
-    let _ = reader_thread.join();
-    let stderr_output = rx.recv().unwrap_or_default();
-
-    spinner.finish_and_clear();
+/// Environment for the clone subprocess derived from the selected profile,
+/// so clones over a per-identity SSH key authenticate with the profile's `core.sshCommand`
+fn clone_env(profile: &Profile) -> Vec<(String, String)> {
+    let Some(ssh_command) = &profile.ssh_command else {
+        return Vec::new();
+    };
+    eprint_debug(format!(
+        "Cloning with core.sshCommand from profile '{}': {ssh_command}",
+        profile.name
+    ));
+    vec![("GIT_SSH_COMMAND".to_string(), ssh_command.clone())]
+}
 
-    if !status.success() {
-        anyhow::bail!("{}", git::format_error("Clone failed", &stderr_output));
+/// Clones the repository with progress spinner showing git stages
+fn clone_repo(url: &str, target: &Path, opts: &git::CloneOptions) -> Result<()> {
+    let guard = CloneTarget::new(target);
+
+    // git receives Ctrl-C from the terminal as well; keep running until it has exited
+    let interrupted = Arc::new(AtomicBool::new(false));
+    // Unregistering a handler leaves SIGINT ignored instead of restoring the default action,
+    // so a dormant default-action handler is armed once the clone is done; Ctrl-C then still
+    // terminates the rest of the run (profile application, state registration)
+    let restore_default = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register_conditional_default(SIGINT, Arc::clone(&restore_default))
+        .context("Failed to install Ctrl-C handler")?;
+    let sigint = signal_hook::flag::register(SIGINT, Arc::clone(&interrupted))
+        .context("Failed to install Ctrl-C handler")?;
+
+    let result = git::clone(url, target, opts);
+    signal_hook::low_level::unregister(sigint);
+    restore_default.store(true, Ordering::Relaxed);
+
+    if result.is_err() || interrupted.load(Ordering::Relaxed) {
+        guard.cleanup();
     }
-
-    Ok(())
+    if interrupted.load(Ordering::Relaxed) {
+        return Err(SilentExit(130).into());
+    }
+    result
 }
 
-/// Parses git progress output to extract the current stage and optional percentage
-fn parse_git_progress(line: &str) -> Option<(&str, Option<u8>)> {
-    // Strip optional "remote:" prefix, then parse "Stage: NN%" format
-    let line = line
-        .trim()
-        .strip_prefix("remote:")
-        .unwrap_or(line.trim())
-        .trim();
-
-    let colon_pos = line.find(':')?;
-    let stage = line[..colon_pos].trim();
-
-    if is_progress_stage(stage) {
-        let percent = extract_percent(&line[colon_pos + 1..]);
-        Some((stage, percent))
-    } else {
-        None
-    }
+/// Clones into a directory that only holds a `.yarm` marker, which git would refuse
+/// as non-empty. The marker is moved aside for the clone and restored afterwards.
+fn clone_into_planned(
+    url: &str,
+    target: &Path,
+    opts: &git::CloneOptions,
+    marker: &str,
+) -> Result<()> {
+    let marker_path = target.join(crate::config::MARKER_FILE);
+    fs::remove_file(&marker_path).context("Failed to move marker file aside")?;
+
+    let result = clone_repo(url, target, opts);
+
+    fs::create_dir_all(target)
+        .and_then(|()| fs::write(&marker_path, marker))
+        .context("Failed to restore marker file")?;
+    result
 }
 
-/// Checks if the given string is a recognized git progress stage
-fn is_progress_stage(stage: &str) -> bool {
-    matches!(
-        stage,
-        "Cloning into"
-            | "Enumerating objects"
-            | "Counting objects"
-            | "Compressing objects"
-            | "Receiving objects"
-            | "Resolving deltas"
-            | "Updating files"
-    )
+/// Clone destination, remembering whether the clone is what created it
+struct CloneTarget<'a> {
+    path: &'a Path,
+    created: bool,
 }
 
-/// Extracts percentage from a string like " 45% (55/123)" or "100% (50/50), done."
-fn extract_percent(s: &str) -> Option<u8> {
-    let s = s.trim();
-    let percent_pos = s.find('%')?;
-    let num_str = s[..percent_pos].trim();
-    num_str.parse().ok()
+impl<'a> CloneTarget<'a> {
+    /// Records whether `path` exists before cloning
+    fn new(path: &'a Path) -> Self {
+        Self {
+            path,
+            created: !path.exists(),
+        }
+    }
+
+    /// Removes a partially cloned target, but never a directory that existed beforehand
+    fn cleanup(&self) {
+        if self.created && self.path.exists() {
+            let _ = fs::remove_dir_all(self.path);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -198,84 +459,262 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_derive_target_from_url_https() {
+    fn test_unknown_profile_fails_before_cloning() {
+        let target = std::env::temp_dir().join("yarm-test-clone-unknown-profile");
+        let _ = fs::remove_dir_all(&target);
+
+        let err = run(
+            "/nonexistent/source/repo",
+            Some(target.clone()),
+            Some("yarm-test-no-such-profile"),
+            None,
+            false,
+            false,
+            true,
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("not found"));
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_clone_env_from_profile_ssh_command() {
+        let dir = std::env::temp_dir().join("yarm-test-clone-env");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let with_key = dir.join("work.gitconfig");
+        fs::write(
+            &with_key,
+            "[user]\n\temail = w@co.com\n[core]\n\tsshCommand = ssh -i ~/.ssh/id_work\n",
+        )
+        .unwrap();
+        let profile = crate::profile::reload_profile(&with_key).unwrap().unwrap();
         assert_eq!(
-            derive_target_from_url("https://github.com/owner/repo.git"),
-            PathBuf::from("repo")
+            clone_env(&profile),
+            [(
+                "GIT_SSH_COMMAND".to_string(),
+                "ssh -i ~/.ssh/id_work".to_string()
+            )]
         );
+
+        let without_key = dir.join("home.gitconfig");
+        fs::write(&without_key, "[user]\n\temail = me@home.org\n").unwrap();
+        let profile = crate::profile::reload_profile(&without_key)
+            .unwrap()
+            .unwrap();
+        assert!(clone_env(&profile).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn test_derive_target_from_url_ssh() {
+    fn test_clone_target_cleanup_created() {
+        let dir = std::env::temp_dir().join("yarm-test-clone-created");
+        let _ = fs::remove_dir_all(&dir);
+
+        let guard = CloneTarget::new(&dir);
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        guard.cleanup();
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_clone_target_cleanup_keeps_existing() {
+        let dir = std::env::temp_dir().join("yarm-test-clone-existing");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("notes.txt"), "keep me").unwrap();
+
+        let guard = CloneTarget::new(&dir);
+        guard.cleanup();
+        assert!(dir.join("notes.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn rules(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(from, to)| ((*from).to_string(), (*to).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_url_rewrites_prefix() {
+        let rules = rules(&[("https://github.com/", "git@github.com:")]);
         assert_eq!(
-            derive_target_from_url("git@github.com:owner/repo.git"),
-            PathBuf::from("repo")
+            apply_url_rewrites("https://github.com/owner/repo.git", &rules),
+            "git@github.com:owner/repo.git"
         );
     }
 
     #[test]
-    fn test_derive_target_from_url_no_extension() {
+    fn test_apply_url_rewrites_longest_prefix_wins() {
+        let rules = rules(&[
+            ("https://github.com/", "git@github.com:"),
+            ("https://github.com/work-org/", "git@github-work:work-org/"),
+        ]);
         assert_eq!(
-            derive_target_from_url("https://github.com/owner/repo"),
-            PathBuf::from("repo")
+            apply_url_rewrites("https://github.com/work-org/repo", &rules),
+            "git@github-work:work-org/repo"
         );
     }
 
     #[test]
-    fn test_extract_repo_display_name_https() {
+    fn test_apply_url_rewrites_no_match() {
+        let rules = rules(&[("https://github.com/", "git@github.com:")]);
         assert_eq!(
-            extract_repo_display_name("https://github.com/dmnq-f/yarm.git"),
-            "dmnq-f/yarm"
+            apply_url_rewrites("https://gitlab.com/owner/repo.git", &rules),
+            "https://gitlab.com/owner/repo.git"
+        );
+        assert_eq!(
+            apply_url_rewrites("git@github.com:o/r", &[]),
+            "git@github.com:o/r"
         );
     }
 
     #[test]
-    fn test_extract_repo_display_name_ssh() {
+    fn test_repos_named_in_pool() {
+        let repos = vec![
+            PathBuf::from("/pool/alice/utils"),
+            PathBuf::from("/pool/tools"),
+            PathBuf::from("/other/utils"),
+        ];
+        let found = repos_named(&repos, Path::new("/pool"), OsStr::new("utils"));
+        assert_eq!(found, vec![&PathBuf::from("/pool/alice/utils")]);
+        assert!(repos_named(&repos, Path::new("/pool"), OsStr::new("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_urls_match_ignores_suffix_and_host_case() {
+        assert!(urls_match(
+            "https://github.com/owner/repo.git",
+            "https://GitHub.com/owner/repo/"
+        ));
+        assert!(urls_match(
+            "git@github.com:owner/repo.git",
+            "https://github.com/owner/repo"
+        ));
+        assert!(urls_match("/srv/git/repo.git", "/srv/git/repo"));
+    }
+
+    #[test]
+    fn test_normalize_clone_url() {
+        for url in [
+            "git@github.com:owner/repo.git",
+            "ssh://git@GitHub.com:22/owner/repo",
+            "https://github.com/owner/repo.git/",
+            "https://user@github.com/owner/repo",
+        ] {
+            assert_eq!(normalize_clone_url(url), "github.com/owner/repo", "{url}");
+        }
+        assert_eq!(normalize_clone_url("/srv/git/repo.git"), "/srv/git/repo");
+    }
+
+    #[test]
+    fn test_find_existing_clone() {
+        let repos = vec![
+            PathBuf::from("/pools/work/repo"),
+            PathBuf::from("/pools/oss/cached"),
+            PathBuf::from("/pools/oss/other"),
+        ];
+        let cached = BTreeMap::from([(
+            PathBuf::from("/pools/oss/cached"),
+            "https://github.com/owner/cached.git".to_string(),
+        )]);
+        let read_origin = |repo: &Path| {
+            (repo == Path::new("/pools/work/repo"))
+                .then(|| "git@github.com:owner/repo.git".to_string())
+        };
+        let find = |url: &str, target: &str| {
+            find_existing_clone(url, &repos, &cached, Path::new(target), read_origin)
+        };
+
         assert_eq!(
-            extract_repo_display_name("git@github.com:dmnq-f/yarm.git"),
-            "dmnq-f/yarm"
+            find("https://github.com/owner/repo", "/new/repo"),
+            Some(PathBuf::from("/pools/work/repo"))
+        );
+        // Cached origins match regardless of the directory name
+        assert_eq!(
+            find("git@github.com:owner/cached", "/new/renamed"),
+            Some(PathBuf::from("/pools/oss/cached"))
+        );
+        assert_eq!(find("https://github.com/someone/repo", "/new/repo"), None);
+        // The clone target itself is not a duplicate
+        assert_eq!(
+            find("https://github.com/owner/repo", "/pools/work/repo"),
+            None
         );
     }
 
     #[test]
-    fn test_parse_git_progress_remote_stage() {
+    fn test_urls_match_different_repos() {
+        assert!(!urls_match(
+            "https://github.com/owner/repo.git",
+            "https://github.com/owner/other.git"
+        ));
+        assert!(!urls_match(
+            "https://github.com/owner/repo.git",
+            "https://gitlab.com/owner/repo.git"
+        ));
+    }
+
+    #[test]
+    fn test_derive_target_from_url_https() {
         assert_eq!(
-            parse_git_progress("remote: Enumerating objects: 123, done."),
-            Some(("Enumerating objects", None))
+            derive_target_from_url("https://github.com/owner/repo.git"),
+            PathBuf::from("repo")
         );
     }
 
     #[test]
-    fn test_parse_git_progress_direct_stage_with_percent() {
+    fn test_derive_target_from_url_ssh() {
         assert_eq!(
-            parse_git_progress("Receiving objects:  45% (55/123)"),
-            Some(("Receiving objects", Some(45)))
+            derive_target_from_url("git@github.com:owner/repo.git"),
+            PathBuf::from("repo")
         );
     }
 
     #[test]
-    fn test_parse_git_progress_resolving_deltas_complete() {
+    fn test_derive_target_from_url_no_extension() {
         assert_eq!(
-            parse_git_progress("Resolving deltas: 100% (50/50), done."),
-            Some(("Resolving deltas", Some(100)))
+            derive_target_from_url("https://github.com/owner/repo"),
+            PathBuf::from("repo")
         );
     }
 
     #[test]
-    fn test_parse_git_progress_remote_with_percent() {
+    fn test_mirror_target_keeps_dotted_names() {
+        let target = |url| mirror_target(&derive_target_from_url(url));
+        assert_eq!(
+            target("https://github.com/socketio/socket.io.git"),
+            PathBuf::from("socket.io.git")
+        );
+        assert_eq!(
+            target("git@github.com:owner/foo.js"),
+            PathBuf::from("foo.js.git")
+        );
         assert_eq!(
-            parse_git_progress("remote: Counting objects: 75% (90/120)"),
-            Some(("Counting objects", Some(75)))
+            target("https://github.com/owner/repo.git"),
+            PathBuf::from("repo.git")
         );
     }
 
     #[test]
-    fn test_parse_git_progress_non_stage_line() {
-        assert_eq!(parse_git_progress("fatal: repository not found"), None);
+    fn test_extract_repo_display_name_https() {
+        assert_eq!(
+            extract_repo_display_name("https://github.com/dmnq-f/yarm.git"),
+            "dmnq-f/yarm"
+        );
     }
 
     #[test]
-    fn test_parse_git_progress_empty_line() {
-        assert_eq!(parse_git_progress(""), None);
+    fn test_extract_repo_display_name_ssh() {
+        assert_eq!(
+            extract_repo_display_name("git@github.com:dmnq-f/yarm.git"),
+            "dmnq-f/yarm"
+        );
     }
 }
@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap_complete::Shell;
+
+use crate::term::{
+    format_home_path, is_interactive, print_header, print_hint, print_success, print_warning,
+    prompt_confirm,
+};
+
+/// A file written by `completions --install`
+#[derive(Debug)]
+struct InstallFile {
+    label: &'static str,
+    path: PathBuf,
+    contents: String,
+}
+
+/// Where a shell's completion script and `ye` function are installed, and the
+/// line the shell's rc file needs to load them (if the location is not loaded automatically)
+#[derive(Debug)]
+struct InstallPlan {
+    files: Vec<InstallFile>,
+    rc_file: Option<PathBuf>,
+    rc_lines: Vec<String>,
+}
+
+/// Installs the completion script and the `ye` function for `shell` into the
+/// shell's conventional locations, asking before replacing files that differ.
+pub fn install(shell: Shell, completion: String, functions: String) -> Result<()> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    let plan = install_plan(shell, &home, completion, functions)?;
+
+    print_header("Shell:", shell);
+    println!();
+
+    for file in &plan.files {
+        install_file(file)?;
+    }
+
+    let Some(rc_file) = &plan.rc_file else {
+        return Ok(());
+    };
+    let rc = fs::read_to_string(rc_file).unwrap_or_default();
+    let missing: Vec<_> = plan
+        .rc_lines
+        .iter()
+        .filter(|line| !rc.lines().any(|l| l.trim() == line.as_str()))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    print_hint(format!("Add to {}:", format_home_path(rc_file)));
+    for line in missing {
+        println!("    {line}");
+    }
+
+    Ok(())
+}
+
+fn install_file(file: &InstallFile) -> Result<()> {
+    let display = format_home_path(&file.path);
+
+    if let Ok(existing) = fs::read_to_string(&file.path) {
+        if existing == file.contents {
+            print_success(format!("Already up to date: {display}"));
+            return Ok(());
+        }
+        if !is_interactive() {
+            anyhow::bail!("{display} already exists; run interactively to confirm overwriting it");
+        }
+        let prompt = format!("Overwrite the existing {} at {display}?", file.label);
+        if prompt_confirm(&prompt, false)? != Some(true) {
+            print_warning(format!("Kept existing {display}"));
+            return Ok(());
+        }
+    }
+
+    if let Some(parent) = file.path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&file.path, &file.contents)
+        .with_context(|| format!("Failed to write {}", file.path.display()))?;
+    print_success(format!("Installed {} to {display}", file.label));
+
+    Ok(())
+}
+
+fn install_plan(
+    shell: Shell,
+    home: &Path,
+    completion: String,
+    functions: String,
+) -> Result<InstallPlan> {
+    let completion_file = |path: PathBuf| InstallFile {
+        label: "completions",
+        path,
+        contents: completion,
+    };
+    let functions_file = |path: PathBuf| InstallFile {
+        label: "ye function",
+        path,
+        contents: functions,
+    };
+    let data_dir = home.join(".local/share/yarm");
+
+    Ok(match shell {
+        Shell::Bash => {
+            let ye = data_dir.join("ye.bash");
+            InstallPlan {
+                rc_lines: vec![format!("source {}", format_home_path(&ye))],
+                files: vec![
+                    completion_file(home.join(".local/share/bash-completion/completions/yarm")),
+                    functions_file(ye),
+                ],
+                rc_file: Some(home.join(".bashrc")),
+            }
+        }
+        Shell::Zsh => {
+            let ye = data_dir.join("ye.zsh");
+            InstallPlan {
+                rc_lines: vec![
+                    "fpath=(~/.zfunc $fpath)".to_string(),
+                    format!("source {}", format_home_path(&ye)),
+                ],
+                files: vec![
+                    completion_file(home.join(".zfunc/_yarm")),
+                    functions_file(ye),
+                ],
+                rc_file: Some(home.join(".zshrc")),
+            }
+        }
+        // fish loads both locations on its own
+        Shell::Fish => InstallPlan {
+            files: vec![
+                completion_file(home.join(".config/fish/completions/yarm.fish")),
+                functions_file(home.join(".config/fish/conf.d/ye.fish")),
+            ],
+            rc_file: None,
+            rc_lines: Vec::new(),
+        },
+        _ => anyhow::bail!(
+            "--install supports bash, zsh, and fish; for {shell}, load the output of `yarm completions {shell}` from your profile"
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(shell: Shell) -> Result<InstallPlan> {
+        install_plan(
+            shell,
+            Path::new("/home/u"),
+            "completion".to_string(),
+            "functions".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_install_plan_locations() {
+        let bash = plan(Shell::Bash).unwrap();
+        assert_eq!(
+            bash.files[0].path,
+            Path::new("/home/u/.local/share/bash-completion/completions/yarm")
+        );
+        assert_eq!(bash.files[0].contents, "completion");
+        assert_eq!(
+            bash.files[1].path,
+            Path::new("/home/u/.local/share/yarm/ye.bash")
+        );
+        assert_eq!(bash.files[1].contents, "functions");
+        assert_eq!(bash.rc_file.as_deref(), Some(Path::new("/home/u/.bashrc")));
+
+        let zsh = plan(Shell::Zsh).unwrap();
+        assert_eq!(zsh.files[0].path, Path::new("/home/u/.zfunc/_yarm"));
+        assert_eq!(zsh.rc_lines[0], "fpath=(~/.zfunc $fpath)");
+
+        let fish = plan(Shell::Fish).unwrap();
+        assert_eq!(
+            fish.files[1].path,
+            Path::new("/home/u/.config/fish/conf.d/ye.fish")
+        );
+        assert!(fish.rc_file.is_none());
+    }
+
+    #[test]
+    fn test_install_plan_unsupported_shell() {
+        let err = plan(Shell::PowerShell).unwrap_err();
+        assert!(err.to_string().contains("powershell"));
+    }
+}
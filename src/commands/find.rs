@@ -1,11 +1,26 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::{Context, Result, bail};
 
 use crate::term::{SilentExit, eprint_hint, eprint_warning, format_home_path};
 
-/// Executes the find command flow
-pub fn run(repo: Option<&str>, pool: Option<&str>) -> Result<()> {
+/// How `find --all` groups the repositories it prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// The pool containing the repository
+    Pool,
+    /// The host of the repository's origin remote
+    Host,
+}
+
+/// Executes the find command flow.
+/// An empty `pool` name (`--pool` without a value) selects the default pool.
+/// With `count`, only the number of matching repositories is printed, always exiting 0.
+/// With `remote`, `repo` is matched against the repositories' origin remote URLs instead.
+pub fn run(repo: Option<&str>, pool: Option<&str>, count: bool, remote: bool) -> Result<()> {
     if let Some(name) = pool {
         return find_pool(name);
     }
@@ -14,6 +29,22 @@ pub fn run(repo: Option<&str>, pool: Option<&str>) -> Result<()> {
         anyhow::bail!("Provide a repository name or use --pool <name>");
     };
 
+    if remote {
+        return find_by_remote(repo, count);
+    }
+
+    if count {
+        let state = crate::state::load()?;
+        println!("{}", match_count(&state.repositories, repo));
+        return Ok(());
+    }
+
+    // An explicit path to a repository needs no name matching
+    if let Some(path) = existing_repo_path(repo) {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
     let state = crate::state::load()?;
 
     if state.repositories.is_empty() {
@@ -22,40 +53,410 @@ pub fn run(repo: Option<&str>, pool: Option<&str>) -> Result<()> {
         return Err(SilentExit(1).into());
     }
 
-    let matches = find_matches(&state.repositories, repo);
+    let matches = prefer_pinned(find_matches(&state.repositories, repo), &state.pinned_repos);
 
-    match matches.len() {
-        0 => {
-            eprint_warning(format!("No repository matching '{repo}'"));
-            if let Some(suggestion) = find_suggestion(&state.repositories, repo) {
-                eprint_hint(format!("Did you mean '{suggestion}'?"));
-            }
-            Err(SilentExit(1).into())
+    if matches.is_empty() {
+        eprint_warning(format!("No repository matching '{repo}'"));
+        if let Some(suggestion) = find_suggestion(&state.repositories, repo) {
+            eprint_hint(format!("Did you mean '{suggestion}'?"));
         }
-        1 => {
-            println!("{}", matches[0].display());
-            Ok(())
+        return Err(SilentExit(1).into());
+    }
+
+    print_single_match(&matches, repo)
+}
+
+/// Resolves an ambiguous match to the single pinned repository among the candidates, if any
+fn prefer_pinned(matches: Vec<PathBuf>, pinned: &[PathBuf]) -> Vec<PathBuf> {
+    if matches.len() < 2 {
+        return matches;
+    }
+    match matches
+        .iter()
+        .filter(|m| pinned.contains(m))
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [only] => vec![(*only).clone()],
+        _ => matches,
+    }
+}
+
+/// Prints the pinned repositories in the order they were pinned
+pub fn run_pinned() -> Result<()> {
+    let state = crate::state::load()?;
+    if state.pinned_repos.is_empty() {
+        eprint_warning("No pinned repositories");
+        eprint_hint("Pin one with `yarm pin <repo>`");
+        return Err(SilentExit(1).into());
+    }
+
+    for repo in &state.pinned_repos {
+        println!("{}", repo.display());
+    }
+    Ok(())
+}
+
+/// Prints the only match, or lists all candidates of an ambiguous match
+fn print_single_match(matches: &[PathBuf], query: &str) -> Result<()> {
+    if let [only] = matches {
+        println!("{}", only.display());
+        return Ok(());
+    }
+
+    eprint_warning(format!(
+        "Ambiguous match '{query}', found {} repositories:",
+        matches.len()
+    ));
+    let pools = crate::config::load()?.pool_paths();
+    for m in matches {
+        eprintln!("  {}", pool_relative_display(m, &pools));
+    }
+    Err(SilentExit(1).into())
+}
+
+/// Finds repositories whose origin remote URL contains `fragment` (e.g. `owner/repo`).
+/// URLs are read with git once per repository and cached in the state until the next scan.
+fn find_by_remote(fragment: &str, count: bool) -> Result<()> {
+    let mut state = crate::state::load()?;
+
+    if state.repositories.is_empty() && !count {
+        eprint_warning("No repositories in state");
+        eprint_hint("Run `yarm scan` to discover repositories");
+        return Err(SilentExit(1).into());
+    }
+
+    let uncached: Vec<PathBuf> = state
+        .repositories
+        .iter()
+        .filter(|r| !state.remote_urls.contains_key(*r))
+        .cloned()
+        .collect();
+    if !uncached.is_empty() {
+        let urls = origin_urls(&uncached);
+        state.remote_urls.extend(uncached.into_iter().zip(urls));
+        if let Err(e) = crate::state::save(&state) {
+            crate::term::eprint_debug(format!("Could not cache remote URLs: {e}"));
         }
-        _ => {
-            eprint_warning(format!(
-                "Ambiguous match '{repo}', found {} repositories:",
-                matches.len()
-            ));
-            for m in &matches {
-                eprintln!("  {}", format_home_path(m));
+    }
+
+    let matches = remote_matches(&state.repositories, &state.remote_urls, fragment);
+
+    if count {
+        println!("{}", matches.len());
+        return Ok(());
+    }
+    if matches.is_empty() {
+        eprint_warning(format!("No repository with a remote matching '{fragment}'"));
+        return Err(SilentExit(1).into());
+    }
+
+    print_single_match(&matches, fragment)
+}
+
+/// Reads the origin (or else the first) remote URL of each repository concurrently,
+/// in the order of `repos`; empty for repositories without remotes
+fn origin_urls(repos: &[PathBuf]) -> Vec<String> {
+    let total = repos.len();
+    let done = AtomicUsize::new(0);
+    let spinner = crate::term::spinner("Reading remote URLs...");
+    let urls = crate::git::batch(
+        repos,
+        |repo| {
+            let remotes = crate::git::remotes(repo).unwrap_or_default();
+            remotes
+                .iter()
+                .find(|r| r.name == "origin")
+                .or_else(|| remotes.first())
+                .map(|r| r.fetch_url.clone())
+                .unwrap_or_default()
+        },
+        || {
+            let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+            spinner.set_message(format!("Reading remote URLs ({n}/{total})..."));
+        },
+    );
+    spinner.finish_and_clear();
+    urls
+}
+
+/// Matches a URL fragment against remote URLs, ignoring case and a trailing `.git`.
+/// URLs ending in the fragment at a path boundary win over URLs merely containing it,
+/// so `owner/repo` does not also match `owner/repo-docs`.
+fn remote_matches(
+    repos: &[PathBuf],
+    urls: &BTreeMap<PathBuf, String>,
+    fragment: &str,
+) -> Vec<PathBuf> {
+    let fragment = normalize_remote(fragment);
+    if fragment.is_empty() {
+        return Vec::new();
+    }
+
+    let candidates: Vec<(&PathBuf, String)> = repos
+        .iter()
+        .filter_map(|r| Some((r, normalize_remote(urls.get(r)?))))
+        .filter(|(_, url)| url.contains(&fragment))
+        .collect();
+
+    let suffix: Vec<PathBuf> = candidates
+        .iter()
+        .filter(|(_, url)| {
+            url.strip_suffix(&fragment)
+                .is_some_and(|rest| rest.is_empty() || rest.ends_with(['/', ':']))
+        })
+        .map(|(r, _)| (*r).clone())
+        .collect();
+
+    if suffix.is_empty() {
+        candidates.into_iter().map(|(r, _)| r.clone()).collect()
+    } else {
+        suffix
+    }
+}
+
+/// Lowercases a remote URL or fragment and strips surrounding slashes and a `.git` suffix
+fn normalize_remote(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix(".git")
+        .unwrap_or(url)
+        .trim_matches('/')
+        .to_lowercase()
+}
+
+/// Formats a repository path as `pool: relative/path`, or tilde-relative if outside all pools
+fn pool_relative_display(repo: &Path, pools: &[PathBuf]) -> String {
+    crate::config::assign_pool(repo, pools)
+        .and_then(|pool| {
+            let name = pool.file_name()?.to_string_lossy();
+            let rel = repo.strip_prefix(pool).ok()?;
+            Some(format!("{name}: {}", rel.display()))
+        })
+        .unwrap_or_else(|| format_home_path(repo))
+}
+
+/// Prints every repository in state, either as plain paths or as a JSON array
+/// of `{name, path, pool}` objects.
+/// With `email` or `profile`, only repositories whose local `user.email` matches are printed.
+pub fn run_all(
+    json: bool,
+    email: Option<&str>,
+    profile: Option<&str>,
+    group_by: Option<GroupBy>,
+) -> Result<()> {
+    let state = crate::state::load()?;
+    let mut repos = state.repositories.clone();
+    repos.sort();
+
+    if let Some(email) = identity_email(email, profile)? {
+        let emails = local_emails(&repos);
+        repos = repos
+            .into_iter()
+            .zip(emails)
+            .filter(|(_, e)| e.as_deref().is_some_and(|e| e.eq_ignore_ascii_case(&email)))
+            .map(|(repo, _)| repo)
+            .collect();
+        if repos.is_empty() && !json {
+            eprint_warning(format!("No repository uses the email '{email}'"));
+            return Err(SilentExit(1).into());
+        }
+    }
+
+    let pools = crate::config::load_unchecked()?.pool_paths();
+    let groups = group_by.map(|group_by| match group_by {
+        GroupBy::Pool => group_repos(&repos, |repo, _| pool_name(repo, &pools)),
+        GroupBy::Host => {
+            let urls = origin_urls(&repos);
+            group_repos(&repos, |_, i| {
+                crate::git::parse_git_url(&urls[i]).map(|url| url.host.to_lowercase())
+            })
+        }
+    });
+
+    if json {
+        match &groups {
+            Some(groups) => println!("{}", groups_json(groups, &pools)),
+            None => println!("{}", repos_json(&repos, &pools)),
+        }
+        return Ok(());
+    }
+
+    if repos.is_empty() {
+        eprint_warning("No repositories in state");
+        eprint_hint("Run `yarm scan` to discover repositories");
+        return Err(SilentExit(1).into());
+    }
+
+    let Some(groups) = groups else {
+        for repo in &repos {
+            println!("{}", repo.display());
+        }
+        return Ok(());
+    };
+
+    let ungrouped = match group_by {
+        Some(GroupBy::Host) => "(no remote)",
+        _ => "(no pool)",
+    };
+    for (i, (key, members)) in groups.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let header = key.as_deref().unwrap_or(ungrouped);
+        println!("{}", console::style(header).bold());
+        for repo in members {
+            println!("  {}", repo.display());
+        }
+    }
+    Ok(())
+}
+
+/// Groups repositories by a key computed from each repository and its index in `repos`.
+/// Groups are sorted by key, with repositories without a key (`None`) last.
+fn group_repos(
+    repos: &[PathBuf],
+    key: impl Fn(&Path, usize) -> Option<String>,
+) -> Vec<(Option<String>, Vec<&PathBuf>)> {
+    let mut groups: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+    for (i, repo) in repos.iter().enumerate() {
+        match key(repo, i) {
+            Some(key) => groups.entry(key).or_default().push(repo),
+            None => ungrouped.push(repo),
+        }
+    }
+
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|(key, members)| (Some(key), members))
+        .collect();
+    if !ungrouped.is_empty() {
+        groups.push((None, ungrouped));
+    }
+    groups
+}
+
+/// Returns the basename of the pool containing `repo`
+fn pool_name(repo: &Path, pools: &[PathBuf]) -> Option<String> {
+    crate::config::assign_pool(repo, pools)
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+}
+
+/// Resolves the email to match against, either given directly or from a profile's `user.email`
+fn identity_email(email: Option<&str>, profile: Option<&str>) -> Result<Option<String>> {
+    let Some(name) = profile else {
+        return Ok(email.map(str::to_string));
+    };
+
+    let profiles = crate::profile::discover_profiles()?;
+    let profile = crate::profile::find_profile_by_name(&profiles, name)?;
+    match profile.user_email {
+        Some(email) => Ok(Some(email)),
+        None => bail!("Profile '{}' has no user.email", profile.name),
+    }
+}
+
+/// Reads the local `user.email` of each repository concurrently, in the order of `repos`
+pub(crate) fn local_emails(repos: &[PathBuf]) -> Vec<Option<String>> {
+    let total = repos.len();
+    let done = AtomicUsize::new(0);
+    let spinner = crate::term::spinner("Reading repository identities...");
+    let emails = crate::git::batch(
+        repos,
+        |repo| crate::git::get_config(repo, "user.email"),
+        || {
+            let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+            spinner.set_message(format!("Reading repository identities ({n}/{total})..."));
+        },
+    );
+    spinner.finish_and_clear();
+    emails
+}
+
+/// Formats repositories as a JSON array with their containing pool's basename (or `null`)
+fn repos_json<P: AsRef<Path>>(repos: &[P], pools: &[PathBuf]) -> String {
+    let entries: Vec<String> = repos
+        .iter()
+        .map(|repo| {
+            let repo = repo.as_ref();
+            let name = repo
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            let pool = pool_name(repo, pools)
+                .map_or_else(|| "null".to_string(), |name| json_string(&name));
+            format!(
+                "{{\"name\":{},\"path\":{},\"pool\":{pool}}}",
+                json_string(&name),
+                json_string(&repo.to_string_lossy())
+            )
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+/// Formats grouped repositories as a JSON object mapping each group key to its
+/// repositories (as in [`repos_json`]); repositories without a key are listed under `""`
+fn groups_json(groups: &[(Option<String>, Vec<&PathBuf>)], pools: &[PathBuf]) -> String {
+    let entries: Vec<String> = groups
+        .iter()
+        .map(|(key, members)| {
+            format!(
+                "{}:{}",
+                json_string(key.as_deref().unwrap_or_default()),
+                repos_json(members, pools)
+            )
+        })
+        .collect();
+    format!("{{{}}}", entries.join(","))
+}
+
+/// Quotes and escapes a string as a JSON string literal
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
             }
-            Err(SilentExit(1).into())
+            c => out.push(c),
         }
     }
+    out.push('"');
+    out
 }
 
-/// Finds a repository pool by basename and prints its path.
+/// Finds a repository pool by basename (or the default pool) and prints its path.
 fn find_pool(name: &str) -> Result<()> {
-    let path = resolve_pool(name)?;
+    let path = resolve_pool_or_default(name)?;
     println!("{}", path.display());
     Ok(())
 }
 
+/// Resolves a pool name to its path.
+/// An empty name resolves to the configured default pool.
+pub(crate) fn resolve_pool_or_default(name: &str) -> Result<PathBuf> {
+    if !name.is_empty() {
+        return resolve_pool(name);
+    }
+
+    let config = crate::config::load()?;
+    if let Some(path) = config.default_pool() {
+        return Ok(path);
+    }
+
+    eprint_warning("No default pool configured");
+    eprint_hint("Set default_pool in the [repositories] section of ~/.config/yarm.toml");
+    Err(SilentExit(1).into())
+}
+
 /// Resolves a pool name to its path.
 pub(crate) fn resolve_pool(name: &str) -> Result<PathBuf> {
     let config = crate::config::load()?;
@@ -99,21 +500,47 @@ pub(crate) fn resolve_pool(name: &str) -> Result<PathBuf> {
 }
 
 /// Prints repository basenames for shell completion (one per line).
-pub fn complete_repo_names() -> Result<()> {
+/// Without a prefix all names are printed, for shells that filter themselves.
+pub fn complete_repo_names(prefix: Option<&str>, max_results: Option<usize>) -> Result<()> {
     let state = crate::state::load()?;
-    let mut names: Vec<_> = state
-        .repositories
-        .iter()
-        .filter_map(|r| r.file_name()?.to_str().map(String::from))
-        .collect();
-    names.sort();
-    names.dedup();
-    for name in &names {
+    for name in completion_names(
+        &state.repositories,
+        &state.pinned_repos,
+        prefix,
+        max_results,
+    ) {
         println!("{name}");
     }
     Ok(())
 }
 
+/// Returns the deduplicated basenames starting with `prefix`, capped at `max_results`:
+/// those of pinned repositories first (in pin order), then the others sorted
+fn completion_names(
+    repos: &[PathBuf],
+    pinned: &[PathBuf],
+    prefix: Option<&str>,
+    max_results: Option<usize>,
+) -> Vec<String> {
+    let basenames = |repos: &[PathBuf]| -> Vec<String> {
+        repos
+            .iter()
+            .filter_map(|r| r.file_name()?.to_str().map(String::from))
+            .filter(|name| prefix.is_none_or(|p| name.starts_with(p)))
+            .collect()
+    };
+
+    let mut names = basenames(pinned);
+    let mut others = basenames(repos);
+    others.sort();
+    names.extend(others);
+
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+    names.truncate(max_results.unwrap_or(usize::MAX));
+    names
+}
+
 /// Prints pool basenames for shell completion (one per line).
 pub fn complete_pool_names() -> Result<()> {
     let config = crate::config::load()?;
@@ -125,13 +552,25 @@ pub fn complete_pool_names() -> Result<()> {
     Ok(())
 }
 
+/// Returns the canonical path if `arg` names an existing repository directory.
+fn existing_repo_path(arg: &str) -> Option<PathBuf> {
+    let path = Path::new(arg);
+    if !path.is_dir() || !crate::git::is_repository(path) {
+        return None;
+    }
+    path.canonicalize().ok()
+}
+
 /// Resolves a name-or-path argument to a repository path.
 /// Tries state-based name lookup first, then filesystem path.
 pub(crate) fn resolve_repo(name_or_path: &str) -> Result<PathBuf> {
     let state = crate::state::load()?;
 
     if !state.repositories.is_empty() {
-        let matches = find_matches(&state.repositories, name_or_path);
+        let matches = prefer_pinned(
+            find_matches(&state.repositories, name_or_path),
+            &state.pinned_repos,
+        );
         if matches.len() == 1 {
             return Ok(matches.into_iter().next().unwrap());
         }
@@ -157,9 +596,17 @@ pub(crate) fn resolve_repo(name_or_path: &str) -> Result<PathBuf> {
     bail!("'{name_or_path}' is not a known repository name or a valid git repo path");
 }
 
+/// Number of repositories a query resolves to, counting an explicit repository path as one
+fn match_count(repos: &[PathBuf], query: &str) -> usize {
+    if existing_repo_path(query).is_some() {
+        return 1;
+    }
+    find_matches(repos, query).len()
+}
+
 /// Finds repositories matching the query.
 /// Tries exact basename match first, then falls back to suffix matching.
-fn find_matches(repos: &[PathBuf], query: &str) -> Vec<PathBuf> {
+pub(crate) fn find_matches(repos: &[PathBuf], query: &str) -> Vec<PathBuf> {
     let query_lower = query.to_lowercase();
     let query_components: Vec<&str> = query.split('/').collect();
 
@@ -224,6 +671,161 @@ fn find_suggestion(repos: &[PathBuf], query: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_completion_names_prefix_and_limit() {
+        let repos = vec![
+            PathBuf::from("/a/yarm"),
+            PathBuf::from("/b/yarm"),
+            PathBuf::from("/a/yaml-tools"),
+            PathBuf::from("/a/other"),
+        ];
+        assert_eq!(
+            completion_names(&repos, &[], None, None),
+            ["other", "yaml-tools", "yarm"]
+        );
+        assert_eq!(
+            completion_names(&repos, &[], Some("ya"), None),
+            ["yaml-tools", "yarm"]
+        );
+        assert_eq!(
+            completion_names(&repos, &[], Some("ya"), Some(1)),
+            ["yaml-tools"]
+        );
+        assert!(completion_names(&repos, &[], Some("zz"), None).is_empty());
+    }
+
+    #[test]
+    fn test_completion_names_pinned_first() {
+        let repos = vec![
+            PathBuf::from("/a/yarm"),
+            PathBuf::from("/a/other"),
+            PathBuf::from("/a/zed"),
+        ];
+        let pinned = vec![PathBuf::from("/a/zed"), PathBuf::from("/a/yarm")];
+        assert_eq!(
+            completion_names(&repos, &pinned, None, None),
+            ["zed", "yarm", "other"]
+        );
+        assert_eq!(completion_names(&repos, &pinned, Some("y"), None), ["yarm"]);
+    }
+
+    #[test]
+    fn test_remote_matches() {
+        let repos: Vec<PathBuf> = ["/p/api", "/p/docs", "/p/fork", "/p/local", "/p/unscanned"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let urls = BTreeMap::from([
+            (
+                PathBuf::from("/p/api"),
+                "git@github.com:Owner/repo.git".to_string(),
+            ),
+            (
+                PathBuf::from("/p/docs"),
+                "https://github.com/owner/repo-docs".to_string(),
+            ),
+            (
+                PathBuf::from("/p/fork"),
+                "https://gitlab.com/someone/owner/repo/".to_string(),
+            ),
+            (PathBuf::from("/p/local"), String::new()),
+        ]);
+
+        assert_eq!(
+            remote_matches(&repos, &urls, "owner/repo"),
+            [PathBuf::from("/p/api"), PathBuf::from("/p/fork")]
+        );
+        assert_eq!(
+            remote_matches(&repos, &urls, "github.com:owner/repo.git"),
+            [PathBuf::from("/p/api")]
+        );
+        assert_eq!(
+            remote_matches(&repos, &urls, "repo-do"),
+            [PathBuf::from("/p/docs")]
+        );
+        assert!(remote_matches(&repos, &urls, "other/thing").is_empty());
+        assert!(remote_matches(&repos, &urls, "/").is_empty());
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_pool_relative_display() {
+        let pools = vec![
+            PathBuf::from("/home/user/work"),
+            PathBuf::from("/home/user/personal"),
+        ];
+        assert_eq!(
+            pool_relative_display(Path::new("/home/user/work/foo/bar"), &pools),
+            "work: foo/bar"
+        );
+        assert_eq!(
+            pool_relative_display(Path::new("/home/user/personal/foo/bar"), &pools),
+            "personal: foo/bar"
+        );
+        assert_eq!(
+            pool_relative_display(Path::new("/srv/foo/bar"), &pools),
+            "/srv/foo/bar"
+        );
+    }
+
+    #[test]
+    fn test_repos_json() {
+        let pools = vec![PathBuf::from("/home/user/projects")];
+        let repos = vec![
+            PathBuf::from("/home/user/projects/yarm"),
+            PathBuf::from("/tmp/loose"),
+        ];
+        assert_eq!(
+            repos_json(&repos, &pools),
+            "[{\"name\":\"yarm\",\"path\":\"/home/user/projects/yarm\",\"pool\":\"projects\"},\
+             {\"name\":\"loose\",\"path\":\"/tmp/loose\",\"pool\":null}]"
+        );
+        assert_eq!(repos_json::<PathBuf>(&[], &pools), "[]");
+    }
+
+    #[test]
+    fn test_group_repos_by_pool() {
+        let pools = vec![
+            PathBuf::from("/home/user/work"),
+            PathBuf::from("/home/user/personal"),
+        ];
+        let repos = vec![
+            PathBuf::from("/home/user/personal/blog"),
+            PathBuf::from("/home/user/work/api"),
+            PathBuf::from("/srv/loose"),
+            PathBuf::from("/home/user/work/web"),
+        ];
+        let groups = group_repos(&repos, |repo, _| pool_name(repo, &pools));
+
+        let keys: Vec<_> = groups.iter().map(|(key, _)| key.as_deref()).collect();
+        assert_eq!(keys, [Some("personal"), Some("work"), None]);
+        assert_eq!(groups[0].1, [&repos[0]]);
+        assert_eq!(groups[1].1, [&repos[1], &repos[3]]);
+        assert_eq!(groups[2].1, [&repos[2]]);
+
+        assert_eq!(
+            groups_json(&groups[1..], &pools),
+            "{\"work\":[{\"name\":\"api\",\"path\":\"/home/user/work/api\",\"pool\":\"work\"},\
+             {\"name\":\"web\",\"path\":\"/home/user/work/web\",\"pool\":\"work\"}],\
+             \"\":[{\"name\":\"loose\",\"path\":\"/srv/loose\",\"pool\":null}]}"
+        );
+    }
+
+    #[test]
+    fn test_group_repos_without_keys() {
+        let repos = vec![PathBuf::from("/a"), PathBuf::from("/b")];
+        let groups = group_repos(&repos, |_, _| None);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, None);
+        assert!(group_repos(&[], |_, _| Some("x".to_string())).is_empty());
+    }
+
     fn repos() -> Vec<PathBuf> {
         vec![
             PathBuf::from("/home/user/projects/yarm"),
@@ -233,6 +835,36 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_existing_repo_path() {
+        let dir = std::env::temp_dir().join("yarm-test-find-passthrough");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("repo/.git")).unwrap();
+        std::fs::create_dir_all(dir.join("plain")).unwrap();
+
+        let repo = dir.join("repo");
+        assert_eq!(
+            existing_repo_path(&repo.to_string_lossy()),
+            Some(repo.canonicalize().unwrap())
+        );
+        assert_eq!(
+            existing_repo_path(&dir.join("plain").to_string_lossy()),
+            None
+        );
+        assert_eq!(
+            existing_repo_path(&dir.join("missing").to_string_lossy()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_match_count() {
+        assert_eq!(match_count(&repos(), "yarm"), 2);
+        assert_eq!(match_count(&repos(), "projects/yarm"), 1);
+        assert_eq!(match_count(&repos(), "missing"), 0);
+        assert_eq!(match_count(&[], "missing"), 0);
+    }
+
     #[test]
     fn test_exact_basename_single() {
         let matches = find_matches(&repos(), "other");
@@ -247,6 +879,27 @@ mod tests {
         assert!(matches.contains(&PathBuf::from("/home/user/work/yarm")));
     }
 
+    #[test]
+    fn test_prefer_pinned_resolves_ambiguity() {
+        let pinned = vec![PathBuf::from("/home/user/work/yarm")];
+        assert_eq!(
+            prefer_pinned(find_matches(&repos(), "yarm"), &pinned),
+            [PathBuf::from("/home/user/work/yarm")]
+        );
+        // Without a pin, or with every candidate pinned, the match stays ambiguous
+        assert_eq!(prefer_pinned(find_matches(&repos(), "yarm"), &[]).len(), 2);
+        let all = vec![
+            PathBuf::from("/home/user/projects/yarm"),
+            PathBuf::from("/home/user/work/yarm"),
+        ];
+        assert_eq!(prefer_pinned(find_matches(&repos(), "yarm"), &all).len(), 2);
+        // A unique match is kept even if another repository is pinned
+        assert_eq!(
+            prefer_pinned(find_matches(&repos(), "other"), &pinned),
+            [PathBuf::from("/home/user/projects/other")]
+        );
+    }
+
     #[test]
     fn test_exact_basename_case_insensitive() {
         let matches = find_matches(&repos(), "YARM");
@@ -1,14 +1,51 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-use crate::git;
-use crate::profile::{ProfileContext, apply_profile, resolve_profile_with_context};
-use crate::term::{print_header, print_success};
+use crate::git::{self, InitOptions, ObjectFormat};
+use crate::profile::{
+    ProfileContext, apply_profile, resolve_profile_with_context, validate_selection,
+};
+use crate::term::{
+    format_home_path, is_interactive, print_header, print_success, print_warning, prompt_confirm,
+};
 
-/// Executes the init command flow
-pub fn run(profile_name: Option<&str>) -> Result<()> {
+/// Executes the init command flow.
+/// `template` overrides the configured `init_template` directory.
+/// `gitignore` names a template in the configured `gitignore_dir` to write as `.gitignore`.
+/// With `initial_commit`, the result is committed with the applied profile's identity.
+/// `object_format` selects the repository's hash algorithm (git's default if `None`).
+pub fn run(
+    profile_name: Option<&str>,
+    template: Option<&str>,
+    gitignore: Option<&str>,
+    initial_commit: bool,
+    object_format: Option<ObjectFormat>,
+) -> Result<()> {
     git::ensure_available()?;
+    validate_selection(profile_name.into())?;
+    if let Some(format) = object_format {
+        git::check_object_format(format, git::version())?;
+    }
+
+    let config = crate::config::load()?;
+    let gitignore = gitignore
+        .map(|name| {
+            let Some(dir) = &config.repositories.gitignore_dir else {
+                anyhow::bail!("No gitignore templates configured; set repositories.gitignore_dir");
+            };
+            find_gitignore_template(&crate::config::expand_path(dir), name)
+        })
+        .transpose()?;
+    let template = template
+        .or(config.repositories.init_template.as_deref())
+        .map(crate::config::expand_path);
+    if let Some(dir) = &template
+        && !dir.is_dir()
+    {
+        anyhow::bail!("Template directory not found: {}", dir.display());
+    }
 
     let target = PathBuf::from(".");
 
@@ -21,16 +58,52 @@ pub fn run(profile_name: Option<&str>) -> Result<()> {
     print_header("Initializing:", display_path.display());
     println!();
 
+    let gitignore = match gitignore {
+        Some(source) if replaces_gitignore(&target, &source)? => Some(source),
+        _ => None,
+    };
+
+    // A .yarm-profile file (or .yarm marker) in the directory stands in for --profile
+    let repo_profile = if profile_name.is_none() {
+        crate::profile::repo_profile_name(&display_path)?
+    } else {
+        None
+    };
     let context = ProfileContext::new(display_path.clone(), None);
-    let Some(selected) = resolve_profile_with_context(profile_name, &context)? else {
+    let Some(selected) =
+        resolve_profile_with_context(profile_name.or(repo_profile.as_deref()).into(), &context)?
+    else {
         return Ok(());
     };
 
-    init_repo(&target)?;
+    git::init(
+        &target,
+        &InitOptions {
+            template,
+            object_format,
+            ..InitOptions::default()
+        },
+    )?;
 
     apply_profile(&target, &selected)?;
 
-    let config = crate::config::load()?;
+    if let Some(source) = &gitignore {
+        fs::copy(source, target.join(".gitignore")).context("Failed to write .gitignore")?;
+    }
+
+    let committed = if !initial_commit {
+        false
+    } else if selected.user_email.is_none() {
+        print_warning(format!(
+            "Profile '{}' has no user.email, skipping the initial commit",
+            selected.name
+        ));
+        false
+    } else {
+        create_initial_commit(&target, gitignore.is_some())?;
+        true
+    };
+
     if crate::config::is_in_pool(&display_path, &config.pool_paths()) {
         crate::state::register_repo(&display_path)?;
     }
@@ -44,23 +117,165 @@ pub fn run(profile_name: Option<&str>) -> Result<()> {
         selected.name,
         selected.config_summary()
     ));
+    if let Some(source) = &gitignore {
+        print_success(format!(
+            "Wrote .gitignore from {}",
+            format_home_path(source)
+        ));
+    }
+    if committed {
+        print_success("Created initial commit");
+    }
 
     Ok(())
 }
 
-/// Initializes a git repository
-fn init_repo(target: &std::path::Path) -> Result<()> {
+/// Finds `<name>.gitignore` or `<name>` in the templates directory, ignoring case
+fn find_gitignore_template(dir: &Path, name: &str) -> Result<PathBuf> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read gitignore templates in {}", dir.display()))?;
+    let wanted = name.to_lowercase();
+    let wanted_ext = format!("{wanted}.gitignore");
+
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .find(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .is_some_and(|n| n == wanted_ext || n == wanted)
+        })
+        .with_context(|| {
+            format!(
+                "Gitignore template '{name}' not found in {}",
+                format_home_path(dir)
+            )
+        })
+}
+
+/// Whether the template may be written as `.gitignore`. An existing, different file is only
+/// replaced after confirmation; without a terminal it is kept with a warning.
+fn replaces_gitignore(target: &Path, source: &Path) -> Result<bool> {
+    if !differs_from_existing_gitignore(target, source) {
+        return Ok(true);
+    }
+    if is_interactive() {
+        let prompt = format!(
+            "Replace the existing .gitignore with {}?",
+            format_home_path(source)
+        );
+        if prompt_confirm(&prompt, false)? == Some(true) {
+            return Ok(true);
+        }
+    }
+    print_warning("Kept existing .gitignore");
+    Ok(false)
+}
+
+/// Whether `target` already has a `.gitignore` whose content differs from the template
+fn differs_from_existing_gitignore(target: &Path, source: &Path) -> bool {
+    fs::read(target.join(".gitignore"))
+        .is_ok_and(|existing| fs::read(source).map_or(true, |template| template != existing))
+}
+
+/// Commits the freshly initialized repository using its local identity.
+/// Only `.gitignore` is staged; without it the commit is empty.
+fn create_initial_commit(target: &Path, with_gitignore: bool) -> Result<()> {
+    if with_gitignore {
+        run_git(target, &["add", ".gitignore"], "Staging .gitignore failed")?;
+    }
+    run_git(
+        target,
+        &["commit", "--quiet", "--allow-empty", "-m", "Initial commit"],
+        "Initial commit failed",
+    )
+}
+
+fn run_git(target: &Path, args: &[&str], failure: &str) -> Result<()> {
     let output = Command::new("git")
-        .args(["init", &target.to_string_lossy()])
+        .arg("-C")
+        .arg(target)
+        .args(args)
         .stdout(Stdio::null())
         .stderr(Stdio::piped())
         .output()
-        .context("Failed to execute git init")?;
+        .context("Failed to execute git")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("{}", git::format_error("Init failed", &stderr));
+        anyhow::bail!("{}", git::format_error(failure, &stderr));
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differs_from_existing_gitignore() {
+        let dir = std::env::temp_dir().join("yarm-test-init-gitignore");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let template = dir.join("Rust.gitignore");
+        fs::write(&template, "/target\n").unwrap();
+
+        assert!(!differs_from_existing_gitignore(&dir, &template));
+
+        fs::write(dir.join(".gitignore"), "/target\n").unwrap();
+        assert!(!differs_from_existing_gitignore(&dir, &template));
+
+        fs::write(dir.join(".gitignore"), "node_modules/\n").unwrap();
+        assert!(differs_from_existing_gitignore(&dir, &template));
+    }
+
+    #[test]
+    fn test_find_gitignore_template() {
+        let dir = std::env::temp_dir().join("yarm-test-gitignore-templates");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Rust.gitignore"), "/target\n").unwrap();
+        fs::write(dir.join("python"), "__pycache__/\n").unwrap();
+
+        assert_eq!(
+            find_gitignore_template(&dir, "rust").unwrap(),
+            dir.join("Rust.gitignore")
+        );
+        assert_eq!(
+            find_gitignore_template(&dir, "Python").unwrap(),
+            dir.join("python")
+        );
+        let err = find_gitignore_template(&dir, "go").unwrap_err();
+        assert!(err.to_string().contains("'go' not found"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_create_initial_commit_with_gitignore() {
+        let repo = std::env::temp_dir().join("yarm-test-init-initial-commit");
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(&repo).unwrap();
+        git::init(&repo, &InitOptions::default()).unwrap();
+        git::set_config(&repo, "user.name", Some("Init Tester")).unwrap();
+        git::set_config(&repo, "user.email", Some("init@example.com")).unwrap();
+        git::set_config(&repo, "commit.gpgsign", Some("false")).unwrap();
+        fs::write(repo.join(".gitignore"), "/target\n").unwrap();
+
+        create_initial_commit(&repo, true).unwrap();
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["log", "-1", "--format=%ae", "--name-only"])
+            .output()
+            .unwrap();
+        let log = String::from_utf8_lossy(&output.stdout);
+        assert!(log.contains("init@example.com"));
+        assert!(log.contains(".gitignore"));
+
+        let _ = fs::remove_dir_all(&repo);
+    }
+}
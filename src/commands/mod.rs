@@ -1,8 +1,51 @@
 pub mod apply;
 pub mod clone;
+pub mod completions;
 pub mod find;
 pub mod init;
+pub mod pin;
 pub mod profiles;
 pub mod scan;
 pub mod stat;
 pub mod status;
+pub mod version;
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::term::{
+    MenuLevel, SilentExit, eprint_hint, eprint_warning, format_home_path, is_cancelled,
+    is_interactive,
+};
+
+/// Lets the user pick a scanned repository from a filterable menu, for commands that
+/// take an optional repository name. Returns `None` if the menu was cancelled.
+pub fn pick_repo() -> Result<Option<PathBuf>> {
+    if !is_interactive() {
+        anyhow::bail!("Picking a repository needs an interactive terminal");
+    }
+
+    let mut repos = crate::state::load()?.repositories;
+    if repos.is_empty() {
+        eprint_warning("No repositories in state");
+        eprint_hint("Run `yarm scan` to discover repositories");
+        return Err(SilentExit(1).into());
+    }
+    repos.sort();
+
+    let options: Vec<String> = repos.iter().map(|r| format_home_path(r)).collect();
+    let selection = match MenuLevel::Top
+        .select_filterable("Select repository:", options.clone())
+        .prompt()
+    {
+        Ok(s) => s,
+        Err(e) if is_cancelled(&e) => return Ok(None),
+        Err(e) => return Err(e).context("Selection failed"),
+    };
+
+    Ok(options
+        .iter()
+        .position(|o| *o == selection)
+        .map(|idx| repos.swap_remove(idx)))
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::commands::find;
+use crate::term::{SilentExit, eprint_warning, format_home_path, print_success, print_warning};
+
+/// Pins a repository as a favorite, so `find` prefers it when a name is ambiguous
+/// and shell completion lists it first.
+pub fn pin(repo: &str) -> Result<()> {
+    let path = find::resolve_repo(repo)?;
+    let mut state = crate::state::load()?;
+
+    if state.pinned_repos.contains(&path) {
+        print_warning(format!("Already pinned: {}", format_home_path(&path)));
+        return Ok(());
+    }
+
+    state.pinned_repos.push(path.clone());
+    crate::state::save(&state)?;
+    print_success(format!("Pinned {}", format_home_path(&path)));
+
+    Ok(())
+}
+
+/// Removes a repository from the pinned favorites.
+/// Matches against the pinned repositories only, so pins to deleted repositories can be removed.
+pub fn unpin(repo: &str) -> Result<()> {
+    let mut state = crate::state::load()?;
+
+    let path = PathBuf::from(repo);
+    let path = path.canonicalize().unwrap_or(path);
+    let matches = if state.pinned_repos.contains(&path) {
+        vec![path]
+    } else {
+        find::find_matches(&state.pinned_repos, repo)
+    };
+
+    match matches.as_slice() {
+        [] => {
+            eprint_warning(format!("No pinned repository matching '{repo}'"));
+            Err(SilentExit(1).into())
+        }
+        [path] => {
+            state.pinned_repos.retain(|p| p != path);
+            crate::state::save(&state)?;
+            print_success(format!("Unpinned {}", format_home_path(path)));
+            Ok(())
+        }
+        _ => {
+            eprint_warning(format!(
+                "Ambiguous match '{repo}', found {} pinned repositories:",
+                matches.len()
+            ));
+            for m in &matches {
+                eprintln!("  {}", format_home_path(m));
+            }
+            Err(SilentExit(1).into())
+        }
+    }
+}
+
+/// Keeps the pins of repositories that are still present after a scan, in pin order
+pub fn retained_pins(pinned: &[PathBuf], repos: &[PathBuf]) -> Vec<PathBuf> {
+    pinned
+        .iter()
+        .filter(|p| repos.contains(p))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retained_pins_prunes_missing() {
+        let pinned = vec![
+            PathBuf::from("/p/b"),
+            PathBuf::from("/p/gone"),
+            PathBuf::from("/p/a"),
+        ];
+        let repos = vec![PathBuf::from("/p/a"), PathBuf::from("/p/b")];
+        assert_eq!(
+            retained_pins(&pinned, &repos),
+            [PathBuf::from("/p/b"), PathBuf::from("/p/a")]
+        );
+    }
+}
@@ -2,11 +2,21 @@ use anyhow::{Context, Result};
 use console::style;
 use std::fmt;
 use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
+use crate::commands::apply::{self, Scope};
+use crate::commands::find;
+use crate::config;
 use crate::git;
-use crate::profile::{Profile, discover_profiles, find_profile_by_name};
+use crate::profile::{
+    PROFILE_KEYS, Profile, ProfileScope, ProfileSelection, discover_profiles, find_profile_by_name,
+    is_valid_email, local_profile, parse_setting, profile_for_email, profile_scope, reload_profile,
+};
 use crate::term::{
-    MenuLevel, MenuSession, format_home_path, is_cancelled, print_success, print_warning,
+    MenuLevel, MenuSession, Table, format_home_path, icon_success, is_cancelled, is_interactive,
+    link, paged_output, print_field_diff, print_header, print_hint, print_success, print_warning,
     prompt_confirm, prompt_required_text, prompt_text, prompt_text_with_help,
 };
 
@@ -35,6 +45,7 @@ impl fmt::Display for MenuOption {
 enum ProfileAction {
     Show,
     Edit,
+    OpenInEditor,
     Delete,
 }
 
@@ -43,17 +54,93 @@ impl fmt::Display for ProfileAction {
         match self {
             Self::Show => write!(f, "Show details"),
             Self::Edit => write!(f, "Edit profile"),
+            Self::OpenInEditor => write!(f, "Open in editor"),
             Self::Delete => write!(f, "Delete profile"),
         }
     }
 }
 
+/// Profile fields given on the command line for non-interactive creation
+pub struct NewProfile {
+    pub name: String,
+    pub user_name: String,
+    pub user_email: Option<String>,
+    pub signing_key: Option<String>,
+    /// Signing format; `openpgp` (git's default) is not written
+    pub gpg_format: Option<String>,
+    /// Sign commits and tags
+    pub sign: bool,
+}
+
+/// Predicates narrowing the profile listing; all given conditions must match
+#[derive(Debug, Default)]
+pub struct ProfileFilter {
+    /// Only profiles with a signing key
+    pub with_signing: bool,
+    /// Only profiles whose email address is in this domain (case-insensitive)
+    pub email_domain: Option<String>,
+}
+
+/// How the profile listing is narrowed and laid out
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    pub filter: ProfileFilter,
+    /// Group profiles by the directory of their config file
+    pub tree: bool,
+}
+
+/// Checks whether a profile satisfies every condition of the filter
+fn matches_filter(profile: &Profile, filter: &ProfileFilter) -> bool {
+    if filter.with_signing && profile.signing_key.is_none() {
+        return false;
+    }
+
+    if let Some(domain) = &filter.email_domain {
+        let in_domain = profile
+            .user_email
+            .as_deref()
+            .and_then(|email| email.rsplit_once('@'))
+            .is_some_and(|(_, d)| d.eq_ignore_ascii_case(domain.trim_start_matches('@')));
+        if !in_domain {
+            return false;
+        }
+    }
+
+    true
+}
+
 /// Main entry point for the profiles command
-pub fn run(name: Option<&str>, show_only: bool) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    name: Option<&str>,
+    show_only: bool,
+    capture: Option<&str>,
+    settings: &[String],
+    porcelain: bool,
+    create: Option<NewProfile>,
+    list: &ListOptions,
+    pager: bool,
+) -> Result<()> {
+    if let Some(repo) = capture {
+        return capture_profile(repo);
+    }
+
+    if let Some(new_profile) = create {
+        return create_profile_from(new_profile);
+    }
+
+    if porcelain {
+        return print_porcelain(name, &list.filter);
+    }
+
     if let Some(name) = name {
         let profiles = discover_profiles()?;
         let profile = find_profile_by_name(&profiles, name)?;
 
+        if !settings.is_empty() {
+            return set_profile_fields(&profile, settings);
+        }
+
         if show_only {
             println!();
             print_profile(&profile);
@@ -64,12 +151,59 @@ pub fn run(name: Option<&str>, show_only: bool) -> Result<()> {
     }
 
     if show_only {
-        return show_profiles();
+        return show_profiles(list, pager);
     }
 
     interactive_menu()
 }
 
+/// Applies the configured `profiles.default` to the global git config
+pub fn apply_default(yes: bool) -> Result<()> {
+    let config = config::load()?;
+    let name = config
+        .profiles
+        .default
+        .context("No default profile configured; set profiles.default in the yarm config")?;
+
+    apply::run(Scope::Global, ProfileSelection::Named(&name), false, yes)
+}
+
+/// Opens the named profile's file in `$EDITOR`
+pub fn open_in_editor(name: &str) -> Result<()> {
+    let profiles = discover_profiles()?;
+    let profile = find_profile_by_name(&profiles, name)?;
+    edit_profile_file(&profile)
+}
+
+/// Lets the user edit the raw gitconfig of a profile, then shows the identity it now defines
+fn edit_profile_file(profile: &Profile) -> Result<()> {
+    ensure_editable(profile)?;
+    let readonly = fs::metadata(&profile.source)
+        .with_context(|| format!("Failed to read {}", profile.source.display()))?
+        .permissions()
+        .readonly();
+    if readonly {
+        anyhow::bail!("{} is not writable", format_home_path(&profile.source));
+    }
+
+    crate::term::open_in_editor(&profile.source)?;
+
+    println!();
+    match reload_profile(&profile.source)? {
+        Some(updated) => {
+            print_success(format!("Profile '{}' saved", updated.name));
+            println!();
+            print_profile(&updated);
+        }
+        None => print_warning(format!(
+            "{} no longer sets user.name or user.email and is not listed as a profile",
+            format_home_path(&profile.source)
+        )),
+    }
+
+    Ok(())
+}
+
 /// Interactive menu for a specific named profile
 fn single_profile_menu(profile: &Profile) -> Result<()> {
     let mut session = MenuSession::new();
@@ -80,6 +214,7 @@ fn single_profile_menu(profile: &Profile) -> Result<()> {
         let options = vec![
             ProfileAction::Show,
             ProfileAction::Edit,
+            ProfileAction::OpenInEditor,
             ProfileAction::Delete,
         ];
 
@@ -98,6 +233,10 @@ fn single_profile_menu(profile: &Profile) -> Result<()> {
                 edit_single_profile(profile)?;
                 break;
             }
+            Ok(ProfileAction::OpenInEditor) => {
+                edit_profile_file(profile)?;
+                break;
+            }
             Ok(ProfileAction::Delete) => {
                 delete_single_profile(profile)?;
                 break;
@@ -110,7 +249,7 @@ fn single_profile_menu(profile: &Profile) -> Result<()> {
 }
 
 /// Lists all discovered profiles (non-interactive)
-fn show_profiles() -> Result<()> {
+fn show_profiles(list: &ListOptions, pager: bool) -> Result<()> {
     let profiles = discover_profiles()?;
 
     if profiles.is_empty() {
@@ -120,48 +259,270 @@ fn show_profiles() -> Result<()> {
         return Ok(());
     }
 
-    print_success(format!(
-        "{} profile{} found",
-        profiles.len(),
-        if profiles.len() == 1 { "" } else { "s" }
-    ));
-    println!();
+    let profiles: Vec<Profile> = profiles
+        .into_iter()
+        .filter(|p| matches_filter(p, &list.filter))
+        .collect();
 
-    for (i, profile) in profiles.iter().enumerate() {
-        if i > 0 {
-            println!();
+    if profiles.is_empty() {
+        print_warning("No profiles match the given filters");
+        return Ok(());
+    }
+
+    let mut lines = vec![
+        format!(
+            "  {} {} profile{} found",
+            icon_success(),
+            profiles.len(),
+            if profiles.len() == 1 { "" } else { "s" }
+        ),
+        String::new(),
+    ];
+
+    if list.tree {
+        for (i, (dir, group)) in group_by_directory(&profiles).into_iter().enumerate() {
+            if i > 0 {
+                lines.push(String::new());
+            }
+            let dir = format_home_path(dir);
+            let separator = if dir.ends_with('/') { "" } else { "/" };
+            lines.push(format!("  {}", style(format!("{dir}{separator}:")).bold()));
+            for profile in group {
+                lines.push(String::new());
+                lines.extend(profile_lines(profile).into_iter().map(|l| format!("  {l}")));
+            }
         }
-        print_profile(profile);
+    } else {
+        for (i, profile) in profiles.iter().enumerate() {
+            if i > 0 {
+                lines.push(String::new());
+            }
+            lines.extend(profile_lines(profile));
+        }
+    }
+
+    paged_output(lines, pager);
+
+    Ok(())
+}
+
+/// Scanned repositories grouped by the profile their local `user.email` matches
+#[derive(Debug)]
+struct ProfileUsage<'a> {
+    /// Every non-local profile with its repositories, in discovery order
+    by_profile: Vec<(&'a Profile, Vec<&'a PathBuf>)>,
+    /// Repositories with a local email that matches no profile
+    unassigned: Vec<(&'a PathBuf, &'a str)>,
+    /// Number of repositories without a local email
+    inherited: usize,
+}
+
+/// Lists the scanned repositories using each profile, followed by repositories whose
+/// local identity matches no profile
+pub fn show_usage(pager: bool) -> Result<()> {
+    let profiles = discover_profiles()?;
+    let state = crate::state::load()?;
+    if state.repositories.is_empty() {
+        print_warning("No repositories in state");
+        print_hint("Run `yarm scan` to discover repositories");
+        return Ok(());
     }
 
+    let emails = find::local_emails(&state.repositories);
+    let usage = profile_usage(&profiles, &state.repositories, &emails);
+    paged_output(usage_lines(&usage), pager);
+
     Ok(())
 }
 
+/// Assigns each repository to the profile matching its local email (`emails` is in the
+/// order of `repos`)
+fn profile_usage<'a>(
+    profiles: &'a [Profile],
+    repos: &'a [PathBuf],
+    emails: &'a [Option<String>],
+) -> ProfileUsage<'a> {
+    let mut usage = ProfileUsage {
+        by_profile: profiles
+            .iter()
+            .filter(|p| profile_scope(&p.source) != ProfileScope::Local)
+            .map(|p| (p, Vec::new()))
+            .collect(),
+        unassigned: Vec::new(),
+        inherited: 0,
+    };
+
+    for (repo, email) in repos.iter().zip(emails) {
+        let Some(email) = email.as_deref() else {
+            usage.inherited += 1;
+            continue;
+        };
+        let group = profile_for_email(profiles, email).and_then(|profile| {
+            usage
+                .by_profile
+                .iter_mut()
+                .find(|(p, _)| std::ptr::eq(*p, profile))
+        });
+        match group {
+            Some((_, members)) => members.push(repo),
+            None => usage.unassigned.push((repo, email)),
+        }
+    }
+
+    usage
+}
+
+/// Renders profile usage as output lines
+fn usage_lines(usage: &ProfileUsage) -> Vec<String> {
+    let count = |n: usize| style(format!("{n} {}", apply::plural_repositories(n))).dim();
+    let mut lines = Vec::new();
+
+    for (profile, repos) in &usage.by_profile {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "  {} {} {}",
+            style(&profile.name).bold(),
+            style(format!("({})", format_home_path(&profile.source))).dim(),
+            count(repos.len())
+        ));
+        for repo in repos {
+            lines.push(format!("    {}", format_home_path(repo)));
+        }
+    }
+
+    if !usage.unassigned.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "  {} {}",
+            style("Unassigned").yellow().bold(),
+            count(usage.unassigned.len())
+        ));
+        for (repo, email) in &usage.unassigned {
+            lines.push(format!(
+                "    {} {}",
+                format_home_path(repo),
+                style(format!("<{email}>")).dim()
+            ));
+        }
+    }
+
+    if usage.inherited > 0 {
+        lines.push(String::new());
+        lines.push(format!(
+            "  {}",
+            style(format!(
+                "{} without a local identity inherit the global one",
+                count(usage.inherited)
+            ))
+            .dim()
+        ));
+    }
+
+    lines
+}
+
+/// Groups profiles by the directory of their source file, in order of first appearance
+fn group_by_directory(profiles: &[Profile]) -> Vec<(&Path, Vec<&Profile>)> {
+    let mut groups: Vec<(&Path, Vec<&Profile>)> = Vec::new();
+    for profile in profiles {
+        let dir = profile.source.parent().unwrap_or(Path::new(""));
+        match groups.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, group)) => group.push(profile),
+            None => groups.push((dir, vec![profile])),
+        }
+    }
+    groups
+}
+
+/// Prints profiles as tab-separated lines for scripting, in discovery order
+fn print_porcelain(name: Option<&str>, filter: &ProfileFilter) -> Result<()> {
+    let profiles = discover_profiles()?;
+
+    if let Some(name) = name {
+        println!(
+            "{}",
+            porcelain_line(&find_profile_by_name(&profiles, name)?)
+        );
+        return Ok(());
+    }
+
+    for profile in profiles.iter().filter(|p| matches_filter(p, filter)) {
+        println!("{}", porcelain_line(profile));
+    }
+
+    Ok(())
+}
+
+/// Formats a profile as `name\tsource\tuser.name\tuser.email\tsigningkey\tgpgsign`.
+/// Unset fields are empty.
+fn porcelain_line(profile: &Profile) -> String {
+    let source = profile.source.display().to_string();
+    let fields = [
+        "user.name",
+        "user.email",
+        "user.signingkey",
+        "commit.gpgsign",
+    ]
+    .map(|key| profile.config_value(key).unwrap_or_default());
+
+    [profile.name.as_str(), source.as_str()]
+        .into_iter()
+        .chain(fields.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
 /// Prints a single profile's details (no trailing blank line)
 fn print_profile(profile: &Profile) {
-    let source_display = format_home_path(&profile.source);
+    for line in profile_lines(profile) {
+        println!("{line}");
+    }
+}
 
-    if profile.is_default {
-        println!(
+/// Renders a single profile's details as output lines
+fn profile_lines(profile: &Profile) -> Vec<String> {
+    let source_display = link(format_home_path(&profile.source), &profile.source);
+
+    let mut lines = vec![if profile.is_default {
+        format!(
             "  {} {} {}",
             style(&profile.name).bold(),
             style("(yarm default)").cyan(),
             style(format!("({source_display})")).dim()
-        );
+        )
     } else {
-        println!(
+        format!(
             "  {} {}",
             style(&profile.name).bold(),
             style(format!("({source_display})")).dim()
-        );
-    }
+        )
+    }];
 
     if let Some(identity) = profile.identity() {
-        println!("    {identity}");
+        lines.push(format!("    {identity}"));
+    }
+    lines.extend(field_table(profile).lines());
+    for source in &profile.duplicate_sources {
+        lines.push(format!(
+            "    {}",
+            style(format!("also defined in {}", format_home_path(source))).dim()
+        ));
     }
+
+    lines
+}
+
+/// Lays out a profile's non-identity fields as an aligned table
+fn field_table(profile: &Profile) -> Table {
+    let mut table = Table::new().plain_labels();
     for field in profile.fields() {
-        println!("    {:<16}{}", field.label, field.value);
+        table.row(field.label, field.value);
     }
+    table
 }
 
 /// Interactive menu for managing profiles
@@ -197,7 +558,7 @@ fn interactive_menu() -> Result<()> {
             }
             Ok(MenuOption::List) => {
                 println!();
-                show_profiles()?;
+                show_profiles(&ListOptions::default(), false)?;
                 session.printed_output();
             }
             Err(_) => break,
@@ -242,9 +603,29 @@ fn edit_profile() -> Result<()> {
 /// Edit a known profile
 #[allow(clippy::too_many_lines)]
 fn edit_single_profile(profile: &Profile) -> Result<()> {
+    if profile_scope(&profile.source) == ProfileScope::NonFile {
+        println!();
+        print_warning(format!(
+            "Profile '{}' is not defined in a file and cannot be edited",
+            profile.name
+        ));
+        return Ok(());
+    }
+    if profile_scope(&profile.source) == ProfileScope::System {
+        println!();
+        print_warning(format!(
+            "Profile '{}' is defined in the system gitconfig, which is read-only",
+            profile.name
+        ));
+        return Ok(());
+    }
+
     println!();
     println!("  Editing: {}", style(&profile.name).bold());
-    println!("  Source:  {}", format_home_path(&profile.source));
+    println!(
+        "  Source:  {}",
+        link(format_home_path(&profile.source), &profile.source)
+    );
     println!();
 
     // Store old values for diff
@@ -278,10 +659,11 @@ fn edit_single_profile(profile: &Profile) -> Result<()> {
             _ => 0,
         };
         let format = match MenuLevel::Sub
-            .select_with_default("Signing format:", format_options, default_idx)
+            .select_with_back("Signing format:", format_options, default_idx)
             .prompt()
         {
-            Ok(s) => s,
+            Ok(Some(s)) => s,
+            Ok(None) => return Ok(()),
             Err(e) if is_cancelled(&e) => return Ok(()),
             Err(e) => return Err(e).context("Selection failed"),
         };
@@ -410,82 +792,42 @@ fn edit_single_profile(profile: &Profile) -> Result<()> {
     Ok(())
 }
 
-/// Prints a field diff if the value changed
-fn print_field_diff(label: &str, old: Option<&str>, new: Option<&str>) {
-    match (old, new) {
-        (Some(o), Some(n)) if o != n => {
-            println!(
-                "    {}: {} {} {}",
-                label,
-                style(o).red(),
-                style("→").dim(),
-                style(n).green()
-            );
-        }
-        (None, Some(n)) => {
-            println!("    {}: {} {}", label, style("+").green(), style(n).green());
-        }
-        (Some(o), None) => {
-            println!("    {}: {} {}", label, style("-").red(), style(o).red());
-        }
-        _ => {} // No change
+/// Non-interactively updates profile keys from `key=value` settings
+fn set_profile_fields(profile: &Profile, settings: &[String]) -> Result<()> {
+    ensure_editable(profile)?;
+
+    // Validate everything before touching the file
+    let changes = settings
+        .iter()
+        .map(|s| parse_setting(s))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (key, value) in &changes {
+        git::set_config(&profile.source, key, value.as_deref())?;
     }
+
+    println!();
+    print_success(format!("Profile '{}' updated", profile.name));
+
+    for (key, value) in &changes {
+        print_field_diff(key, profile.config_value(key).as_deref(), value.as_deref());
+    }
+
+    Ok(())
 }
 
 /// Create a new profile
 fn create_profile() -> Result<()> {
     println!();
 
-    let Some(name) = prompt_text_with_help(
-        "Profile name:",
-        &MenuLevel::Sub.help_with("e.g., 'work', 'personal', 'oss'"),
-    )?
-    else {
+    let Some(name) = prompt_profile_name()? else {
         return Ok(());
     };
 
-    if name.is_empty() {
-        print_warning("Profile name cannot be empty");
+    let Some(path) = prompt_profile_path(&name)? else {
         return Ok(());
-    }
-
-    let home = dirs::home_dir().context("Could not determine home directory")?;
-    let gitconfig_path = home.join(format!(".gitconfig-{name}"));
-    let xdg_path = home.join(format!(".config/git/{name}.gitconfig"));
-
-    let location_options = vec![
-        format!("~/.gitconfig-{name}"),
-        format!("~/.config/git/{name}.gitconfig"),
-    ];
-
-    let location = match MenuLevel::Sub
-        .select("Where to create the profile:", location_options)
-        .prompt()
-    {
-        Ok(s) => s,
-        Err(e) if is_cancelled(&e) => return Ok(()),
-        Err(e) => return Err(e).context("Selection failed"),
     };
 
-    let path = if location.starts_with("~/.config") {
-        // Ensure directory exists
-        let parent = xdg_path.parent().unwrap();
-        if !parent.exists() {
-            fs::create_dir_all(parent).context("Failed to create config directory")?;
-        }
-        xdg_path
-    } else {
-        gitconfig_path
-    };
-
-    if path.exists() {
-        print_warning(format!(
-            "Profile file already exists: {}",
-            format_home_path(&path)
-        ));
-        return Ok(());
-    }
-
     let Some(user_name) = prompt_required_text("Name:", None)? else {
         return Ok(());
     };
@@ -503,10 +845,11 @@ fn create_profile() -> Result<()> {
     } else {
         let format_options = vec!["openpgp (GPG)", "ssh", "x509"];
         let format = match MenuLevel::Sub
-            .select("Signing format:", format_options)
+            .select_with_back("Signing format:", format_options, 0)
             .prompt()
         {
-            Ok(s) => s,
+            Ok(Some(s)) => s,
+            Ok(None) => return Ok(()),
             Err(e) if is_cancelled(&e) => return Ok(()),
             Err(e) => return Err(e).context("Selection failed"),
         };
@@ -524,25 +867,200 @@ fn create_profile() -> Result<()> {
         (gpg_format, commit_sign, tag_sign)
     };
 
-    fs::write(&path, "# Git profile configuration\n").context("Failed to create profile file")?;
+    let profile = Profile {
+        name,
+        source: path,
+        user_name: Some(user_name),
+        user_email: Some(user_email).filter(|e| !e.is_empty()),
+        signing_key: Some(signing_key).filter(|k| !k.is_empty()),
+        gpg_sign: gpg_sign.then_some(true),
+        gpg_format,
+        tag_gpg_sign: tag_gpg_sign.then_some(true),
+        is_default: false,
+        is_current: false,
+        ssh_command: None,
+        duplicate_sources: Vec::new(),
+    };
+
+    write_profile(&profile.source, &profile)?;
+
+    println!();
+    print_success(format!(
+        "Created profile '{}' at {}",
+        profile.name,
+        format_home_path(&profile.source)
+    ));
+
+    Ok(())
+}
+
+/// Capture the local identity of a repository as a new profile
+fn capture_profile(repo: &str) -> Result<()> {
+    let repo_path = find::resolve_repo(repo)?;
+
+    println!();
+    println!(
+        "  Capturing: {}",
+        style(format_home_path(&repo_path)).bold()
+    );
+    println!();
+
+    let Some(captured) = local_profile(&repo_path) else {
+        print_warning("Repository has no local identity (only inherited configuration)");
+        return Ok(());
+    };
+
+    if let Some(email) = captured.user_email.as_deref()
+        && !is_valid_email(email)
+    {
+        print_warning(format!(
+            "Local user.email '{email}' is not a valid email address"
+        ));
+        return Ok(());
+    }
+
+    if let Some(identity) = captured.identity() {
+        println!("    {identity}");
+    }
+    field_table(&captured).print();
+    println!();
+
+    let Some(name) = prompt_profile_name()? else {
+        return Ok(());
+    };
+
+    let Some(path) = prompt_profile_path(&name)? else {
+        return Ok(());
+    };
+
+    write_profile(&path, &captured)?;
 
-    git::set_config(&path, "user.name", Some(&user_name))?;
-    if !user_email.is_empty() {
-        git::set_config(&path, "user.email", Some(&user_email))?;
+    println!();
+    print_success(format!(
+        "Created profile '{}' at {}",
+        name,
+        format_home_path(&path)
+    ));
+
+    Ok(())
+}
+
+/// Creates a profile non-interactively at `~/.gitconfig-<name>`
+fn create_profile_from(new_profile: NewProfile) -> Result<()> {
+    let name = new_profile.name.trim();
+    if name.is_empty() {
+        anyhow::bail!("Profile name cannot be empty");
     }
-    if !signing_key.is_empty() {
-        git::set_config(&path, "user.signingkey", Some(&signing_key))?;
+    if let Some(email) = new_profile.user_email.as_deref()
+        && !is_valid_email(email)
+    {
+        anyhow::bail!("Invalid email address '{email}'");
     }
-    if let Some(ref format) = gpg_format {
-        git::set_config(&path, "gpg.format", Some(format))?;
+
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    let path = home.join(format!(".gitconfig-{name}"));
+    if path.exists() {
+        anyhow::bail!("Profile file already exists: {}", format_home_path(&path));
     }
-    if gpg_sign {
-        git::set_config(&path, "commit.gpgsign", Some("true"))?;
+
+    let sign = new_profile.sign.then_some(true);
+    let profile = Profile {
+        name: name.to_string(),
+        source: path,
+        user_name: Some(new_profile.user_name),
+        user_email: new_profile.user_email,
+        signing_key: new_profile.signing_key,
+        gpg_sign: sign,
+        gpg_format: new_profile.gpg_format.filter(|f| f != "openpgp"),
+        tag_gpg_sign: sign,
+        is_default: false,
+        is_current: false,
+        ssh_command: None,
+        duplicate_sources: Vec::new(),
+    };
+
+    write_profile(&profile.source, &profile)?;
+
+    println!();
+    print_success(format!(
+        "Created profile '{}' at {}",
+        profile.name,
+        format_home_path(&profile.source)
+    ));
+
+    Ok(())
+}
+
+/// Largest file `--import-url` downloads, in bytes
+const IMPORT_MAX_BYTES: &str = "65536";
+
+/// Downloads a gitconfig file over HTTPS and installs its identity as a new profile
+/// after showing the full content and asking for confirmation and a name.
+/// The file is only parsed, never included, so nothing in it is executed.
+pub fn import_url(url: &str) -> Result<()> {
+    let url = import_download_url(url)?;
+    git::ensure_online("Importing a profile")?;
+    if !is_interactive() {
+        anyhow::bail!("--import-url needs an interactive terminal to review the profile");
     }
-    if tag_gpg_sign {
-        git::set_config(&path, "tag.gpgsign", Some("true"))?;
+
+    let spinner = crate::term::spinner("Downloading...");
+    let downloaded = download_file(&url);
+    spinner.finish_and_clear();
+    let content = String::from_utf8(downloaded?).context("Downloaded file is not a text file")?;
+
+    // git reads the profile from a file; create it exclusively so a planted file or symlink
+    // in a shared temp directory is never written through
+    let download = std::env::temp_dir().join(format!(
+        "yarm-import-{}-{}.gitconfig",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos())
+    ));
+    create_private_file(&download, content.as_bytes())?;
+    let result = import_downloaded(&url, &content, &download);
+    let _ = fs::remove_file(&download);
+    result
+}
+
+fn import_downloaded(url: &str, content: &str, download: &Path) -> Result<()> {
+    let Some(imported) = reload_profile(download)? else {
+        anyhow::bail!("{url} does not define user.name or user.email");
+    };
+    if let Some(email) = imported.user_email.as_deref()
+        && !is_valid_email(email)
+    {
+        anyhow::bail!("Imported user.email '{email}' is not a valid email address");
     }
 
+    println!();
+    print_header("Importing:", url);
+    println!();
+    for line in raw_config_lines(content) {
+        println!("{line}");
+    }
+    println!();
+    if let Some(identity) = imported.identity() {
+        println!("    {identity}");
+    }
+    field_table(&imported).print();
+    println!();
+    print_hint("Only identity and signing settings are installed, other keys are ignored");
+    println!();
+
+    if prompt_confirm("Install this profile?", true)? != Some(true) {
+        return Ok(());
+    }
+    let Some(name) = prompt_profile_name()? else {
+        return Ok(());
+    };
+    let Some(path) = prompt_profile_path(&name)? else {
+        return Ok(());
+    };
+
+    write_profile(&path, &imported)?;
+
     println!();
     print_success(format!(
         "Created profile '{}' at {}",
@@ -553,6 +1071,147 @@ fn create_profile() -> Result<()> {
     Ok(())
 }
 
+/// Validates an import URL and returns the address to download.
+/// Only HTTPS is accepted; gist pages are mapped to their raw content.
+fn import_download_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let Some(rest) = url
+        .get(..8)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("https://"))
+        .map(|_| &url[8..])
+    else {
+        anyhow::bail!("Refusing to import from '{url}': only https:// URLs are supported");
+    };
+    if rest.is_empty() {
+        anyhow::bail!("Invalid URL '{url}'");
+    }
+
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let path = path.trim_end_matches('/');
+    if host.eq_ignore_ascii_case("gist.github.com")
+        && path.split('/').count() == 2
+        && !path.contains(['?', '#'])
+    {
+        return Ok(format!("https://{host}/{path}/raw"));
+    }
+    Ok(url.to_string())
+}
+
+/// Downloads `url` to `path` with curl, refusing redirects away from HTTPS
+fn download_file(url: &str) -> Result<Vec<u8>> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--proto", "=https", "--proto-redir", "=https"])
+        .args(["--max-filesize", IMPORT_MAX_BYTES])
+        .arg(url)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => Ok(output.stdout),
+        Ok(output) => anyhow::bail!(
+            "Failed to download {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+            "--import-url needs curl; install it or download the file and create the profile from it"
+        ),
+        Err(e) => Err(e).context("Failed to run curl"),
+    }
+}
+
+/// Creates `path` with `content`, failing if anything (including a symlink) already exists there.
+/// On Unix the file is only readable by the current user.
+fn create_private_file(path: &Path, content: &[u8]) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(content)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Prompts for a new profile name.
+/// Returns `Ok(None)` if cancelled or empty.
+fn prompt_profile_name() -> Result<Option<String>> {
+    let Some(name) = prompt_text_with_help(
+        "Profile name:",
+        &MenuLevel::Sub.help_with("e.g., 'work', 'personal', 'oss'"),
+    )?
+    else {
+        return Ok(None);
+    };
+
+    if name.is_empty() {
+        print_warning("Profile name cannot be empty");
+        return Ok(None);
+    }
+
+    Ok(Some(name))
+}
+
+/// Prompts for where to create the profile file for `name`.
+/// Returns `Ok(None)` if cancelled or if the file already exists.
+fn prompt_profile_path(name: &str) -> Result<Option<PathBuf>> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    let gitconfig_path = home.join(format!(".gitconfig-{name}"));
+    let xdg_path = home.join(format!(".config/git/{name}.gitconfig"));
+
+    let location_options = vec![
+        format!("~/.gitconfig-{name}"),
+        format!("~/.config/git/{name}.gitconfig"),
+    ];
+
+    let location = match MenuLevel::Sub
+        .select_with_back("Where to create the profile:", location_options, 0)
+        .prompt()
+    {
+        Ok(Some(s)) => s,
+        Ok(None) => return Ok(None),
+        Err(e) if is_cancelled(&e) => return Ok(None),
+        Err(e) => return Err(e).context("Selection failed"),
+    };
+
+    let path = if location.starts_with("~/.config") {
+        // Ensure directory exists
+        let parent = xdg_path.parent().unwrap();
+        if !parent.exists() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        xdg_path
+    } else {
+        gitconfig_path
+    };
+
+    if path.exists() {
+        print_warning(format!(
+            "Profile file already exists: {}",
+            format_home_path(&path)
+        ));
+        return Ok(None);
+    }
+
+    Ok(Some(path))
+}
+
+/// Writes a new profile file at `path` containing the profile's configured values
+fn write_profile(path: &Path, profile: &Profile) -> Result<()> {
+    fs::write(path, "# Git profile configuration\n").context("Failed to create profile file")?;
+
+    for key in PROFILE_KEYS {
+        if let Some(value) = profile.config_value(key) {
+            git::set_config(path, key, Some(&value))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Delete a profile (with interactive selection)
 fn delete_profile() -> Result<()> {
     let profiles = discover_profiles()?;
@@ -604,6 +1263,19 @@ fn delete_single_profile(profile: &Profile) -> Result<()> {
     println!();
     print_profile(profile);
 
+    // Show the whole file, which may contain more than the keys a profile models
+    if let Ok(content) = fs::read_to_string(&profile.source) {
+        println!();
+        println!(
+            "  {}",
+            style(format!("{}:", format_home_path(&profile.source))).dim()
+        );
+        for line in raw_config_lines(&content) {
+            println!("{line}");
+        }
+        println!();
+    }
+
     let Some(confirmed) = prompt_confirm(
         &format!("Delete profile '{}' and its config file?", profile.name),
         false,
@@ -625,18 +1297,66 @@ fn delete_single_profile(profile: &Profile) -> Result<()> {
     Ok(())
 }
 
+/// Renders gitconfig file contents indented, with sections highlighted and comments dimmed
+fn raw_config_lines(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| {
+            let line = escape_control(line);
+            let trimmed = line.trim_start();
+            let styled = if trimmed.starts_with('[') {
+                style(&line).cyan().to_string()
+            } else if trimmed.starts_with('#') || trimmed.starts_with(';') {
+                style(&line).dim().to_string()
+            } else {
+                line
+            };
+            format!("    {} {styled}", style("│").dim())
+        })
+        .collect()
+}
+
+/// Makes control characters other than tabs visible as escapes, so terminal sequences
+/// in untrusted content cannot hide or rewrite what is shown
+fn escape_control(line: &str) -> String {
+    line.chars()
+        .map(|c| {
+            if c.is_control() && c != '\t' {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
 /// Checks whether a profile can be deleted (not system/main gitconfig)
 fn is_deletable(profile: &Profile) -> bool {
-    let path_str = profile.source.to_string_lossy();
-    !path_str.ends_with("/.gitconfig")
-        && !path_str.contains("/etc/")
-        && !path_str.ends_with("/.git/config")
+    profile_scope(&profile.source) == ProfileScope::Custom
+}
+
+/// Errors if the profile lives in a system gitconfig, which users cannot write to
+fn ensure_editable(profile: &Profile) -> Result<()> {
+    if profile_scope(&profile.source) == ProfileScope::NonFile {
+        anyhow::bail!(
+            "Profile '{}' comes from {} rather than a file and cannot be edited",
+            profile.name,
+            profile.source.display()
+        );
+    }
+    if profile_scope(&profile.source) == ProfileScope::System {
+        anyhow::bail!(
+            "Profile '{}' is defined in the system gitconfig ({}), which is read-only",
+            profile.name,
+            profile.source.display()
+        );
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     fn profile_with_source(source: &str) -> Profile {
         Profile {
@@ -649,9 +1369,190 @@ mod tests {
             gpg_format: None,
             tag_gpg_sign: None,
             is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_profile_usage_groups_repos_by_email() {
+        let mut work = profile_with_source("/home/user/.gitconfig-work");
+        work.name = "work".to_string();
+        work.user_email = Some("jane@work.com".to_string());
+        let mut oss = profile_with_source("/home/user/.gitconfig-oss");
+        oss.name = "oss".to_string();
+        oss.user_email = Some("jane@oss.org".to_string());
+        let mut local = profile_with_source("/home/user/work/api/.git/config");
+        local.user_email = Some("other@home.net".to_string());
+        let profiles = vec![work, oss, local];
+
+        let repos: Vec<PathBuf> = ["api", "web", "blog", "loose"]
+            .iter()
+            .map(|r| PathBuf::from(format!("/home/user/{r}")))
+            .collect();
+        let emails = vec![
+            Some("other@home.net".to_string()),
+            Some("Jane@Work.com".to_string()),
+            None,
+            Some("jane@work.com".to_string()),
+        ];
+
+        let usage = profile_usage(&profiles, &repos, &emails);
+
+        // Local profiles are not listed and never match
+        let names: Vec<_> = usage
+            .by_profile
+            .iter()
+            .map(|(p, _)| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["work", "oss"]);
+        assert_eq!(usage.by_profile[0].1, [&repos[1], &repos[3]]);
+        assert!(usage.by_profile[1].1.is_empty());
+        assert_eq!(usage.unassigned, [(&repos[0], "other@home.net")]);
+        assert_eq!(usage.inherited, 1);
+    }
+
+    #[test]
+    fn test_porcelain_line_full() {
+        let mut profile = profile_with_source("/home/user/.gitconfig-work");
+        profile.user_name = Some("Jane Doe".to_string());
+        profile.user_email = Some("jane@work.com".to_string());
+        profile.signing_key = Some("ABC123".to_string());
+        profile.gpg_sign = Some(true);
+        assert_eq!(
+            porcelain_line(&profile),
+            "test\t/home/user/.gitconfig-work\tJane Doe\tjane@work.com\tABC123\ttrue"
+        );
+    }
+
+    #[test]
+    fn test_porcelain_line_empty_fields() {
+        let mut profile = profile_with_source("/home/user/.gitconfig-work");
+        profile.user_name = None;
+        profile.user_email = None;
+        assert_eq!(
+            porcelain_line(&profile),
+            "test\t/home/user/.gitconfig-work\t\t\t\t"
+        );
+    }
+
+    #[test]
+    fn test_raw_config_lines() {
+        let content = "# comment\n[user]\n\tname = Jane\n[alias]\n\tco = checkout\n";
+        let lines: Vec<String> = raw_config_lines(content)
+            .iter()
+            .map(|l| console::strip_ansi_codes(l).into_owned())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "    │ # comment",
+                "    │ [user]",
+                "    │ \tname = Jane",
+                "    │ [alias]",
+                "    │ \tco = checkout",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raw_config_lines_escape_control_characters() {
+        let content = "[user]\n\temail = a@b.c\x1b[2K\r\x1b[1A\n";
+        let lines = raw_config_lines(content);
+        assert!(lines.iter().all(|l| !l.contains('\r')));
+        let plain = console::strip_ansi_codes(&lines[1]).into_owned();
+        assert_eq!(plain, "    │ \temail = a@b.c\\u{1b}[2K\\r\\u{1b}[1A");
+    }
+
+    #[test]
+    fn test_create_private_file_refuses_existing_paths() {
+        let dir = std::env::temp_dir().join("yarm-test-private-file");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("import.gitconfig");
+        create_private_file(&path, b"[user]\n").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"[user]\n");
+        assert!(create_private_file(&path, b"other").is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"[user]\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+                0o600
+            );
+
+            let victim = dir.join("victim");
+            fs::write(&victim, "keep").unwrap();
+            let link = dir.join("planted.gitconfig");
+            std::os::unix::fs::symlink(&victim, &link).unwrap();
+            assert!(create_private_file(&link, b"evil").is_err());
+            assert_eq!(fs::read_to_string(&victim).unwrap(), "keep");
         }
     }
 
+    #[test]
+    fn test_group_by_directory() {
+        let profiles = vec![
+            profile_with_source("/home/user/.gitconfig"),
+            profile_with_source("/home/user/.config/git/work.gitconfig"),
+            profile_with_source("/home/user/.gitconfig-oss"),
+        ];
+
+        let groups: Vec<(&Path, usize)> = group_by_directory(&profiles)
+            .into_iter()
+            .map(|(dir, group)| (dir, group.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Path::new("/home/user"), 2),
+                (Path::new("/home/user/.config/git"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let mut profile = profile_with_source("/home/user/.gitconfig-work");
+        profile.user_email = Some("jane@Work.co.com".to_string());
+
+        assert!(matches_filter(&profile, &ProfileFilter::default()));
+
+        let signing = ProfileFilter {
+            with_signing: true,
+            email_domain: None,
+        };
+        assert!(!matches_filter(&profile, &signing));
+
+        let domain = |d: &str| ProfileFilter {
+            with_signing: false,
+            email_domain: Some(d.to_string()),
+        };
+        assert!(matches_filter(&profile, &domain("work.co.com")));
+        assert!(matches_filter(&profile, &domain("@WORK.co.com")));
+        assert!(!matches_filter(&profile, &domain("co.com")));
+
+        // Conditions compose
+        profile.signing_key = Some("ABC123".to_string());
+        let both = ProfileFilter {
+            with_signing: true,
+            email_domain: Some("work.co.com".to_string()),
+        };
+        assert!(matches_filter(&profile, &both));
+        profile.user_email = None;
+        assert!(!matches_filter(&profile, &both));
+    }
+
+    #[test]
+    fn test_ensure_editable_rejects_system() {
+        assert!(ensure_editable(&profile_with_source("/etc/gitconfig")).is_err());
+        assert!(ensure_editable(&profile_with_source("/home/user/.gitconfig")).is_ok());
+    }
+
     #[test]
     fn test_is_deletable_custom_profile() {
         assert!(is_deletable(&profile_with_source(
@@ -676,10 +1577,55 @@ mod tests {
         assert!(!is_deletable(&profile_with_source("/etc/gitconfig")));
     }
 
+    #[test]
+    fn test_is_deletable_user_file_under_etc_directory() {
+        let profile = profile_with_source("/home/user/dotfiles/etc/work.gitconfig");
+        assert!(is_deletable(&profile));
+        assert!(ensure_editable(&profile).is_ok());
+    }
+
     #[test]
     fn test_is_not_deletable_local_git_config() {
         assert!(!is_deletable(&profile_with_source(
             "/home/user/project/.git/config"
         )));
     }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_is_deletable_windows_paths() {
+        assert!(is_deletable(&profile_with_source(
+            r"C:\Users\u\.gitconfig-work"
+        )));
+        assert!(!is_deletable(&profile_with_source(
+            r"C:\Users\u\.gitconfig"
+        )));
+        assert!(!is_deletable(&profile_with_source(
+            r"C:\Users\u\project\.git\config"
+        )));
+    }
+
+    #[test]
+    fn test_import_download_url_requires_https() {
+        assert!(import_download_url("http://example.com/work.gitconfig").is_err());
+        assert!(import_download_url("file:///etc/gitconfig").is_err());
+        assert!(import_download_url("https://").is_err());
+        assert_eq!(
+            import_download_url(" https://example.com/work.gitconfig ").unwrap(),
+            "https://example.com/work.gitconfig"
+        );
+    }
+
+    #[test]
+    fn test_import_download_url_maps_gists_to_raw() {
+        assert_eq!(
+            import_download_url("https://gist.github.com/user/abc123/").unwrap(),
+            "https://gist.github.com/user/abc123/raw"
+        );
+        // Already raw, or pointing at a specific file
+        assert_eq!(
+            import_download_url("https://gist.github.com/user/abc123/raw/work.gitconfig").unwrap(),
+            "https://gist.github.com/user/abc123/raw/work.gitconfig"
+        );
+    }
 }
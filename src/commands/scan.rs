@@ -2,16 +2,47 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use console::style;
 use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use indicatif::ProgressBar;
+use std::collections::{BTreeSet, HashSet};
 
+use crate::config::{MARKER_FILE, read_marker};
 use crate::state::State;
-use crate::term::{print_success, print_warning};
+use crate::term::{eprint_warning, format_home_path, print_success, print_warning};
 
 /// Directories to skip during recursive scanning
 const SKIP_DIRS: &[&str] = &["node_modules", "target", "vendor", "__pycache__", ".build"];
 
-/// Executes the scan command flow
-pub fn run() -> Result<()> {
+/// How scan results are reported
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Per-pool counts followed by the summary line
+    Full,
+    /// Only the summary line
+    Summary,
+    /// Repository paths on stdout, one per line, without styling
+    Porcelain,
+    /// Only the number of repositories found
+    Count,
+}
+
+/// Executes the scan command flow.
+/// With `verbose`, the paths of new and removed repositories are listed.
+/// With `recurse_nested`, repositories nested inside other repositories' working trees are found too.
+/// `output` selects between the full report, porcelain paths, and a bare count.
+/// With `dry_run`, the result is reported but the state is not updated.
+pub fn run(verbose: bool, recurse_nested: bool, output: Output, dry_run: bool) -> Result<()> {
+    scan(verbose, recurse_nested, output, dry_run)
+}
+
+/// Rescans pools in the background of another command, printing only the summary line.
+pub fn run_auto() -> Result<()> {
+    scan(false, false, Output::Summary, false)
+}
+
+/// Scans all pools, saves the result (unless `dry_run`), and reports it according to `output`.
+fn scan(verbose: bool, recurse_nested: bool, output: Output, dry_run: bool) -> Result<()> {
     let config = crate::config::load()?;
     let pools = config.pool_paths();
 
@@ -26,23 +57,42 @@ pub fn run() -> Result<()> {
 
     let exclude = build_exclude_set(&config.repositories.exclude)?;
 
-    let spinner = crate::term::spinner("");
+    let quiet = matches!(output, Output::Porcelain | Output::Count);
+    let spinner = if quiet {
+        ProgressBar::hidden()
+    } else {
+        crate::term::spinner("")
+    };
 
     let mut repos = Vec::new();
     let mut pool_count = 0;
+    let mut scanned_pools = Vec::new();
+
+    if !quiet {
+        crate::config::warn_overlapping_pools(&pools);
+    }
 
     for pool in &pools {
         if !pool.is_dir() {
-            spinner.suspend(|| {
-                print_warning(format!("Pool directory not found: {}", pool.display()));
-            });
+            let message = format!("Pool directory not found: {}", pool.display());
+            if quiet {
+                eprint_warning(message);
+            } else {
+                spinner.suspend(|| print_warning(message));
+            }
             continue;
         }
 
         pool_count += 1;
         spinner.set_message(format!("Scanning {}...", pool.display()));
 
-        let found = scan_directory(pool, &exclude, config.repositories.max_depth);
+        let found = scan_directory(
+            pool,
+            &exclude,
+            config.repositories.max_depth,
+            recurse_nested,
+        );
+        scanned_pools.push(pool.clone());
         repos.extend(found);
     }
 
@@ -54,29 +104,179 @@ pub fn run() -> Result<()> {
 
     repos.sort();
     repos.dedup();
+    let pool_counts = count_by_pool(&repos, &scanned_pools);
 
-    let mut state = State {
-        repositories: repos.clone(),
-        ..State::default()
-    };
-    state.mark_scanned();
-    crate::state::save(&state)?;
+    let previous = crate::state::load().unwrap_or_default();
+
+    let mut state = scanned_state(&repos);
+    state.pinned_repos = crate::commands::pin::retained_pins(&previous.pinned_repos, &repos);
+    save_scanned(&state, crate::state::state_path().as_deref(), dry_run)?;
+    if dry_run && quiet {
+        eprint_warning("(dry run, state not updated)");
+    }
+
+    match output {
+        Output::Porcelain => {
+            for repo in &repos {
+                println!("{}", repo.display());
+            }
+            return Ok(());
+        }
+        Output::Count => {
+            println!("{}", repos.len());
+            return Ok(());
+        }
+        Output::Full | Output::Summary => {}
+    }
 
     println!();
-    let repo_label = if repos.len() == 1 {
-        "repository"
-    } else {
-        "repositories"
-    };
+    if output == Output::Full {
+        print_pool_counts(&pool_counts);
+        println!();
+    }
+    let repo_label = repo_label(repos.len());
     let pool_label = if pool_count == 1 { "pool" } else { "pools" };
     print_success(format!(
         "Found {} {repo_label} across {pool_count} {pool_label}",
         repos.len()
     ));
+    if output == Output::Full && !state.empty_repos.is_empty() {
+        print_empty_repos(&state.empty_repos, verbose);
+    }
+
+    // Only report changes relative to an actual previous scan
+    if previous.last_scan.is_some() {
+        let (added, removed) = diff_repos(&previous.repositories, &repos);
+        print_changes(&added, &removed, verbose);
+    }
+    if dry_run {
+        print_warning("(dry run, state not updated)");
+    }
 
     Ok(())
 }
 
+/// Saves the scanned state to the state file at `path`, unless this is a dry run
+fn save_scanned(state: &State, path: Option<&Path>, dry_run: bool) -> Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+    let Some(path) = path else {
+        anyhow::bail!("Could not determine data directory");
+    };
+    crate::state::save_to(path, state)
+}
+
+/// Builds the state for freshly scanned repositories, with their pinned profiles
+/// and which of them have no commits yet
+fn scanned_state(repos: &[PathBuf]) -> State {
+    let pinned_profiles = repos
+        .iter()
+        .filter_map(|repo| Some((repo.clone(), read_marker(repo)?.profile?)))
+        .collect();
+    let empty_repos = repos
+        .iter()
+        .filter(|repo| crate::git::is_empty_repository(repo))
+        .cloned()
+        .collect();
+    let mut state = State {
+        repositories: repos.to_vec(),
+        pinned_profiles,
+        empty_repos,
+        ..State::default()
+    };
+    state.mark_scanned();
+    state
+}
+
+/// Reports repositories without commits, listing them when `verbose`
+fn print_empty_repos(empty: &BTreeSet<PathBuf>, verbose: bool) {
+    print_warning(format!(
+        "{} empty {} without commits",
+        empty.len(),
+        repo_label(empty.len())
+    ));
+    if verbose {
+        for repo in empty {
+            println!("    {}", style(format_home_path(repo)).dim());
+        }
+    }
+}
+
+/// Returns the singular or plural noun for a repository count
+fn repo_label(count: usize) -> &'static str {
+    if count == 1 {
+        "repository"
+    } else {
+        "repositories"
+    }
+}
+
+/// Counts repositories per pool, attributing each to its most specific pool
+fn count_by_pool(repos: &[PathBuf], pools: &[PathBuf]) -> Vec<(PathBuf, usize)> {
+    pools
+        .iter()
+        .map(|pool| {
+            let count = repos
+                .iter()
+                .filter(|r| crate::config::assign_pool(r, pools) == Some(pool.as_path()))
+                .count();
+            (pool.clone(), count)
+        })
+        .collect()
+}
+
+/// Prints each scanned pool with the number of repositories found in it
+fn print_pool_counts(pool_counts: &[(PathBuf, usize)]) {
+    for (pool, count) in pool_counts {
+        let bullet = if *count == 0 {
+            style("•").dim()
+        } else {
+            style("•").cyan()
+        };
+        println!(
+            "    {bullet} {} {}",
+            format_home_path(pool),
+            style(format!("({count} {})", repo_label(*count))).dim()
+        );
+    }
+}
+
+/// Returns repositories that are new in `current` and those no longer present compared to `previous`.
+fn diff_repos(previous: &[PathBuf], current: &[PathBuf]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let before: HashSet<&PathBuf> = previous.iter().collect();
+    let after: HashSet<&PathBuf> = current.iter().collect();
+
+    let mut added: Vec<PathBuf> = after.difference(&before).map(|p| (*p).clone()).collect();
+    let mut removed: Vec<PathBuf> = before.difference(&after).map(|p| (*p).clone()).collect();
+    added.sort();
+    removed.sort();
+
+    (added, removed)
+}
+
+/// Prints the change summary since the last scan, with paths if `verbose`
+fn print_changes(added: &[PathBuf], removed: &[PathBuf], verbose: bool) {
+    if added.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    println!(
+        "    {} {}",
+        style(format!("+ {} new,", added.len())).green(),
+        style(format!("- {} gone", removed.len())).red()
+    );
+
+    if verbose {
+        for path in added {
+            println!("      {} {}", style("+").green(), format_home_path(path));
+        }
+        for path in removed {
+            println!("      {} {}", style("-").red(), format_home_path(path));
+        }
+    }
+}
+
 /// Builds a `GlobSet` from the configured exclude patterns.
 fn build_exclude_set(patterns: &[String]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
@@ -91,10 +291,17 @@ fn build_exclude_set(patterns: &[String]) -> Result<GlobSet> {
 }
 
 /// Recursively scans a directory for git repositories.
-/// Returns the paths of directories containing a `.git` subdirectory.
+/// Returns the paths of directories containing a `.git` subdirectory,
+/// as well as bare repositories (e.g. mirror clones).
 /// When `max_depth` is `Some(n)`, only directories up to `n` levels below the root are visited.
 /// Depth 0 means only the root itself is checked; `None` means unlimited.
-fn scan_directory(root: &Path, exclude: &GlobSet, max_depth: Option<u32>) -> Vec<PathBuf> {
+/// With `recurse_nested`, the working trees of found repositories are searched for nested repositories.
+fn scan_directory(
+    root: &Path,
+    exclude: &GlobSet,
+    max_depth: Option<u32>,
+    recurse_nested: bool,
+) -> Vec<PathBuf> {
     let mut repos = Vec::new();
     let mut stack: Vec<(PathBuf, u32)> = vec![(root.to_path_buf(), 0)];
 
@@ -113,9 +320,13 @@ fn scan_directory(root: &Path, exclude: &GlobSet, max_depth: Option<u32>) -> Vec
                 continue;
             };
 
-            // .git can be a directory (regular repo) or a file (submodule/worktree)
-            if name == ".git" {
+            // .git can be a directory (regular repo) or a file (submodule/worktree);
+            // a .yarm marker tracks the directory even without one
+            if name == ".git" || name == MARKER_FILE {
                 is_repo = true;
+                if recurse_nested {
+                    continue;
+                }
                 break;
             }
 
@@ -136,9 +347,14 @@ fn scan_directory(root: &Path, exclude: &GlobSet, max_depth: Option<u32>) -> Vec
             subdirs.push(path);
         }
 
-        if is_repo {
+        // Bare repositories have no working tree to descend into
+        let is_bare = !is_repo && crate::git::is_bare_repository(&dir);
+        let descend = if is_repo { recurse_nested } else { !is_bare };
+
+        if is_repo || is_bare {
             repos.push(dir);
-        } else if max_depth.is_none_or(|limit| depth < limit) {
+        }
+        if descend && max_depth.is_none_or(|limit| depth < limit) {
             stack.extend(subdirs.into_iter().map(|p| (p, depth + 1)));
         }
     }
@@ -155,6 +371,32 @@ mod tests {
         GlobSetBuilder::new().build().unwrap()
     }
 
+    #[test]
+    fn test_dry_run_leaves_state_unchanged() {
+        let tmp = tempdir("dry-run");
+        let pool = tmp.join("pool");
+        fs::create_dir_all(pool.join("old/.git")).unwrap();
+        fs::create_dir_all(pool.join("new/.git")).unwrap();
+        let state_file = tmp.join("state.bin");
+
+        let previous = scanned_state(&[pool.join("old"), pool.join("gone")]);
+        crate::state::save_to(&state_file, &previous).unwrap();
+        let before = fs::read(&state_file).unwrap();
+
+        let mut repos = scan_directory(&pool, &empty_exclude(), None, false);
+        repos.sort();
+        let state = scanned_state(&repos);
+
+        save_scanned(&state, Some(&state_file), true).unwrap();
+        assert_eq!(fs::read(&state_file).unwrap(), before);
+
+        save_scanned(&state, Some(&state_file), false).unwrap();
+        let saved = crate::state::load_from(&state_file).unwrap();
+        assert_eq!(saved.repositories, [pool.join("new"), pool.join("old")]);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
     #[test]
     fn test_scan_finds_repos() {
         let tmp = tempdir("finds-repos");
@@ -166,7 +408,7 @@ mod tests {
         fs::create_dir_all(repo_b.join(".git")).unwrap();
         fs::create_dir_all(&not_repo).unwrap();
 
-        let mut repos = scan_directory(&tmp, &empty_exclude(), None);
+        let mut repos = scan_directory(&tmp, &empty_exclude(), None, false);
         repos.sort();
 
         assert_eq!(repos.len(), 2);
@@ -174,6 +416,20 @@ mod tests {
         assert_eq!(repos[1], repo_b);
     }
 
+    #[test]
+    fn test_count_by_pool_nested_pools() {
+        let pools = vec![PathBuf::from("/p"), PathBuf::from("/p/work")];
+        let repos = vec![
+            PathBuf::from("/p/tool"),
+            PathBuf::from("/p/work/api"),
+            PathBuf::from("/p/work/web"),
+        ];
+        assert_eq!(
+            count_by_pool(&repos, &pools),
+            [(PathBuf::from("/p"), 1), (PathBuf::from("/p/work"), 2)]
+        );
+    }
+
     #[test]
     fn test_scan_skips_hidden_dirs() {
         let tmp = tempdir("skips-hidden");
@@ -183,7 +439,7 @@ mod tests {
         fs::create_dir_all(visible.join(".git")).unwrap();
         fs::create_dir_all(hidden.join(".git")).unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], visible);
@@ -198,7 +454,7 @@ mod tests {
         fs::create_dir_all(real_repo.join(".git")).unwrap();
         fs::create_dir_all(nm_repo.join(".git")).unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], real_repo);
@@ -212,12 +468,39 @@ mod tests {
 
         fs::create_dir_all(inner.join(".git")).unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], inner);
     }
 
+    #[test]
+    fn test_scan_repo_inside_repo_default_not_recursed() {
+        let tmp = tempdir("repo-in-repo-default");
+        let outer = tmp.join("mono");
+        fs::create_dir_all(outer.join(".git")).unwrap();
+        fs::create_dir_all(outer.join("vendor-libs/lib/.git")).unwrap();
+
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
+
+        assert_eq!(repos, vec![outer]);
+    }
+
+    #[test]
+    fn test_scan_repo_inside_repo_recurse_nested() {
+        let tmp = tempdir("repo-in-repo-recurse");
+        let outer = tmp.join("mono");
+        let inner = outer.join("third_party/lib");
+        fs::create_dir_all(outer.join(".git")).unwrap();
+        fs::create_dir_all(inner.join(".git")).unwrap();
+        fs::create_dir_all(outer.join("node_modules/dep/.git")).unwrap();
+
+        let mut repos = scan_directory(&tmp, &empty_exclude(), None, true);
+        repos.sort();
+
+        assert_eq!(repos, vec![outer, inner]);
+    }
+
     #[test]
     fn test_scan_detects_git_file() {
         let tmp = tempdir("git-file");
@@ -226,16 +509,44 @@ mod tests {
         fs::create_dir_all(&submodule).unwrap();
         fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/sub").unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], submodule);
     }
 
+    #[test]
+    fn test_scan_detects_marker_file() {
+        let tmp = tempdir("marker-file");
+        let planned = tmp.join("planned");
+        fs::create_dir_all(&planned).unwrap();
+        fs::write(planned.join(MARKER_FILE), "profile = \"work\"\n").unwrap();
+        fs::create_dir_all(tmp.join("plain")).unwrap();
+
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
+
+        assert_eq!(repos, vec![planned]);
+    }
+
+    #[test]
+    fn test_scan_detects_bare_repo() {
+        let tmp = tempdir("bare-repo");
+        let mirror = tmp.join("project.git");
+
+        fs::create_dir_all(mirror.join("objects")).unwrap();
+        fs::create_dir_all(mirror.join("refs").join("heads")).unwrap();
+        fs::write(mirror.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0], mirror);
+    }
+
     #[test]
     fn test_scan_empty_directory() {
         let tmp = tempdir("empty");
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
         assert!(repos.is_empty());
     }
 
@@ -249,7 +560,7 @@ mod tests {
         fs::create_dir_all(excluded.join("nested-repo").join(".git")).unwrap();
 
         let exclude = build_exclude_set(&["build-output".to_string()]).unwrap();
-        let repos = scan_directory(&tmp, &exclude, None);
+        let repos = scan_directory(&tmp, &exclude, None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], kept);
@@ -267,7 +578,7 @@ mod tests {
         fs::create_dir_all(excluded_b.join("repo").join(".git")).unwrap();
 
         let exclude = build_exclude_set(&["*-build".to_string()]).unwrap();
-        let repos = scan_directory(&tmp, &exclude, None);
+        let repos = scan_directory(&tmp, &exclude, None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], kept);
@@ -283,7 +594,7 @@ mod tests {
         fs::create_dir_all(excluded.join("dep").join(".git")).unwrap();
 
         let exclude = build_exclude_set(&["project/external".to_string()]).unwrap();
-        let repos = scan_directory(&tmp, &exclude, None);
+        let repos = scan_directory(&tmp, &exclude, None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], kept);
@@ -295,7 +606,7 @@ mod tests {
         fs::create_dir_all(tmp.join(".git")).unwrap();
         fs::create_dir_all(tmp.join("child").join(".git")).unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), Some(0));
+        let repos = scan_directory(&tmp, &empty_exclude(), Some(0), false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], tmp);
@@ -312,11 +623,11 @@ mod tests {
         fs::create_dir_all(shallow.join(".git")).unwrap();
         fs::create_dir_all(deep.join(".git")).unwrap();
 
-        let repos_limited = scan_directory(&tmp, &empty_exclude(), Some(2));
+        let repos_limited = scan_directory(&tmp, &empty_exclude(), Some(2), false);
         assert_eq!(repos_limited.len(), 1);
         assert_eq!(repos_limited[0], shallow);
 
-        let repos_unlimited = scan_directory(&tmp, &empty_exclude(), None);
+        let repos_unlimited = scan_directory(&tmp, &empty_exclude(), None, false);
         assert_eq!(repos_unlimited.len(), 2);
     }
 
@@ -326,12 +637,34 @@ mod tests {
         let deep = tmp.join("a").join("b").join("c").join("repo");
         fs::create_dir_all(deep.join(".git")).unwrap();
 
-        let repos = scan_directory(&tmp, &empty_exclude(), None);
+        let repos = scan_directory(&tmp, &empty_exclude(), None, false);
 
         assert_eq!(repos.len(), 1);
         assert_eq!(repos[0], deep);
     }
 
+    #[test]
+    fn test_diff_repos() {
+        let previous = vec![PathBuf::from("/p/a"), PathBuf::from("/p/b")];
+        let current = vec![
+            PathBuf::from("/p/b"),
+            PathBuf::from("/p/d"),
+            PathBuf::from("/p/c"),
+        ];
+
+        let (added, removed) = diff_repos(&previous, &current);
+        assert_eq!(added, vec![PathBuf::from("/p/c"), PathBuf::from("/p/d")]);
+        assert_eq!(removed, vec![PathBuf::from("/p/a")]);
+    }
+
+    #[test]
+    fn test_diff_repos_unchanged() {
+        let repos = vec![PathBuf::from("/p/a")];
+        let (added, removed) = diff_repos(&repos, &repos);
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+    }
+
     fn tempdir(name: &str) -> PathBuf {
         let dir = std::env::temp_dir().join(format!("yarm-test-{name}"));
         let _ = fs::remove_dir_all(&dir);
@@ -1,88 +1,447 @@
+use std::fmt::Write;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use anyhow::Result;
-use console::style;
+use console::{Term, style, truncate_str};
 
 use crate::git;
-use crate::term::{SilentExit, format_elapsed, print_header, print_warning};
+use crate::profile::{Profile, discover_profiles, profile_for_email};
+use crate::term::{
+    SilentExit, Table, format_time, is_interactive, link, print_header, print_warning,
+};
 
-/// Executes the stat command flow
-pub fn run(repo: Option<String>) -> Result<()> {
+/// How `stat` renders the repository values
+#[derive(Debug)]
+pub enum Layout {
+    /// Every field as a table (the default)
+    Full,
+    /// Only the named fields, in the given order
+    Fields(Vec<String>),
+    /// A single line from a template with `{field}` placeholders
+    Format(String),
+}
+
+/// Field names accepted by `--fields` and `--format`
+const FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "description",
+    "branch",
+    "default_branch",
+    "remote",
+    "dirty",
+    "size",
+    "files",
+    "dirs",
+    "lfs",
+    "hooks",
+    "last_fetch",
+    "identity",
+];
+
+/// Fields that need the directory walk (or the LFS listing) to be computed
+const SIZE_FIELDS: &[&str] = &["size", "files", "dirs", "lfs"];
+
+/// Values computed for a repository, shared by all layouts
+struct RepoValues {
+    path: PathBuf,
+    description: Option<String>,
+    branch: String,
+    default_branch: Option<String>,
+    remotes: Vec<git::Remote>,
+    dirty: bool,
+    /// `None` when the size walk was skipped
+    stats: Option<DirStats>,
+    lfs: Option<(u64, u64)>,
+    hooks: Vec<String>,
+    fetch_time: Option<SystemTime>,
+    identity: Identity,
+}
+
+/// The repository's local identity, matched against the discovered profiles
+#[derive(Debug, PartialEq, Eq)]
+enum Identity {
+    /// The local `user.email` belongs to this profile
+    Profile(String),
+    /// A local `user.email` that no profile defines
+    Unmanaged(String),
+    /// No local `user.email`, the inherited identity applies
+    Inherited,
+}
+
+/// Executes the stat command flow.
+/// With `branches`, all local branches are listed with their upstream divergence.
+/// With `no_size`, the size walk is skipped; it is also skipped when the layout shows no size field.
+pub fn run(repo: Option<String>, branches: bool, layout: &Layout, no_size: bool) -> Result<()> {
     git::ensure_available()?;
+    match layout {
+        Layout::Full => {}
+        Layout::Fields(fields) => check_fields(fields.iter().map(String::as_str))?,
+        Layout::Format(format) => check_fields(template_fields(format))?,
+    }
 
     let repo_path = resolve_target(repo)?;
-    let display_name = repo_path
+    let values = collect_values(
+        repo_path,
+        !no_size && shows_size(layout),
+        shows_any(layout, &["default_branch"]),
+    )?;
+
+    match layout {
+        Layout::Full => print_full(&values, branches)?,
+        Layout::Fields(fields) => {
+            let mut table = Table::new();
+            for field in fields {
+                table.row(format!("{field}:"), field_value(&values, field));
+            }
+            table.print();
+        }
+        Layout::Format(format) => println!("{}", render_template(format, &values)),
+    }
+
+    Ok(())
+}
+
+/// Whether the layout shows any value from [`SIZE_FIELDS`]
+fn shows_size(layout: &Layout) -> bool {
+    shows_any(layout, SIZE_FIELDS)
+}
+
+/// Whether the layout shows any of `names`; the full view shows all fields
+fn shows_any(layout: &Layout, names: &[&str]) -> bool {
+    match layout {
+        Layout::Full => true,
+        Layout::Fields(fields) => fields.iter().any(|f| names.contains(&f.as_str())),
+        Layout::Format(format) => template_fields(format).any(|f| names.contains(&f)),
+    }
+}
+
+/// Gathers everything `stat` can show about the repository at `path`.
+/// The size walk and LFS listing only run with `measure_size`, and the default branch
+/// (which may query the remote) only with `lookup_default_branch`.
+fn collect_values(
+    path: PathBuf,
+    measure_size: bool,
+    lookup_default_branch: bool,
+) -> Result<RepoValues> {
+    let branch = git::current_branch(&path)?;
+    let remotes = git::remotes(&path)?;
+    let dirty = git::is_dirty(&path)?;
+    let fetch_time = last_fetch_time(&path);
+    let default_branch = if lookup_default_branch {
+        git::default_branch(&path)?
+    } else {
+        None
+    };
+    let identity = match_identity(git::get_config(&path, "user.email"), &discover_profiles()?);
+
+    let (stats, lfs) = if measure_size {
+        let spinner = crate::term::spinner("Calculating size...");
+
+        let stats = dir_stats(&path);
+        let lfs = uses_lfs(&path)
+            .then(|| git::lfs_files_size(&path))
+            .flatten();
+
+        spinner.finish_and_clear();
+        (Some(stats), lfs)
+    } else {
+        (None, None)
+    };
+
+    Ok(RepoValues {
+        description: repo_description(&path),
+        hooks: active_hooks(&path),
+        path,
+        branch,
+        default_branch,
+        remotes,
+        dirty,
+        stats,
+        lfs,
+        fetch_time,
+        identity,
+    })
+}
+
+/// Finds the profile providing `local_email` (see [`profile_for_email`])
+fn match_identity(local_email: Option<String>, profiles: &[Profile]) -> Identity {
+    let Some(email) = local_email else {
+        return Identity::Inherited;
+    };
+
+    profile_for_email(profiles, &email).map_or(Identity::Unmanaged(email), |p| {
+        Identity::Profile(p.name.clone())
+    })
+}
+
+/// Prints the default view: header, description, and a table of all fields
+fn print_full(values: &RepoValues, branches: bool) -> Result<()> {
+    let display_name = values
+        .path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown");
 
-    print_header("Repository:", display_name);
+    print_header("Repository:", link(display_name, &values.path));
+    if let Some(description) = &values.description {
+        let width = usize::from(Term::stdout().size().1).saturating_sub(2);
+        println!("  {}", style(truncate_str(description, width, "…")).dim());
+    }
     println!();
 
-    let branch = git::current_branch(&repo_path)?;
-    let remotes = git::remotes(&repo_path)?;
-    let dirty = git::is_dirty(&repo_path)?;
-    let fetch_time = last_fetch_time(&repo_path);
-
-    let spinner = crate::term::spinner("Calculating size...");
-
-    let (total_size, file_count, dir_count) = dir_stats(&repo_path);
-
-    spinner.finish_and_clear();
+    let branch = &values.branch;
+    let local_branches = if branches {
+        git::branches(&values.path)?
+    } else {
+        Vec::new()
+    };
 
-    print_field("Branch:", &branch);
-    if remotes.is_empty() {
-        print_field("Remotes:", &style("(none)").dim().to_string());
+    let mut table = Table::new();
+    for (i, b) in local_branches.iter().enumerate() {
+        let label = if i == 0 { "Branches:" } else { "" };
+        let name = if b.name == *branch {
+            style(&b.name).bold().to_string()
+        } else {
+            b.name.clone()
+        };
+        table.row(label, format!("{name}{}", format_tracking(b)));
+    }
+    if local_branches.is_empty() {
+        table.row("Branch:", branch);
+    }
+    if let Some(default) = values.default_branch.as_ref().filter(|d| *d != branch) {
+        table.row("Default branch:", style(default).yellow().to_string());
+    }
+    if values.remotes.is_empty() {
+        table.row("Remotes:", style("(none)").dim().to_string());
     } else {
-        for (i, (name, url)) in remotes.iter().enumerate() {
-            let label = if i == 0 {
-                "Remotes:".to_string()
-            } else {
-                String::new()
-            };
-            print_field(&label, &format!("{} {}", style(name).cyan(), url));
+        for (i, remote) in values.remotes.iter().enumerate() {
+            let label = if i == 0 { "Remotes:" } else { "" };
+            let forge = git::parse_git_url(&remote.fetch_url)
+                .and_then(|u| forge_label(&u.host))
+                .map(|f| format!(" {}", style(format!("({f})")).dim()))
+                .unwrap_or_default();
+            table.row(
+                label,
+                format!("{} {}{forge}", style(&remote.name).cyan(), remote.fetch_url),
+            );
+            if let Some(push_url) = &remote.push_url {
+                table.row("", format!("{} {push_url}", style("  push").dim()));
+            }
         }
     }
-    print_field(
+    table.row(
         "Status:",
-        &if dirty {
+        if values.dirty {
             style("dirty").yellow().to_string()
         } else {
             style("clean").green().to_string()
         },
     );
-
-    print_field(
-        "Size:",
-        &format!(
-            "{} ({} files, {} directories)",
-            format_size(total_size),
-            format_count(file_count),
-            format_count(dir_count)
-        ),
+    table.row(
+        "Identity:",
+        match &values.identity {
+            Identity::Profile(name) => style(name).cyan().to_string(),
+            Identity::Unmanaged(email) => {
+                style(format!("(unmanaged: {email})")).yellow().to_string()
+            }
+            Identity::Inherited => style("(inherited)").dim().to_string(),
+        },
     );
-    print_field(
+
+    if let Some(stats) = &values.stats {
+        table.row("Size:", format_size_breakdown(stats));
+        if let Some((files, bytes)) = values.lfs {
+            table.row("LFS:", format_lfs(files, bytes));
+        }
+        table.row(
+            "Contents:",
+            format!(
+                "{} files, {} directories",
+                format_count(stats.files),
+                format_count(stats.dirs)
+            ),
+        );
+    }
+    if !values.hooks.is_empty() {
+        table.row(
+            "Hooks:",
+            style(values.hooks.join(", ")).yellow().to_string(),
+        );
+    }
+    table.row(
         "Last fetch:",
-        &match fetch_time {
-            Some(t) => format_elapsed(t),
+        match values.fetch_time {
+            Some(t) => format_time(t),
             None => style("(never)").dim().to_string(),
         },
     );
+    table.print();
+
+    Ok(())
+}
 
+/// Errors on the first name that is not in [`FIELDS`], listing the valid ones
+fn check_fields<'a>(mut names: impl Iterator<Item = &'a str>) -> Result<()> {
+    if let Some(unknown) = names.find(|name| !FIELDS.contains(name)) {
+        anyhow::bail!(
+            "Unknown field '{unknown}'. Valid fields: {}",
+            FIELDS.join(", ")
+        );
+    }
     Ok(())
 }
 
+/// Returns the `{field}` placeholder names in a format template
+fn template_fields(format: &str) -> impl Iterator<Item = &str> {
+    format
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+}
+
+/// Replaces each `{field}` placeholder in the template with its plain value
+fn render_template(format: &str, values: &RepoValues) -> String {
+    let mut out = format.to_string();
+    for field in FIELDS {
+        let placeholder = format!("{{{field}}}");
+        if out.contains(&placeholder) {
+            out = out.replace(&placeholder, &field_value(values, field));
+        }
+    }
+    out
+}
+
+/// Returns an unstyled value for a field name from [`FIELDS`]
+fn field_value(values: &RepoValues, field: &str) -> String {
+    match field {
+        "name" => values
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        "path" => values.path.display().to_string(),
+        "description" => values.description.clone().unwrap_or_default(),
+        "branch" => values.branch.clone(),
+        "default_branch" => values.default_branch.clone().unwrap_or_default(),
+        "remote" => values
+            .remotes
+            .iter()
+            .find(|r| r.name == "origin")
+            .or_else(|| values.remotes.first())
+            .map(|r| r.fetch_url.clone())
+            .unwrap_or_default(),
+        "dirty" => if values.dirty { "dirty" } else { "clean" }.to_string(),
+        "size" | "files" | "dirs" | "lfs" => size_value(values, field),
+        "hooks" => values.hooks.join(","),
+        "last_fetch" => values.fetch_time.map(format_time).unwrap_or_default(),
+        "identity" => match &values.identity {
+            Identity::Profile(name) => name.clone(),
+            Identity::Unmanaged(email) => format!("(unmanaged: {email})"),
+            Identity::Inherited => "(inherited)".to_string(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Returns the value of a field from [`SIZE_FIELDS`], or `(skipped)` without a size walk
+fn size_value(values: &RepoValues, field: &str) -> String {
+    let Some(stats) = &values.stats else {
+        return "(skipped)".to_string();
+    };
+    match field {
+        "size" => format_size(stats.total),
+        "files" => stats.files.to_string(),
+        "dirs" => stats.dirs.to_string(),
+        _ => values
+            .lfs
+            .map(|(files, bytes)| format_lfs(files, bytes))
+            .unwrap_or_default(),
+    }
+}
+
+/// Formats a branch's upstream and ahead/behind counts, e.g. ` → origin/main ↑2 ↓1`
+fn format_tracking(branch: &git::Branch) -> String {
+    let Some(upstream) = &branch.upstream else {
+        return String::new();
+    };
+    let mut parts = vec![format!("→ {upstream}")];
+    if branch.gone {
+        parts.push("(gone)".to_string());
+    }
+    if branch.ahead > 0 {
+        parts.push(format!("↑{}", branch.ahead));
+    }
+    if branch.behind > 0 {
+        parts.push(format!("↓{}", branch.behind));
+    }
+    format!(" {}", style(parts.join(" ")).dim())
+}
+
+/// git's placeholder content of `.git/description` in new repositories
+const DEFAULT_GIT_DESCRIPTION: &str = "Unnamed repository";
+
+/// Returns a one-line description from the README's first heading (or first line),
+/// falling back to `.git/description` unless it still holds git's placeholder.
+fn repo_description(path: &Path) -> Option<String> {
+    readme_description(path).or_else(|| {
+        let common_dir = git::common_dir(path).unwrap_or_else(|| path.join(".git"));
+        let content = fs::read_to_string(common_dir.join("description")).ok()?;
+        git_description(&content)
+    })
+}
+
+/// Reads the first `README*` file in `path` and extracts its description line
+fn readme_description(path: &Path) -> Option<String> {
+    let mut readmes: Vec<_> = fs::read_dir(path)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.to_ascii_uppercase().starts_with("README"))
+        })
+        .collect();
+    readmes.sort();
+    let content = fs::read_to_string(readmes.first()?).ok()?;
+    first_heading(&content)
+}
+
+/// Returns the first markdown heading without its `#` markers, or the first non-empty line
+fn first_heading(content: &str) -> Option<String> {
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+    let heading = content
+        .lines()
+        .map(str::trim)
+        .find(|l| l.starts_with('#'))
+        .map(|l| l.trim_start_matches('#').trim())
+        .filter(|l| !l.is_empty());
+    heading.or_else(|| lines.next()).map(String::from)
+}
+
+/// Returns the content of `.git/description` unless it is empty or git's placeholder
+fn git_description(content: &str) -> Option<String> {
+    let line = content.lines().next()?.trim();
+    (!line.is_empty() && !line.starts_with(DEFAULT_GIT_DESCRIPTION)).then(|| line.to_string())
+}
+
 fn resolve_target(repo: Option<String>) -> Result<PathBuf> {
     match repo {
         None => {
             let cwd = std::env::current_dir()?;
-            if !cwd.join(".git").exists() {
-                print_warning(format!("Not a git repository: {}", cwd.display()));
+            if let Some(root) = git::toplevel(&cwd) {
+                return Ok(root);
+            }
+            print_warning(format!("Not a git repository: {}", cwd.display()));
+            if !is_interactive() {
                 return Err(SilentExit(1).into());
             }
-            Ok(cwd)
+            super::pick_repo()?.ok_or_else(|| SilentExit(0).into())
         }
         Some(name_or_path) => {
             if let Ok(path) = super::find::resolve_repo(&name_or_path) {
@@ -97,44 +456,233 @@ fn resolve_target(repo: Option<String>) -> Result<PathBuf> {
     }
 }
 
-fn print_field(label: &str, value: &str) {
-    println!("    {:<14}{value}", style(label).bold());
+/// Returns a forge name for well-known hosts and common self-hosted naming patterns.
+fn forge_label(host: &str) -> Option<&'static str> {
+    let host = host.to_lowercase();
+    let first_label = host.split('.').next().unwrap_or_default();
+
+    match host.as_str() {
+        "github.com" => Some("GitHub"),
+        "gitlab.com" => Some("GitLab"),
+        "bitbucket.org" => Some("Bitbucket"),
+        "codeberg.org" => Some("Codeberg"),
+        "gitea.com" => Some("Gitea"),
+        _ if host.ends_with(".ghe.com") || first_label == "github" => Some("GitHub"),
+        _ if first_label == "gitlab" => Some("GitLab"),
+        _ if first_label == "bitbucket" => Some("Bitbucket"),
+        _ if first_label == "gitea" => Some("Gitea"),
+        _ => None,
+    }
 }
 
 fn last_fetch_time(repo: &Path) -> Option<SystemTime> {
     // FETCH_HEAD is written by `git fetch` and `git pull`, but not by `git clone`.
-    // Fall back to .git/HEAD mtime which is set during clone and on checkout/fetch.
-    let candidates = [".git/FETCH_HEAD", ".git/HEAD"];
+    // Fall back to the HEAD mtime which is set during clone and on checkout/fetch.
+    let git_dir = git::git_dir(repo).unwrap_or_else(|| repo.join(".git"));
+    let candidates = ["FETCH_HEAD", "HEAD"];
     candidates
         .iter()
-        .find_map(|f| fs::metadata(repo.join(f)).ok()?.modified().ok())
+        .find_map(|f| fs::metadata(git_dir.join(f)).ok()?.modified().ok())
+}
+
+/// Sizes and entry counts of a repository directory
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct DirStats {
+    /// Total size of all files in bytes
+    total: u64,
+    /// Size of the files below the top-level `.git` entry
+    git: u64,
+    files: u64,
+    dirs: u64,
+    /// Submodule working trees that were skipped
+    submodules: u64,
+}
+
+impl DirStats {
+    fn add(&mut self, other: &Self) {
+        self.total += other.total;
+        self.git += other.git;
+        self.files += other.files;
+        self.dirs += other.dirs;
+        self.submodules += other.submodules;
+    }
 }
 
-fn dir_stats(path: &Path) -> (u64, u64, u64) {
-    let mut total: u64 = 0;
-    let mut files: u64 = 0;
-    let mut dirs: u64 = 0;
-    let mut stack = vec![path.to_path_buf()];
+/// Walks the repository, accounting `.git` contents separately from the working tree.
+/// Submodule working trees (listed in `.gitmodules` or with a `.git` file) are not descended into.
+/// Top-level directories are walked concurrently; their totals are summed afterwards.
+fn dir_stats(path: &Path) -> DirStats {
+    let submodules = fs::read_to_string(path.join(".gitmodules"))
+        .map(|content| submodule_paths(&content))
+        .unwrap_or_default();
+
+    let mut stats = DirStats::default();
+    let mut subdirs = Vec::new();
+    visit_dir(path, false, path, &submodules, &mut stats, &mut subdirs);
+
+    let subdirs: Vec<_> = subdirs.into_iter().map(|(dir, _)| dir).collect();
+    let git_dir = path.join(".git");
+    let subtrees = git::batch(
+        &subdirs,
+        |dir| walk_tree(dir, dir == git_dir, path, &submodules),
+        || {},
+    );
+    for subtree in &subtrees {
+        stats.add(subtree);
+    }
+
+    stats
+}
 
-    while let Some(dir) = stack.pop() {
-        let Ok(entries) = fs::read_dir(&dir) else {
+/// Walks the tree below `start` on the current thread
+fn walk_tree(start: &Path, in_git: bool, root: &Path, submodules: &[PathBuf]) -> DirStats {
+    let mut stats = DirStats::default();
+    let mut stack = vec![(start.to_path_buf(), in_git)];
+    while let Some((dir, in_git)) = stack.pop() {
+        visit_dir(&dir, in_git, root, submodules, &mut stats, &mut stack);
+    }
+    stats
+}
+
+/// Accounts the entries of a single directory and queues its subdirectories
+fn visit_dir(
+    dir: &Path,
+    inside_git: bool,
+    root: &Path,
+    submodules: &[PathBuf],
+    stats: &mut DirStats,
+    subdirs: &mut Vec<(PathBuf, bool)>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
             continue;
         };
-        for entry in entries.flatten() {
-            let Ok(meta) = entry.metadata() else {
-                continue;
-            };
-            if meta.is_dir() {
-                dirs += 1;
-                stack.push(entry.path());
-            } else {
-                total += meta.len();
-                files += 1;
+        let is_git = inside_git || (dir == root && entry.file_name() == ".git");
+        if meta.is_dir() && !is_git && is_submodule(&entry.path(), root, submodules) {
+            stats.submodules += 1;
+        } else if meta.is_dir() {
+            stats.dirs += 1;
+            subdirs.push((entry.path(), is_git));
+        } else {
+            stats.total += meta.len();
+            stats.files += 1;
+            if is_git {
+                stats.git += meta.len();
             }
         }
     }
+}
 
-    (total, files, dirs)
+/// Lists the installed (executable, non-`.sample`) hooks, sorted by name.
+/// Honors `core.hooksPath` from any config scope, relative to the working tree.
+fn active_hooks(path: &Path) -> Vec<String> {
+    installed_hooks(&hooks_dir(
+        path,
+        git::get_config_path(path, "core.hooksPath"),
+    ))
+}
+
+/// The hooks directory git uses: the configured `hooks_path` (resolved against the
+/// working tree if relative), or `hooks` in the repository's git directory
+fn hooks_dir(path: &Path, hooks_path: Option<String>) -> PathBuf {
+    hooks_path.map_or_else(
+        || {
+            git::common_dir(path)
+                .unwrap_or_else(|| path.join(".git"))
+                .join("hooks")
+        },
+        |dir| path.join(dir),
+    )
+}
+
+/// Lists the executable, non-`.sample` files in `hooks_dir`, sorted by name
+fn installed_hooks(hooks_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(hooks_dir) else {
+        return Vec::new();
+    };
+
+    let mut hooks: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.metadata().is_ok_and(|m| m.is_file() && is_executable(&m)))
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| !name.ends_with(".sample"))
+        .collect();
+    hooks.sort();
+    hooks
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+/// Windows has no executable bit; git runs any hook file found
+#[cfg(not(unix))]
+fn is_executable(_meta: &fs::Metadata) -> bool {
+    true
+}
+
+/// Checks whether `.gitattributes` routes any files through the LFS filter
+fn uses_lfs(path: &Path) -> bool {
+    fs::read_to_string(path.join(".gitattributes"))
+        .is_ok_and(|content| content.contains("filter=lfs"))
+}
+
+/// Returns the `path = ...` entries of a `.gitmodules` file
+fn submodule_paths(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "path").then(|| PathBuf::from(value.trim()))
+        })
+        .collect()
+}
+
+/// Checks whether `dir` is a submodule working tree of the repository at `root`
+fn is_submodule(dir: &Path, root: &Path, submodules: &[PathBuf]) -> bool {
+    dir.join(".git").is_file()
+        || dir
+            .strip_prefix(root)
+            .is_ok_and(|rel| submodules.iter().any(|s| s == rel))
+}
+
+/// Formats the total size, split into `.git` and working tree when a `.git` directory exists.
+/// Skipped submodules are noted as `(+N submodules)`.
+fn format_size_breakdown(stats: &DirStats) -> String {
+    let mut size = if stats.git == 0 {
+        format_size(stats.total)
+    } else {
+        format!(
+            "{} ({} .git, {} worktree)",
+            format_size(stats.total),
+            format_size(stats.git),
+            format_size(stats.total - stats.git)
+        )
+    };
+    if stats.submodules > 0 {
+        let noun = if stats.submodules == 1 {
+            "submodule"
+        } else {
+            "submodules"
+        };
+        let _ = write!(size, " (+{} {noun})", stats.submodules);
+    }
+    size
+}
+
+/// Formats the LFS object count and size, e.g. `12 files, 3.4 MB`
+fn format_lfs(files: u64, bytes: u64) -> String {
+    format!(
+        "{} {}, {}",
+        format_count(files),
+        if files == 1 { "file" } else { "files" },
+        format_size(bytes)
+    )
 }
 
 #[allow(clippy::cast_precision_loss)]
@@ -169,6 +717,193 @@ fn format_count(n: u64) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_forge_label_known_hosts() {
+        assert_eq!(forge_label("github.com"), Some("GitHub"));
+        assert_eq!(forge_label("GitLab.com"), Some("GitLab"));
+        assert_eq!(forge_label("bitbucket.org"), Some("Bitbucket"));
+        assert_eq!(forge_label("codeberg.org"), Some("Codeberg"));
+    }
+
+    #[test]
+    fn test_forge_label_self_hosted() {
+        assert_eq!(forge_label("gitlab.company.com"), Some("GitLab"));
+        assert_eq!(forge_label("gitea.home.lan"), Some("Gitea"));
+        assert_eq!(forge_label("github.enterprise.io"), Some("GitHub"));
+    }
+
+    #[test]
+    fn test_forge_label_unknown() {
+        assert_eq!(forge_label("git.example.com"), None);
+        assert_eq!(forge_label("mygitlab.com"), None);
+    }
+
+    fn sample_values() -> RepoValues {
+        RepoValues {
+            path: PathBuf::from("/repos/yarm"),
+            description: None,
+            branch: "main".to_string(),
+            default_branch: Some("main".to_string()),
+            remotes: Vec::new(),
+            dirty: true,
+            stats: Some(DirStats {
+                total: 2048,
+                ..DirStats::default()
+            }),
+            lfs: None,
+            hooks: vec!["pre-commit".to_string(), "pre-push".to_string()],
+            fetch_time: None,
+            identity: Identity::Inherited,
+        }
+    }
+
+    fn profile(name: &str, source: &str, email: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            source: PathBuf::from(source),
+            user_name: None,
+            user_email: Some(email.to_string()),
+            signing_key: None,
+            gpg_sign: None,
+            gpg_format: None,
+            tag_gpg_sign: None,
+            is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_match_identity() {
+        let profiles = [
+            profile("local", "/repos/yarm/.git/config", "stray@ex.com"),
+            profile("work", "/home/u/.gitconfig-work", "me@work.com"),
+        ];
+
+        assert_eq!(
+            match_identity(Some("Me@Work.com".to_string()), &profiles),
+            Identity::Profile("work".to_string())
+        );
+        // The repository's own config is not a managed profile
+        assert_eq!(
+            match_identity(Some("stray@ex.com".to_string()), &profiles),
+            Identity::Unmanaged("stray@ex.com".to_string())
+        );
+        assert_eq!(match_identity(None, &profiles), Identity::Inherited);
+        assert_eq!(field_value(&sample_values(), "identity"), "(inherited)");
+    }
+
+    #[test]
+    fn test_check_fields_unknown_lists_valid() {
+        assert!(check_fields(["branch", "dirty", "size"].into_iter()).is_ok());
+        let err = check_fields(["branch", "colour"].into_iter()).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("'colour'"));
+        assert!(msg.contains("branch, default_branch"));
+    }
+
+    #[test]
+    fn test_template_fields() {
+        let fields: Vec<_> = template_fields("{name} on {branch} ({dirty})").collect();
+        assert_eq!(fields, ["name", "branch", "dirty"]);
+        assert_eq!(template_fields("no placeholders").count(), 0);
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render_template("{name}:{branch} {dirty} {size}", &sample_values()),
+            "yarm:main dirty 2.0 KB"
+        );
+        assert_eq!(field_value(&sample_values(), "remote"), "");
+        assert_eq!(
+            field_value(&sample_values(), "hooks"),
+            "pre-commit,pre-push"
+        );
+    }
+
+    #[test]
+    fn test_skipped_size() {
+        let values = RepoValues {
+            stats: None,
+            ..sample_values()
+        };
+        assert_eq!(
+            render_template("{branch} {size}", &values),
+            "main (skipped)"
+        );
+        assert_eq!(field_value(&values, "files"), "(skipped)");
+    }
+
+    #[test]
+    fn test_shows_size() {
+        assert!(shows_size(&Layout::Full));
+        assert!(shows_size(&Layout::Fields(vec![
+            "branch".to_string(),
+            "files".to_string()
+        ])));
+        assert!(!shows_size(&Layout::Fields(vec!["branch".to_string()])));
+        assert!(shows_size(&Layout::Format("{lfs}".to_string())));
+        assert!(!shows_size(&Layout::Format("{branch} {dirty}".to_string())));
+    }
+
+    #[test]
+    fn test_shows_default_branch() {
+        let names = &["default_branch"];
+        assert!(shows_any(&Layout::Full, names));
+        assert!(!shows_any(&Layout::Format("{name}".to_string()), names));
+        assert!(!shows_any(
+            &Layout::Fields(vec!["branch".to_string()]),
+            names
+        ));
+        assert!(shows_any(
+            &Layout::Fields(vec!["default_branch".to_string()]),
+            names
+        ));
+        assert!(shows_any(
+            &Layout::Format("{branch} ({default_branch})".to_string()),
+            names
+        ));
+    }
+
+    #[test]
+    fn test_first_heading_markdown() {
+        assert_eq!(
+            first_heading("[![CI](badge)](ci)\n\n# yarm – Yet Another Repository Manager\n\nText")
+                .as_deref(),
+            Some("yarm – Yet Another Repository Manager")
+        );
+        assert_eq!(
+            first_heading("Intro paragraph\n## Usage").as_deref(),
+            Some("Usage")
+        );
+    }
+
+    #[test]
+    fn test_first_heading_plain_text() {
+        assert_eq!(
+            first_heading("\n\n  A small tool  \nMore").as_deref(),
+            Some("A small tool")
+        );
+        assert_eq!(first_heading(""), None);
+    }
+
+    #[test]
+    fn test_git_description_skips_default() {
+        assert_eq!(
+            git_description(
+                "Unnamed repository; edit this file 'description' to name the repository.\n"
+            ),
+            None
+        );
+        assert_eq!(git_description("\n"), None);
+        assert_eq!(
+            git_description("Mirror of the docs site\n").as_deref(),
+            Some("Mirror of the docs site")
+        );
+    }
+
     #[test]
     fn test_format_size_bytes() {
         assert_eq!(format_size(0), "0 B");
@@ -192,6 +927,196 @@ mod tests {
         assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
     }
 
+    #[test]
+    fn test_dir_stats_separates_git() {
+        let dir = std::env::temp_dir().join("yarm-test-dir-stats");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(".git/objects")).unwrap();
+        fs::create_dir_all(dir.join("src/.git")).unwrap();
+        fs::write(dir.join(".git/objects/pack"), vec![0u8; 300]).unwrap();
+        fs::write(dir.join("src/main.rs"), vec![0u8; 100]).unwrap();
+        // Only the top-level .git counts as repository data
+        fs::write(dir.join("src/.git/file"), vec![0u8; 20]).unwrap();
+
+        let stats = dir_stats(&dir);
+        assert_eq!(
+            stats,
+            DirStats {
+                total: 420,
+                git: 300,
+                files: 3,
+                dirs: 4,
+                submodules: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_dir_stats_skips_submodules() {
+        let dir = std::env::temp_dir().join("yarm-test-dir-stats-submodules");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("vendor/lib")).unwrap();
+        fs::create_dir_all(dir.join("deps/tool")).unwrap();
+        fs::write(
+            dir.join(".gitmodules"),
+            "[submodule \"lib\"]\n\tpath = vendor/lib\n\turl = ../lib.git\n",
+        )
+        .unwrap();
+        fs::write(dir.join("vendor/lib/big.bin"), vec![0u8; 500]).unwrap();
+        // Checked-out submodules have a .git file pointing into the superproject
+        fs::write(
+            dir.join("deps/tool/.git"),
+            "gitdir: ../../.git/modules/tool",
+        )
+        .unwrap();
+        fs::write(dir.join("deps/tool/big.bin"), vec![0u8; 500]).unwrap();
+
+        let stats = dir_stats(&dir);
+        assert_eq!(stats.submodules, 2);
+        assert_eq!(stats.total, 55);
+        assert_eq!(stats.dirs, 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dir_stats_parallel_matches_serial() {
+        let dir = std::env::temp_dir().join("yarm-test-dir-stats-parallel");
+        let _ = fs::remove_dir_all(&dir);
+        for (i, sub) in ["a", "b/c", "b/d/e", ".git/objects/ab", "f"]
+            .iter()
+            .enumerate()
+        {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+            for n in 0..=i {
+                fs::write(
+                    dir.join(sub).join(format!("{n}.bin")),
+                    vec![0u8; 10 * (n + 1)],
+                )
+                .unwrap();
+            }
+        }
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/.git"), "gitdir: ../.git/modules/sub").unwrap();
+        fs::write(dir.join("top.txt"), "top").unwrap();
+
+        let parallel = dir_stats(&dir);
+        let serial = walk_tree(&dir, false, &dir, &[]);
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel.submodules, 1);
+        assert!(parallel.git > 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_active_hooks_skips_samples_and_non_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("yarm-test-active-hooks");
+        let _ = fs::remove_dir_all(&dir);
+        let hooks = dir.join(".git/hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        for (name, mode) in [
+            ("pre-commit.sample", 0o755),
+            ("pre-push", 0o755),
+            ("pre-commit", 0o755),
+            ("notes.txt", 0o644),
+        ] {
+            let file = hooks.join(name);
+            fs::write(&file, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&file, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        assert_eq!(installed_hooks(&hooks), ["pre-commit", "pre-push"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_active_hooks_honors_hooks_path() {
+        let dir = std::env::temp_dir().join("yarm-test-active-hooks-path");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["config", "core.hooksPath", "tools/hooks"]);
+        fs::create_dir_all(dir.join("tools/hooks")).unwrap();
+        let hook = dir.join("tools/hooks/commit-msg");
+        fs::write(&hook, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        assert_eq!(active_hooks(&dir), ["commit-msg"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hooks_dir_resolution() {
+        let repo = Path::new("/home/user/project");
+        assert_eq!(
+            hooks_dir(repo, Some("/home/user/.githooks".to_string())),
+            PathBuf::from("/home/user/.githooks")
+        );
+        assert_eq!(
+            hooks_dir(repo, Some(".husky".to_string())),
+            PathBuf::from("/home/user/project/.husky")
+        );
+    }
+
+    #[test]
+    fn test_submodule_paths() {
+        let content = "[submodule \"a\"]\n\tpath = libs/a\n\turl = https://ex.com/a\n[submodule \"b\"]\n\tpath=b\n";
+        assert_eq!(
+            submodule_paths(content),
+            [PathBuf::from("libs/a"), PathBuf::from("b")]
+        );
+    }
+
+    #[test]
+    fn test_format_size_breakdown() {
+        let stats = DirStats {
+            total: 3 * 1024,
+            git: 1024,
+            files: 2,
+            dirs: 1,
+            submodules: 0,
+        };
+        assert_eq!(
+            format_size_breakdown(&stats),
+            "3.0 KB (1.0 KB .git, 2.0 KB worktree)"
+        );
+
+        let bare = DirStats {
+            total: 512,
+            ..DirStats::default()
+        };
+        assert_eq!(format_size_breakdown(&bare), "512 B");
+
+        let with_submodules = DirStats {
+            total: 512,
+            submodules: 2,
+            ..DirStats::default()
+        };
+        assert_eq!(
+            format_size_breakdown(&with_submodules),
+            "512 B (+2 submodules)"
+        );
+    }
+
     #[test]
     fn test_format_count_small() {
         assert_eq!(format_count(0), "0");
@@ -1,84 +1,117 @@
-use anyhow::Result;
-use console::style;
+use anyhow::{Context, Result, bail};
+use console::{Term, style};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::term::{format_elapsed, format_home_path, print_hint, print_warning};
+use crate::git::RepoStatus;
+use crate::term::{format_home_path, format_time, link, paged_output, print_hint, print_warning};
 
 /// Executes the status command flow
-pub fn run(full: bool) -> Result<()> {
+pub fn run(full: bool, sort: bool, active_since: Option<&str>, pager: bool) -> Result<()> {
+    let active_since = active_since.map(parse_since).transpose()?;
     let config = crate::config::load()?;
-    let pools = config.pool_paths();
+    let mut pools = config.pool_paths();
+    if sort {
+        sort_pools(&mut pools);
+    }
     let state = crate::state::load()?;
 
     println!();
 
     if pools.is_empty() {
-        print_warning("No repository pools configured");
-        println!();
-        print_hint(format!(
-            "Add pools to {}:",
-            style("~/.config/yarm.toml").dim()
-        ));
-        println!();
-        println!("        [repositories]");
-        println!("        pools = [\"~/projects\", \"~/work\"]");
+        print_no_pools_hint();
         return Ok(());
     }
 
-    println!("  {}", style("Repository pools:").bold());
+    let statuses = if full {
+        crate::git::require_version(crate::git::STATUS_V2_MIN_VERSION, "status --full")?;
+        collect_statuses(&state.repositories, &pools)
+    } else {
+        HashMap::new()
+    };
+    let activity = active_since.map(|since| collect_activity(&state.repositories, &pools, since));
+
+    crate::config::warn_overlapping_pools(&pools);
+
+    let mut lines = vec![format!("  {}", style("Repository pools:").bold())];
 
     for pool in &pools {
         let pool_repos: Vec<_> = state
             .repositories
             .iter()
-            .filter(|r| r.starts_with(pool))
+            .filter(|r| crate::config::assign_pool(r, &pools) == Some(pool.as_path()))
             .collect();
         let repo_count = pool_repos.len();
+        let pool_repos: Vec<_> = match &activity {
+            Some(activity) => pool_repos
+                .into_iter()
+                .filter(|r| activity.contains_key(*r))
+                .collect(),
+            None => pool_repos,
+        };
 
         let exists = pool.is_dir();
         let path_display = format_home_path(pool);
 
         if !exists {
-            println!(
+            lines.push(format!(
                 "    {} {} {}",
                 style("•").dim(),
                 style(&path_display).dim(),
                 style("(not found)").red()
-            );
+            ));
         } else if repo_count == 0 {
-            println!(
+            lines.push(format!(
                 "    {} {} {}",
                 style("•").dim(),
-                path_display,
+                link(&path_display, pool),
                 style("(no scan data)").dim()
-            );
+            ));
         } else {
             let label = if repo_count == 1 {
                 "repository"
             } else {
                 "repositories"
             };
-            println!(
+            let summary = if activity.is_some() {
+                format!("({} of {repo_count} {label} active)", pool_repos.len())
+            } else {
+                format!("({repo_count} {label})")
+            };
+            lines.push(format!(
                 "    {} {} {}",
                 style("•").cyan(),
-                path_display,
-                style(format!("({repo_count} {label})")).dim()
-            );
+                link(&path_display, pool),
+                style(summary).dim()
+            ));
 
             if full {
-                print_repo_list(&pool_repos, pool);
+                lines.extend(repo_list_lines(
+                    &pool_repos,
+                    pool,
+                    &statuses,
+                    activity.as_ref(),
+                    &state.empty_repos,
+                ));
             }
         }
     }
 
     if let Some(scan_time) = state.last_scan_time() {
-        println!();
-        println!(
+        lines.push(String::new());
+        lines.push(format!(
             "  {} {}",
             style("Last scan:").bold(),
-            style(format_elapsed(scan_time)).dim()
-        );
+            style(format_time(scan_time)).dim()
+        ));
     }
 
+    paged_output(lines, pager);
+
     if state.repositories.is_empty() {
         println!();
         print_hint(format!(
@@ -90,14 +123,236 @@ pub fn run(full: bool) -> Result<()> {
     Ok(())
 }
 
-fn print_repo_list(repos: &[&std::path::PathBuf], pool: &std::path::Path) {
-    let mut rel_paths: Vec<_> = repos
+/// Re-renders the status view every `interval` until interrupted with Ctrl-C
+pub fn watch(full: bool, sort: bool, active_since: Option<&str>, interval: Duration) -> Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        bail!("--watch requires stdout to be a terminal");
+    }
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))
+        .context("Failed to install Ctrl-C handler")?;
+
+    while !interrupted.load(Ordering::Relaxed) {
+        term.clear_screen()?;
+        run(full, sort, active_since, false)?;
+        println!();
+        println!(
+            "  {}",
+            style(format!(
+                "Refreshing every {}s, press Ctrl-C to exit",
+                interval.as_secs()
+            ))
+            .dim()
+        );
+
+        let deadline = Instant::now() + interval;
+        while !interrupted.load(Ordering::Relaxed) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    term.show_cursor()?;
+    Ok(())
+}
+
+/// Explains how to configure pools when none are set up
+fn print_no_pools_hint() {
+    print_warning("No repository pools configured");
+    println!();
+    print_hint(format!(
+        "Add pools to {}:",
+        style("~/.config/yarm.toml").dim()
+    ));
+    println!();
+    println!("        [repositories]");
+    println!("        pools = [\"~/projects\", \"~/work\"]");
+}
+
+/// Sorts pools alphabetically by basename (case-insensitive), then by full path.
+fn sort_pools(pools: &mut [PathBuf]) {
+    pools.sort_by_cached_key(|p| {
+        let name = p
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        (name, p.clone())
+    });
+}
+
+fn repo_list_lines(
+    repos: &[&PathBuf],
+    pool: &Path,
+    statuses: &HashMap<PathBuf, RepoStatus>,
+    activity: Option<&HashMap<PathBuf, SystemTime>>,
+    empty: &BTreeSet<PathBuf>,
+) -> Vec<String> {
+    let mut repos = repos.to_vec();
+    repos.sort();
+
+    repos
         .iter()
-        .map(|r| r.strip_prefix(pool).unwrap_or(r))
+        .map(|repo| {
+            let rel = repo.strip_prefix(pool).unwrap_or(repo);
+            let mut line = format!("        {}", link(rel.display(), repo));
+            if let Some(status) = statuses.get(*repo) {
+                line = format!("{line} {}", format_status(status));
+            }
+            if empty.contains(*repo) {
+                line = format!("{line} {}", style("empty").yellow());
+            }
+            if let Some(time) = activity.and_then(|a| a.get(*repo)) {
+                line = format!("{line} {}", style(format_time(*time)).dim());
+            }
+            line
+        })
+        .collect()
+}
+
+/// Returns the last commit time of repositories committed to within `since`
+fn collect_activity(
+    repositories: &[PathBuf],
+    pools: &[PathBuf],
+    since: Duration,
+) -> HashMap<PathBuf, SystemTime> {
+    let repos: Vec<PathBuf> = repositories
+        .iter()
+        .filter(|r| pools.iter().any(|p| r.starts_with(p) && p.is_dir()))
+        .cloned()
+        .collect();
+
+    let total = repos.len();
+    let done = AtomicUsize::new(0);
+    let spinner = crate::term::spinner("Checking recent activity...");
+    let times = crate::git::batch(&repos, crate::git::last_commit_time, || {
+        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+        spinner.set_message(format!("Checking recent activity ({n}/{total})..."));
+    });
+    spinner.finish_and_clear();
+
+    let cutoff = SystemTime::now()
+        .checked_sub(since)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    repos
+        .into_iter()
+        .zip(times)
+        .filter_map(|(repo, time)| time.filter(|t| *t >= cutoff).map(|t| (repo, t)))
+        .collect()
+}
+
+/// Parses an activity window such as `12h`, `7d`, `2w`, `3mo`, or `1y`
+pub(crate) fn parse_since(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let Ok(amount) = amount.parse::<u64>() else {
+        bail!("Invalid duration '{value}', expected e.g. 7d, 2w, or 3mo");
+    };
+
+    let hours = match unit {
+        "h" => 1,
+        "d" => 24,
+        "w" => 24 * 7,
+        "mo" => 24 * 30,
+        "y" => 24 * 365,
+        _ => bail!("Invalid duration unit in '{value}', expected h, d, w, mo, or y"),
+    };
+
+    // Duration::from_hours panics past u64::MAX seconds
+    match amount.checked_mul(hours).filter(|h| *h <= u64::MAX / 3600) {
+        Some(total) => Ok(Duration::from_hours(total)),
+        None => bail!("Invalid duration '{value}', expected e.g. 7d, 2w, or 3mo"),
+    }
+}
+
+/// Queries branch and working tree status for all repositories in existing pools
+fn collect_statuses(repositories: &[PathBuf], pools: &[PathBuf]) -> HashMap<PathBuf, RepoStatus> {
+    let repos: Vec<PathBuf> = repositories
+        .iter()
+        .filter(|r| pools.iter().any(|p| r.starts_with(p) && p.is_dir()))
+        .cloned()
         .collect();
-    rel_paths.sort();
 
-    for rel in &rel_paths {
-        println!("        {}", rel.display());
+    let total = repos.len();
+    let done = AtomicUsize::new(0);
+    let spinner = crate::term::spinner("Checking repositories...");
+    let results = crate::git::batch_status(&repos, || {
+        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+        spinner.set_message(format!("Checking repositories ({n}/{total})..."));
+    });
+    spinner.finish_and_clear();
+
+    results
+        .into_iter()
+        .filter(|s| s.branch.is_some())
+        .map(|s| (s.path.clone(), s))
+        .collect()
+}
+
+/// Formats branch, dirty marker and upstream divergence for a repository line
+fn format_status(status: &RepoStatus) -> String {
+    let mut parts = vec![status.branch.clone().unwrap_or_default()];
+    if status.dirty {
+        parts.push("*".to_string());
+    }
+    if status.ahead > 0 {
+        parts.push(format!("↑{}", status.ahead));
+    }
+    if status.behind > 0 {
+        parts.push(format!("↓{}", status.behind));
+    }
+    style(format!("({})", parts.join(" "))).dim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since_units() {
+        assert_eq!(parse_since("12h").unwrap(), Duration::from_hours(12));
+        assert_eq!(parse_since("7d").unwrap(), Duration::from_hours(7 * 24));
+        assert_eq!(parse_since("2w").unwrap(), Duration::from_hours(14 * 24));
+        assert_eq!(parse_since("3mo").unwrap(), Duration::from_hours(90 * 24));
+        assert_eq!(parse_since("1y").unwrap(), Duration::from_hours(365 * 24));
+    }
+
+    #[test]
+    fn test_parse_since_invalid() {
+        assert!(parse_since("").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("7x").is_err());
+        assert!(parse_since("-1d").is_err());
+    }
+
+    #[test]
+    fn test_parse_since_overflow() {
+        assert!(parse_since("99999999999999999y").is_err());
+        assert!(parse_since("18446744073709551615h").is_err());
+        assert!(parse_since("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_sort_pools_by_basename() {
+        let mut pools = vec![
+            PathBuf::from("/a/work"),
+            PathBuf::from("/z/Archive"),
+            PathBuf::from("/m/projects"),
+        ];
+        sort_pools(&mut pools);
+        assert_eq!(
+            pools,
+            vec![
+                PathBuf::from("/z/Archive"),
+                PathBuf::from("/m/projects"),
+                PathBuf::from("/a/work"),
+            ]
+        );
     }
 }
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use anyhow::Result;
+use console::style;
+
+use crate::term::{Table, format_home_path, print_header};
+
+/// Executes the version command flow.
+/// With `verbose`, git, configuration, and state details are reported for bug reports.
+pub fn run(verbose: bool) -> Result<()> {
+    if !verbose {
+        println!("yarm {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+
+    print_header("yarm", env!("CARGO_PKG_VERSION"));
+    println!();
+
+    let mut table = Table::new();
+    table.row(
+        "Git:",
+        crate::git::version()
+            .map_or_else(|| style("(not found)").red().to_string(), |v| v.to_string()),
+    );
+    table.row(
+        "Config:",
+        describe_file(crate::config::config_path().as_deref()),
+    );
+    table.row(
+        "State:",
+        describe_file(crate::state::state_path().as_deref()),
+    );
+    table.row(
+        "State version:",
+        match crate::state::file_version() {
+            Some(v) if v == crate::state::STATE_VERSION => v.to_string(),
+            Some(v) => format!(
+                "{v} {}",
+                style(format!("(current: {})", crate::state::STATE_VERSION)).yellow()
+            ),
+            None => style("(none)").dim().to_string(),
+        },
+    );
+    table.row(
+        "Repositories:",
+        crate::state::load()?.repositories.len().to_string(),
+    );
+    table.print();
+
+    Ok(())
+}
+
+/// Formats a file path, noting when the file does not exist
+fn describe_file(path: Option<&Path>) -> String {
+    match path {
+        Some(path) if path.exists() => format_home_path(path),
+        Some(path) => format!("{} {}", format_home_path(path), style("(missing)").dim()),
+        None => style("(unknown)").dim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_file() {
+        let missing = std::env::temp_dir().join("yarm-test-version-missing.toml");
+        let _ = std::fs::remove_file(&missing);
+        assert!(describe_file(Some(&missing)).contains("(missing)"));
+        assert!(!describe_file(Some(&std::env::temp_dir())).contains("(missing)"));
+        assert!(describe_file(None).contains("(unknown)"));
+    }
+}
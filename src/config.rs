@@ -1,15 +1,20 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Once;
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
+use crate::term::{eprint_warning, format_home_path};
+
 #[derive(Debug, Default, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub profiles: ProfilesConfig,
     #[serde(default)]
     pub repositories: RepositoriesConfig,
+    #[serde(default)]
+    pub clone: CloneConfig,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -18,6 +23,28 @@ pub struct ProfilesConfig {
     pub default: Option<String>,
     #[serde(default)]
     pub paths: Vec<String>,
+    /// Also search subdirectories of `paths` (bounded depth)
+    #[serde(default)]
+    pub recursive: bool,
+    /// Collapse profiles defining the same identity in several files
+    #[serde(default)]
+    pub dedupe: bool,
+    /// Write `commit.gpgsign=false` when applying a profile without a signing key
+    #[serde(default)]
+    pub disable_signing_without_key: bool,
+    /// Prefixes and suffixes stripped from file names when deriving profile names
+    #[serde(default)]
+    pub name_strip: Vec<String>,
+    /// Path glob to profile rules for `apply --pool --auto-map`, first match wins
+    #[serde(default)]
+    pub map: Vec<ProfileMapping>,
+}
+
+/// A `[[profiles.map]]` entry assigning a profile to repositories matching a path glob
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileMapping {
+    pub glob: String,
+    pub profile: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,6 +57,16 @@ pub struct RepositoriesConfig {
     pub auto_rescan: bool,
     #[serde(default)]
     pub max_depth: Option<u32>,
+    #[serde(default)]
+    pub default_pool: Option<String>,
+    #[serde(default)]
+    pub init_template: Option<String>,
+    /// Directory of `.gitignore` templates for `init --with-gitignore`
+    #[serde(default)]
+    pub gitignore_dir: Option<String>,
+    /// Age after which the scan state is reported as stale (e.g. `24h`)
+    #[serde(default)]
+    pub stale_after: Option<String>,
 }
 
 impl Default for RepositoriesConfig {
@@ -39,37 +76,97 @@ impl Default for RepositoriesConfig {
             exclude: Vec::new(),
             auto_rescan: true,
             max_depth: None,
+            default_pool: None,
+            init_template: None,
+            gitignore_dir: None,
+            stale_after: None,
         }
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct CloneConfig {
+    /// URL prefix rewrites as `[from, to]` pairs, applied before cloning
+    #[serde(default)]
+    pub rewrite: Vec<(String, String)>,
+    /// Resolve SSH host aliases from `~/.ssh/config` before includeIf URL matching
+    #[serde(default)]
+    pub resolve_ssh_aliases: bool,
+}
+
 fn default_true() -> bool {
     true
 }
 
 impl Config {
-    /// Returns the resolved profile discovery paths, with `~` expanded.
+    /// Returns the resolved profile discovery paths, with `~` and environment variables expanded.
     pub fn profile_paths(&self) -> Vec<PathBuf> {
-        self.profiles
-            .paths
-            .iter()
-            .map(|p| expand_tilde(p))
-            .collect()
+        self.profiles.paths.iter().map(|p| expand_path(p)).collect()
     }
 
-    /// Returns the resolved repository pool paths, with `~` expanded.
+    /// Returns the resolved repository pool paths, with `~` and environment variables expanded.
     pub fn pool_paths(&self) -> Vec<PathBuf> {
         self.repositories
             .pools
             .iter()
-            .map(|p| expand_tilde(p))
+            .map(|p| expand_path(p))
             .collect()
     }
+
+    /// Returns the configured default pool path, if it names one of the configured pools.
+    /// The setting may be given as a pool basename or as a path.
+    pub fn default_pool(&self) -> Option<PathBuf> {
+        let name = self.repositories.default_pool.as_deref()?;
+        let as_path = expand_path(name);
+        let name_lower = name.to_lowercase();
+        self.pool_paths().into_iter().find(|p| {
+            *p == as_path
+                || p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.to_lowercase() == name_lower)
+        })
+    }
+
+    /// Returns human-readable problems with the configuration.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(name) = self.repositories.default_pool.as_deref()
+            && self.default_pool().is_none()
+        {
+            problems.push(format!(
+                "Default pool '{name}' is not one of the configured pools"
+            ));
+        }
+
+        if let Some(value) = self.repositories.stale_after.as_deref()
+            && let Err(e) = crate::commands::status::parse_since(value)
+        {
+            problems.push(format!("Ignoring repositories.stale_after: {e}"));
+        }
+
+        problems
+    }
 }
 
+/// Guards configuration warnings so they are printed once per invocation.
+static VALIDATE: Once = Once::new();
+
 /// Loads the yarm configuration from `~/.config/yarm.toml`.
 /// Returns a default config if the file does not exist.
+/// Configuration problems are reported as warnings on stderr.
 pub fn load() -> Result<Config> {
+    let config = load_unchecked()?;
+    VALIDATE.call_once(|| {
+        for problem in config.validate() {
+            eprint_warning(problem);
+        }
+    });
+    Ok(config)
+}
+
+/// Loads the yarm configuration without reporting configuration problems.
+pub fn load_unchecked() -> Result<Config> {
     let Some(config_path) = config_path() else {
         return Ok(Config::default());
     };
@@ -85,10 +182,59 @@ pub fn load() -> Result<Config> {
 }
 
 /// Returns the path to the yarm configuration file.
-fn config_path() -> Option<PathBuf> {
+pub fn config_path() -> Option<PathBuf> {
     dirs::home_dir().map(|h| h.join(".config/yarm.toml"))
 }
 
+/// Marker file that makes `yarm scan` track a directory, optionally pinning a profile
+pub const MARKER_FILE: &str = ".yarm";
+
+/// Contents of a `.yarm` marker file
+#[derive(Debug, Default, Deserialize)]
+pub struct Marker {
+    /// Profile used by `apply` and `clone` when none is given
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// File in a repository root naming the profile to use for it
+pub const PROFILE_FILE: &str = ".yarm-profile";
+
+/// Reads the profile name from the `.yarm-profile` file in `dir`, if present:
+/// its first line that is neither empty nor a `#` comment, trimmed.
+pub fn read_profile_file(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join(PROFILE_FILE)).ok()?;
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// Reads the `.yarm` marker in `dir`, if present.
+/// An unparsable marker is reported and treated as empty.
+pub fn read_marker(dir: &Path) -> Option<Marker> {
+    let path = dir.join(MARKER_FILE);
+    let content = fs::read_to_string(&path).ok()?;
+    if let Ok(marker) = toml::from_str(&content) {
+        Some(marker)
+    } else {
+        eprint_warning(format!("Ignoring invalid marker file {}", path.display()));
+        Some(Marker::default())
+    }
+}
+
+/// Returns the marker contents if `dir` is a planned repository: a directory
+/// whose only entry is a `.yarm` marker.
+pub fn planned_marker(dir: &Path) -> Option<String> {
+    let mut entries = fs::read_dir(dir).ok()?;
+    let only = entries.next()?.ok()?;
+    if entries.next().is_some() || only.file_name() != MARKER_FILE {
+        return None;
+    }
+    fs::read_to_string(only.path()).ok()
+}
+
 /// Checks whether a path is inside one of the configured repository pools.
 pub fn is_in_pool(path: &Path, pools: &[PathBuf]) -> bool {
     let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
@@ -98,6 +244,78 @@ pub fn is_in_pool(path: &Path, pools: &[PathBuf]) -> bool {
     })
 }
 
+/// Returns the configured pool containing a repository path, if any.
+/// With nested pools (e.g. `~/projects` and `~/projects/work`), the deepest one wins.
+pub fn assign_pool<'a>(path: &Path, pools: &'a [PathBuf]) -> Option<&'a Path> {
+    pools
+        .iter()
+        .filter(|pool| path.starts_with(pool))
+        .max_by_key(|pool| pool.components().count())
+        .map(PathBuf::as_path)
+}
+
+/// Returns `(outer, inner)` for every configured pool that lies inside another one.
+pub fn overlapping_pools(pools: &[PathBuf]) -> Vec<(&Path, &Path)> {
+    pools
+        .iter()
+        .flat_map(|outer| {
+            pools
+                .iter()
+                .filter(move |inner| inner != &outer && inner.starts_with(outer))
+                .map(move |inner| (outer.as_path(), inner.as_path()))
+        })
+        .collect()
+}
+
+/// Warns about nested pools, whose repositories are only attributed to the inner pool.
+pub fn warn_overlapping_pools(pools: &[PathBuf]) {
+    for (outer, inner) in overlapping_pools(pools) {
+        eprint_warning(format!(
+            "Pool {} is inside pool {}; its repositories are counted under the inner pool only",
+            format_home_path(inner),
+            format_home_path(outer)
+        ));
+    }
+}
+
+/// Expands `$VAR` and `${VAR}` references, then a leading `~/`.
+/// Unset variables are left in place.
+pub fn expand_path(path: &str) -> PathBuf {
+    expand_tilde(&expand_vars(path, |name| std::env::var(name).ok()))
+}
+
+/// Replaces `$VAR` and `${VAR}` with values from `lookup`, leaving unknown references as written.
+fn expand_vars(input: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let (name, reference_len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        match lookup(name).filter(|_| !name.is_empty()) {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[pos..=pos + reference_len]),
+        }
+        rest = &after[reference_len..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
 /// Expands a leading `~/` to the user's home directory.
 pub fn expand_tilde(path: &str) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/")
@@ -191,6 +409,34 @@ pools = ["~/projects"]
         assert!(config.repositories.auto_rescan);
     }
 
+    #[test]
+    fn test_stale_after_parsed() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+stale_after = "24h"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.repositories.stale_after.as_deref(), Some("24h"));
+        assert!(Config::default().repositories.stale_after.is_none());
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_stale_after_is_a_warning() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+stale_after = "soon"
+"#,
+        )
+        .unwrap();
+        let problems = config.validate();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("repositories.stale_after"));
+    }
+
     #[test]
     fn test_auto_rescan_explicit_false() {
         let config: Config = toml::from_str(
@@ -203,6 +449,22 @@ auto_rescan = false
         assert!(!config.repositories.auto_rescan);
     }
 
+    #[test]
+    fn test_auto_rescan_and_max_depth_together() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+pools = ["~/work"]
+auto_rescan = false
+max_depth = 2
+"#,
+        )
+        .unwrap();
+        assert!(!config.repositories.auto_rescan);
+        assert_eq!(config.repositories.max_depth, Some(2));
+        assert_eq!(config.repositories.pools, ["~/work"]);
+    }
+
     #[test]
     fn test_max_depth_defaults_none() {
         let config: Config = toml::from_str("").unwrap();
@@ -221,6 +483,111 @@ max_depth = 3
         assert_eq!(config.repositories.max_depth, Some(3));
     }
 
+    #[test]
+    fn test_init_template() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.repositories.init_template.is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+init_template = "~/.config/git/template"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.repositories.init_template.as_deref(),
+            Some("~/.config/git/template")
+        );
+    }
+
+    #[test]
+    fn test_default_pool_by_basename() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+pools = ["/srv/projects", "/srv/work"]
+default_pool = "work"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_pool(), Some(PathBuf::from("/srv/work")));
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_default_pool_by_path() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+pools = ["/srv/projects", "/srv/work"]
+default_pool = "/srv/projects"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_pool(), Some(PathBuf::from("/srv/projects")));
+    }
+
+    #[test]
+    fn test_default_pool_unknown_is_reported() {
+        let config: Config = toml::from_str(
+            r#"
+[repositories]
+pools = ["/srv/projects"]
+default_pool = "missing"
+"#,
+        )
+        .unwrap();
+        assert!(config.default_pool().is_none());
+        assert_eq!(config.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_clone_rewrite_rules() {
+        let config: Config = toml::from_str(
+            r#"
+[clone]
+rewrite = [["https://github.com/", "git@github.com:"]]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.clone.rewrite,
+            vec![(
+                "https://github.com/".to_string(),
+                "git@github.com:".to_string()
+            )]
+        );
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/user".to_string()),
+            "WORKSPACE" => Some("/mnt/ws".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_vars_home() {
+        assert_eq!(expand_vars("$HOME/projects", lookup), "/home/user/projects");
+    }
+
+    #[test]
+    fn test_expand_vars_braced() {
+        assert_eq!(expand_vars("${WORKSPACE}/repos", lookup), "/mnt/ws/repos");
+        assert_eq!(expand_vars("${WORKSPACE}_old", lookup), "/mnt/ws_old");
+    }
+
+    #[test]
+    fn test_expand_vars_undefined_left_in_place() {
+        assert_eq!(expand_vars("$NOPE/repos", lookup), "$NOPE/repos");
+        assert_eq!(expand_vars("${NOPE}/repos", lookup), "${NOPE}/repos");
+        assert_eq!(expand_vars("/cost/$5", lookup), "/cost/$5");
+        assert_eq!(expand_vars("${unterminated", lookup), "${unterminated");
+        assert_eq!(expand_vars("trailing$", lookup), "trailing$");
+    }
+
     #[test]
     fn test_expand_tilde_absolute() {
         assert_eq!(
@@ -236,6 +603,91 @@ max_depth = 3
         dir
     }
 
+    #[test]
+    fn test_read_marker() {
+        let dir = tempdir("marker-read");
+        assert!(read_marker(&dir).is_none());
+
+        std::fs::write(dir.join(MARKER_FILE), "profile = \"work\"\n").unwrap();
+        assert_eq!(read_marker(&dir).unwrap().profile.as_deref(), Some("work"));
+
+        std::fs::write(dir.join(MARKER_FILE), "").unwrap();
+        assert!(read_marker(&dir).unwrap().profile.is_none());
+    }
+
+    #[test]
+    fn test_read_profile_file() {
+        let dir = tempdir("profile-file");
+        assert_eq!(read_profile_file(&dir), None);
+
+        std::fs::write(dir.join(PROFILE_FILE), "work\n").unwrap();
+        assert_eq!(read_profile_file(&dir).as_deref(), Some("work"));
+
+        std::fs::write(
+            dir.join(PROFILE_FILE),
+            "# identity for this repo\n\n  oss  \nignored\n",
+        )
+        .unwrap();
+        assert_eq!(read_profile_file(&dir).as_deref(), Some("oss"));
+
+        std::fs::write(dir.join(PROFILE_FILE), "\n# only a comment\n").unwrap();
+        assert_eq!(read_profile_file(&dir), None);
+    }
+
+    #[test]
+    fn test_planned_marker() {
+        let dir = tempdir("marker-planned");
+        assert_eq!(planned_marker(&dir), None);
+
+        std::fs::write(dir.join(MARKER_FILE), "profile = \"work\"\n").unwrap();
+        assert_eq!(
+            planned_marker(&dir).as_deref(),
+            Some("profile = \"work\"\n")
+        );
+
+        std::fs::write(dir.join("README.md"), "").unwrap();
+        assert_eq!(planned_marker(&dir), None);
+    }
+
+    #[test]
+    fn test_containing_pool() {
+        let pools = vec![PathBuf::from("/home/u/work"), PathBuf::from("/home/u/oss")];
+        assert_eq!(
+            assign_pool(Path::new("/home/u/oss/tool"), &pools),
+            Some(pools[1].as_path())
+        );
+        assert_eq!(assign_pool(Path::new("/home/u/other/tool"), &pools), None);
+    }
+
+    #[test]
+    fn test_assign_pool_prefers_nested_pool() {
+        let pools = vec![
+            PathBuf::from("/home/u/projects"),
+            PathBuf::from("/home/u/projects/work"),
+            PathBuf::from("/home/u/projects-old"),
+        ];
+        assert_eq!(
+            assign_pool(Path::new("/home/u/projects/work/api"), &pools),
+            Some(Path::new("/home/u/projects/work"))
+        );
+        assert_eq!(
+            assign_pool(Path::new("/home/u/projects/tool"), &pools),
+            Some(Path::new("/home/u/projects"))
+        );
+        assert_eq!(
+            assign_pool(Path::new("/home/u/projects-old/tool"), &pools),
+            Some(Path::new("/home/u/projects-old"))
+        );
+
+        assert_eq!(
+            overlapping_pools(&pools),
+            [(
+                Path::new("/home/u/projects"),
+                Path::new("/home/u/projects/work")
+            )]
+        );
+    }
+
     #[test]
     fn test_is_in_pool_inside() {
         let pool = tempdir("pool-inside");
@@ -1,9 +1,15 @@
 use anyhow::{Context, Result, bail};
 use console::style;
-use std::path::Path;
-use std::process::Command;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError, mpsc};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::term::icon_error;
+use crate::term::{eprint_warning, icon_error};
 
 /// Verifies that git is available and returns a friendly error if not
 pub fn ensure_available() -> Result<()> {
@@ -21,6 +27,208 @@ pub fn ensure_available() -> Result<()> {
     }
 }
 
+/// A git version as `major.minor.patch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl GitVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl std::fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Minimum git version for `includeIf "hasconfig:remote.*.url:..."` conditions
+pub const HASCONFIG_MIN_VERSION: GitVersion = GitVersion::new(2, 36, 0);
+
+/// Minimum git version for `git status --porcelain=v2`
+pub const STATUS_V2_MIN_VERSION: GitVersion = GitVersion::new(2, 11, 0);
+
+/// Minimum git version for `git init --object-format`
+pub const OBJECT_FORMAT_MIN_VERSION: GitVersion = GitVersion::new(2, 29, 0);
+
+/// Returns the installed git version, queried once per invocation.
+/// Returns `None` if git is unavailable or the output cannot be parsed.
+pub fn version() -> Option<GitVersion> {
+    static VERSION: OnceLock<Option<GitVersion>> = OnceLock::new();
+    *VERSION.get_or_init(|| {
+        let output = Command::new("git").arg("--version").output().ok()?;
+        parse_version(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+/// Parses `git --version` output such as "git version 2.39.2 (Apple Git-154)"
+/// or "git version 2.45.1.windows.1". A missing patch component is treated as 0.
+fn parse_version(output: &str) -> Option<GitVersion> {
+    let rest = output.trim().strip_prefix("git version ")?;
+    let number = rest.split_whitespace().next()?;
+
+    let mut parts = number.split('.').map(|p| {
+        let digits: String = p.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<u32>().ok()
+    });
+
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().flatten().unwrap_or(0);
+
+    Some(GitVersion::new(major, minor, patch))
+}
+
+/// Errors if the installed git is older than `min`, naming the `feature` that needs it.
+/// An unknown version is assumed to be recent enough.
+pub fn require_version(min: GitVersion, feature: &str) -> Result<()> {
+    match version() {
+        Some(found) if found < min => bail!(
+            "{}\n\n  Installed git version is {found}.",
+            style(format!("{feature} requires git {min} or newer"))
+                .red()
+                .bold()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Prints a warning if the installed git is older than `min`, naming the `feature` that needs it.
+pub fn warn_if_older(min: GitVersion, feature: &str) {
+    if let Some(found) = version()
+        && found < min
+    {
+        eprint_warning(format!(
+            "{feature} requires git {min} or newer (found {found})"
+        ));
+    }
+}
+
+/// Whether network operations were disabled with `--offline`
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enables offline mode for this invocation
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if offline mode is enabled via `--offline` or the `YARM_OFFLINE` environment variable
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed) || env_flag(std::env::var("YARM_OFFLINE").ok().as_deref())
+}
+
+/// Interprets a boolean environment variable such as `YARM_OFFLINE`;
+/// unset, empty, `0`, and `false` count as disabled.
+pub fn env_flag(value: Option<&str>) -> bool {
+    value.is_some_and(|v| !matches!(v.trim().to_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// Fails fast if `operation` would need the network while offline mode is enabled
+pub fn ensure_online(operation: &str) -> Result<()> {
+    if is_offline() {
+        bail!(
+            "{}\n\n  Offline mode is enabled (--offline or YARM_OFFLINE).",
+            style(format!("{operation} requires network access"))
+                .red()
+                .bold()
+        );
+    }
+    Ok(())
+}
+
+/// Returns `true` if `path` is a git repository, either with a `.git` entry
+/// (regular repo, submodule, or worktree) or laid out as a bare repository.
+pub fn is_repository(path: &Path) -> bool {
+    path.join(".git").exists() || is_bare_repository(path)
+}
+
+/// Returns the root of the working tree containing `path`, or `None` outside of one.
+/// Inside a linked worktree (`git worktree add`) this is the worktree's own root.
+pub fn toplevel(path: &Path) -> Option<PathBuf> {
+    rev_parse_path(path, "--show-toplevel")
+}
+
+/// Returns the git directory of the worktree containing `path`, which holds per-worktree
+/// files such as `HEAD` and `FETCH_HEAD` (`.git/worktrees/<name>` in a linked worktree)
+pub fn git_dir(path: &Path) -> Option<PathBuf> {
+    rev_parse_path(path, "--git-dir")
+}
+
+/// Returns the git directory shared by all worktrees of the repository,
+/// which holds the config, hooks, and `description`
+pub fn common_dir(path: &Path) -> Option<PathBuf> {
+    rev_parse_path(path, "--git-common-dir")
+}
+
+/// Runs `git rev-parse <flag>` in `path`, resolving relative output against `path`
+fn rev_parse_path(path: &Path, flag: &str) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", flag])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let value = stdout.trim();
+    (!value.is_empty()).then(|| path.join(value))
+}
+
+/// Returns `true` if `path` has the layout of a bare repository (e.g. a mirror clone).
+pub fn is_bare_repository(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+/// Returns `true` if a repository has no refs yet, e.g. right after `git init`.
+/// Only looks at files in the git directory, so it is cheap enough for `scan`.
+/// Linked worktrees, submodules (a `.git` file), and reftable repositories count as non-empty.
+pub fn is_empty_repository(path: &Path) -> bool {
+    let dot_git = path.join(".git");
+    let git_dir = if dot_git.is_dir() {
+        dot_git
+    } else if dot_git.exists() || !is_bare_repository(path) {
+        return false;
+    } else {
+        path.to_path_buf()
+    };
+
+    if !git_dir.join("HEAD").is_file() || git_dir.join("reftable").exists() {
+        return false;
+    }
+
+    let packed = fs::read_to_string(git_dir.join("packed-refs")).unwrap_or_default();
+    let has_packed = packed
+        .lines()
+        .any(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('^'));
+
+    !has_packed && !contains_file(&git_dir.join("refs"))
+}
+
+/// Returns `true` if `dir` or any directory below it contains a file
+fn contains_file(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            contains_file(&path)
+        } else {
+            true
+        }
+    })
+}
+
 /// Formats a git command failure with styled output
 pub fn format_error(operation: &str, stderr: &str) -> String {
     let header = format!("{} {}", icon_error(), style(operation).bold());
@@ -40,6 +248,44 @@ pub fn format_error(operation: &str, stderr: &str) -> String {
     format!("{header}\n\n{details}")
 }
 
+/// Config file a value is written to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigTarget {
+    /// A repository's local config (`git -C <path> config --local`)
+    Local(PathBuf),
+    /// A standalone config file (`git config --file <path>`)
+    File(PathBuf),
+    /// The user's global config (`git config --global`)
+    Global,
+}
+
+impl ConfigTarget {
+    /// Infers the target from a path: directories are repositories, anything else a file
+    pub fn from_path(path: &Path) -> Self {
+        if path.is_dir() {
+            Self::Local(path.to_path_buf())
+        } else {
+            Self::File(path.to_path_buf())
+        }
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("git");
+        match self {
+            Self::Local(repo) => {
+                cmd.arg("-C").arg(repo).args(["config", "--local"]);
+            }
+            Self::File(file) => {
+                cmd.args(["config", "--file"]).arg(file);
+            }
+            Self::Global => {
+                cmd.args(["config", "--global"]);
+            }
+        }
+        cmd
+    }
+}
+
 /// Sets or unsets a git config value.
 ///
 /// Automatically detects whether `path` is a repository directory or a config file:
@@ -48,16 +294,17 @@ pub fn format_error(operation: &str, stderr: &str) -> String {
 ///
 /// Pass `None` for `value` to unset the key.
 pub fn set_config(path: &Path, key: &str, value: Option<&str>) -> Result<()> {
-    let path_str = path.to_string_lossy().into_owned();
-
-    let mut cmd = Command::new("git");
+    set_config_scoped(&ConfigTarget::from_path(path), key, value)
+}
 
-    if path.is_dir() {
-        cmd.args(["-C", &path_str, "config", "--local"]);
-    } else {
-        cmd.args(["config", "--file", &path_str]);
-    }
+/// Sets or unsets a git config value in an explicit target.
+///
+/// Pass `None` for `value` to unset the key.
+pub fn set_config_scoped(target: &ConfigTarget, key: &str, value: Option<&str>) -> Result<()> {
+    run_set_config(target.command(), key, value)
+}
 
+fn run_set_config(mut cmd: Command, key: &str, value: Option<&str>) -> Result<()> {
     match value {
         Some(v) => cmd.args([key, v]),
         None => cmd.args(["--unset", key]),
@@ -79,20 +326,80 @@ pub fn set_config(path: &Path, key: &str, value: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-/// Returns the current branch name for the repository at `path`.
-pub fn current_branch(path: &Path) -> Result<String> {
-    let output = Command::new("git")
+/// Reads the effective value of a path-valued config key for the repository at `path`,
+/// including inherited (global, system, included) values, with `~/` expanded by git.
+/// Returns `None` if the key is unset or git fails.
+pub fn get_config_path(path: &Path, key: &str) -> Option<String> {
+    read_only_git(path)
+        .args(["config", "--type=path", "--get", key])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Reads a config value from the repository's local config (not inherited values).
+/// Returns `None` if the key is unset or git fails.
+pub fn get_config(path: &Path, key: &str) -> Option<String> {
+    Command::new("git")
         .args([
             "-C",
             &path.to_string_lossy(),
-            "rev-parse",
-            "--abbrev-ref",
-            "HEAD",
+            "config",
+            "--local",
+            "--get",
+            key,
         ])
         .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Reads a config value from an explicit target only (not inherited values).
+/// Returns `None` if the key is unset or git fails.
+pub fn get_config_scoped(target: &ConfigTarget, key: &str) -> Option<String> {
+    target
+        .command()
+        .args(["--get", key])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Starts a git command in `path` for read-only introspection.
+/// `--no-optional-locks` keeps it from waiting on or refreshing the index lock
+/// held by a concurrent git process.
+fn read_only_git(path: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("--no-optional-locks").arg("-C").arg(path);
+    cmd
+}
+
+/// Returns the current branch name for the repository at `path`.
+pub fn current_branch(path: &Path) -> Result<String> {
+    let output = read_only_git(path)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
         .context("Failed to run git rev-parse")?;
 
     if !output.status.success() {
+        // Without commits, HEAD only names the branch the first commit will create
+        if let Some(unborn) = read_only_git(path)
+            .args(["symbolic-ref", "--short", "-q", "HEAD"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+        {
+            return Ok(String::from_utf8_lossy(&unborn.stdout).trim().to_string());
+        }
         bail!(
             "{}",
             format_error(
@@ -107,8 +414,8 @@ pub fn current_branch(path: &Path) -> Result<String> {
 
 /// Returns `true` if the working tree has uncommitted changes.
 pub fn is_dirty(path: &Path) -> Result<bool> {
-    let output = Command::new("git")
-        .args(["-C", &path.to_string_lossy(), "status", "--porcelain"])
+    let output = read_only_git(path)
+        .args(["status", "--porcelain"])
         .output()
         .context("Failed to run git status")?;
 
@@ -125,10 +432,133 @@ pub fn is_dirty(path: &Path) -> Result<bool> {
     Ok(!output.stdout.is_empty())
 }
 
-/// Returns all configured remotes as `(name, url)` pairs.
-pub fn remotes(path: &Path) -> Result<Vec<(String, String)>> {
-    let output = Command::new("git")
-        .args(["-C", &path.to_string_lossy(), "remote", "-v"])
+/// Upper bound for concurrent git processes in batch operations
+const MAX_BATCH_THREADS: usize = 8;
+
+/// Working tree summary of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoStatus {
+    pub path: PathBuf,
+    /// Current branch, `HEAD` when detached, `None` if git failed
+    pub branch: Option<String>,
+    pub dirty: bool,
+    /// Commits ahead of the upstream branch
+    pub ahead: u32,
+    /// Commits behind the upstream branch
+    pub behind: u32,
+}
+
+/// Returns branch, dirty state and upstream divergence with a single git call.
+pub fn repo_status(path: &Path) -> RepoStatus {
+    let output = read_only_git(path)
+        .args(["status", "--porcelain=v2", "--branch"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success());
+
+    let mut status = output.map_or_else(RepoStatus::default, |o| {
+        parse_status_v2(&String::from_utf8_lossy(&o.stdout))
+    });
+    status.path = path.to_path_buf();
+    status
+}
+
+/// Queries the status of many repositories concurrently with a bounded number of threads.
+/// Results are returned in the order of `repos`; `on_done` is called after each repository.
+pub fn batch_status(repos: &[PathBuf], on_done: impl Fn() + Sync) -> Vec<RepoStatus> {
+    batch(repos, repo_status, on_done)
+}
+
+/// Runs `query` for each repository on a bounded pool of threads.
+/// Results are returned in the order of `repos`; `on_done` is called after each repository.
+pub fn batch<T: Default + Clone + Send>(
+    repos: &[PathBuf],
+    query: impl Fn(&Path) -> T + Sync,
+    on_done: impl Fn() + Sync,
+) -> Vec<T> {
+    let threads = thread::available_parallelism()
+        .map_or(1, usize::from)
+        .clamp(1, MAX_BATCH_THREADS)
+        .min(repos.len());
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![T::default(); repos.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(repo) = repos.get(i) else {
+                        break;
+                    };
+                    let result = query(repo);
+                    results.lock().unwrap_or_else(PoisonError::into_inner)[i] = result;
+                    on_done();
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the committer time of the latest commit on `HEAD`, or `None` for
+/// repositories without commits.
+pub fn last_commit_time(path: &Path) -> Option<SystemTime> {
+    let output = read_only_git(path)
+        .args(["log", "-1", "--format=%ct"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    let secs: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Parses `git status --porcelain=v2 --branch` output.
+fn parse_status_v2(output: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+
+    for line in output.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            status.branch = Some(if head == "(detached)" {
+                "HEAD".to_string()
+            } else {
+                head.to_string()
+            });
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            // Format: "+<ahead> -<behind>"
+            for part in ab.split_whitespace() {
+                if let Some(n) = part.strip_prefix('+') {
+                    status.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = part.strip_prefix('-') {
+                    status.behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            status.dirty = true;
+        }
+    }
+
+    status
+}
+
+/// A configured git remote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remote {
+    pub name: String,
+    pub fetch_url: String,
+    /// Push URL, only set when it differs from the fetch URL
+    pub push_url: Option<String>,
+}
+
+/// Returns all configured remotes.
+pub fn remotes(path: &Path) -> Result<Vec<Remote>> {
+    let output = read_only_git(path)
+        .args(["remote", "-v"])
         .output()
         .context("Failed to run git remote")?;
 
@@ -136,21 +566,533 @@ pub fn remotes(path: &Path) -> Result<Vec<(String, String)>> {
         return Ok(Vec::new());
     }
 
-    let mut seen = Vec::new();
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
+    Ok(parse_remotes(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `git remote -v` output into remotes, pairing `(fetch)` and `(push)` lines.
+fn parse_remotes(output: &str) -> Vec<Remote> {
+    let mut remotes: Vec<Remote> = Vec::new();
+    let mut push_urls: Vec<(String, String)> = Vec::new();
+
+    for line in output.lines() {
         // Format: "name\turl (fetch|push)"
-        // Only take fetch entries to avoid duplicates
-        if !line.ends_with("(fetch)") {
+        let Some((name, rest)) = line.split_once('\t') else {
             continue;
+        };
+        if let Some(url) = rest.strip_suffix("(fetch)") {
+            remotes.push(Remote {
+                name: name.to_string(),
+                fetch_url: url.trim().to_string(),
+                push_url: None,
+            });
+        } else if let Some(url) = rest.strip_suffix("(push)") {
+            push_urls.push((name.to_string(), url.trim().to_string()));
         }
-        let Some((name, rest)) = line.split_once('\t') else {
+    }
+
+    for (name, url) in push_urls {
+        if let Some(remote) = remotes.iter_mut().find(|r| r.name == name)
+            && remote.fetch_url != url
+        {
+            remote.push_url = Some(url);
+        }
+    }
+
+    remotes
+}
+
+/// Reads the URL of a repository's `origin` remote
+pub fn origin_url(path: &Path) -> Option<String> {
+    remotes(path)
+        .ok()?
+        .into_iter()
+        .find(|r| r.name == "origin")
+        .map(|r| r.fetch_url)
+}
+
+/// Returns the branch `origin/HEAD` points to, e.g. `main`, or `None` without an origin remote.
+/// Reads the local `refs/remotes/origin/HEAD` first and only asks the remote
+/// (`git ls-remote --symref origin HEAD`) if that ref is missing and network access is allowed.
+/// The remote query runs without stdin or credential prompts and yields `None` where it fails.
+pub fn default_branch(path: &Path) -> Result<Option<String>> {
+    if !remotes(path)?.iter().any(|r| r.name == "origin") {
+        return Ok(None);
+    }
+
+    let output = read_only_git(path)
+        .args([
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            "refs/remotes/origin/HEAD",
+        ])
+        .output()
+        .context("Failed to run git symbolic-ref")?;
+    if output.status.success() {
+        let head = String::from_utf8_lossy(&output.stdout);
+        let head = head.trim();
+        return Ok(Some(
+            head.strip_prefix("origin/").unwrap_or(head).to_string(),
+        ));
+    }
+
+    if is_offline() {
+        return Ok(None);
+    }
+
+    let output = read_only_git(path)
+        .args(["ls-remote", "--symref", "origin", "HEAD"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .context("Failed to run git ls-remote")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(parse_symref_head(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Extracts the branch from the `ref: refs/heads/<branch>\tHEAD` line of `git ls-remote --symref`
+fn parse_symref_head(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("ref: ")?.strip_suffix("\tHEAD"))
+        .and_then(|r| r.strip_prefix("refs/heads/"))
+        .filter(|b| !b.is_empty())
+        .map(String::from)
+}
+
+/// Returns the number and total size of LFS objects in the working tree,
+/// or `None` if git-lfs is not installed or fails.
+pub fn lfs_files_size(path: &Path) -> Option<(u64, u64)> {
+    let output = read_only_git(path)
+        .args(["lfs", "ls-files", "--size"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    let sizes: Vec<u64> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_lfs_size)
+        .collect();
+    Some((sizes.len() as u64, sizes.iter().sum()))
+}
+
+/// Parses the size of a `git lfs ls-files --size` line like `4d7a2146b1 * big.bin (1.2 MB)`.
+/// git-lfs reports decimal units (1 KB = 1000 B).
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn parse_lfs_size(line: &str) -> Option<u64> {
+    let size = line.trim_end().strip_suffix(')')?.rsplit_once('(')?.1;
+    let (value, unit) = size.split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let factor = match unit {
+        "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((value * factor).round() as u64)
+}
+
+/// A local branch with its upstream tracking state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    pub name: String,
+    /// Upstream branch (e.g. `origin/main`), `None` if not tracking
+    pub upstream: Option<String>,
+    /// Commits ahead of the upstream branch
+    pub ahead: u32,
+    /// Commits behind the upstream branch
+    pub behind: u32,
+    /// The upstream branch no longer exists on the remote
+    pub gone: bool,
+}
+
+/// Returns all local branches with their upstream tracking state.
+pub fn branches(path: &Path) -> Result<Vec<Branch>> {
+    let output = read_only_git(path)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)%09%(upstream:short)%09%(upstream:track)",
+            "refs/heads",
+        ])
+        .output()
+        .context("Failed to run git for-each-ref")?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(parse_branches(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses tab-separated `name upstream track` lines from `git for-each-ref`.
+fn parse_branches(output: &str) -> Vec<Branch> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next().filter(|n| !n.is_empty())?;
+            let upstream = fields.next().filter(|u| !u.is_empty());
+            let (ahead, behind, gone) = parse_track(fields.next().unwrap_or_default());
+            Some(Branch {
+                name: name.to_string(),
+                upstream: upstream.map(String::from),
+                ahead,
+                behind,
+                gone,
+            })
+        })
+        .collect()
+}
+
+/// Parses an `%(upstream:track)` value like `[ahead 2, behind 1]` or `[gone]`
+/// into `(ahead, behind, gone)`.
+fn parse_track(track: &str) -> (u32, u32, bool) {
+    let inner = track.trim().trim_start_matches('[').trim_end_matches(']');
+    let (mut ahead, mut behind, mut gone) = (0, 0, false);
+    for part in inner.split(',').map(str::trim) {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        } else if part == "gone" {
+            gone = true;
+        }
+    }
+    (ahead, behind, gone)
+}
+
+/// Host and path components of a remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitUrl {
+    /// Host name without user or port (e.g., "github.com")
+    pub host: String,
+    /// Repository path without leading slash (e.g., "owner/repo.git")
+    pub path: String,
+}
+
+/// Parses a remote URL in URL form (`https://host/path`, `ssh://user@host:22/path`)
+/// or scp-like form (`user@host:path`). Returns `None` for local paths and `file://` URLs.
+pub fn parse_git_url(url: &str) -> Option<GitUrl> {
+    let (authority, path) = if let Some((scheme, rest)) = url.split_once("://") {
+        if scheme == "file" {
+            return None;
+        }
+        rest.split_once('/')?
+    } else {
+        // scp-like syntax requires a colon before any slash
+        let (authority, path) = url.split_once(':')?;
+        if authority.contains('/') {
+            return None;
+        }
+        (authority, path)
+    };
+
+    let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = host.split_once(':').map_or(host, |(h, _)| h);
+
+    if host.is_empty() {
+        return None;
+    }
+
+    Some(GitUrl {
+        host: host.to_lowercase(),
+        path: path.trim_start_matches('/').to_string(),
+    })
+}
+
+/// Replaces an SSH host alias in an `ssh://` or scp-like URL with the `HostName` it maps to
+/// in an OpenSSH client config (`~/.ssh/config` contents). Other URLs, and hosts without
+/// an exact `Host` entry setting `HostName`, are returned unchanged.
+pub fn resolve_ssh_alias(url: &str, ssh_config: &str) -> String {
+    let (prefix, rest) = match url.split_once("://") {
+        Some((scheme, rest)) if scheme == "ssh" || scheme == "git+ssh" => {
+            (&url[..scheme.len() + 3], rest)
+        }
+        Some(_) => return url.to_string(),
+        None => ("", url),
+    };
+
+    let authority_end = if prefix.is_empty() {
+        match rest.split_once(':') {
+            Some((authority, _)) if !authority.contains('/') => authority.len(),
+            _ => return url.to_string(),
+        }
+    } else {
+        rest.find('/').unwrap_or(rest.len())
+    };
+    let authority = &rest[..authority_end];
+    let host_start = authority.rfind('@').map_or(0, |at| at + 1);
+    let host_end = authority[host_start..]
+        .find(':')
+        .map_or(authority_end, |colon| host_start + colon);
+
+    match ssh_host_name(ssh_config, &rest[host_start..host_end]) {
+        Some(host_name) => format!(
+            "{prefix}{}{host_name}{}",
+            &rest[..host_start],
+            &rest[host_end..]
+        ),
+        None => url.to_string(),
+    }
+}
+
+/// Looks up the `HostName` for `alias` in an OpenSSH client config. Only `Host` entries
+/// naming the alias exactly are considered (no wildcards); the first `HostName` wins, as in ssh.
+fn ssh_host_name(ssh_config: &str, alias: &str) -> Option<String> {
+    let mut in_block = false;
+    for line in ssh_config.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((keyword, value)) = line.split_once(|c: char| c.is_whitespace() || c == '=')
+        else {
             continue;
         };
-        let url = rest.trim_end_matches("(fetch)").trim();
-        seen.push((name.to_string(), url.to_string()));
+        let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+
+        if keyword.eq_ignore_ascii_case("host") {
+            in_block = value.split_whitespace().any(|pattern| pattern == alias);
+        } else if keyword.eq_ignore_ascii_case("match") {
+            in_block = false;
+        } else if in_block && keyword.eq_ignore_ascii_case("hostname") {
+            return Some(value.trim().replace("%h", alias));
+        }
     }
+    None
+}
 
-    Ok(seen)
+/// Hash algorithm for the objects of a new repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ObjectFormat {
+    Sha1,
+    Sha256,
+}
+
+impl std::fmt::Display for ObjectFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+        })
+    }
+}
+
+/// Options for `init`, mapped onto `git init` flags
+#[derive(Debug, Default, Clone)]
+pub struct InitOptions {
+    pub bare: bool,
+    /// Name of the initial branch instead of git's `init.defaultBranch`
+    pub initial_branch: Option<String>,
+    /// Template directory to seed hooks and config from
+    pub template: Option<PathBuf>,
+    /// Object hash algorithm, git's default (sha1) if `None`
+    pub object_format: Option<ObjectFormat>,
+}
+
+/// Initializes a repository in `target`
+pub fn init(target: &Path, opts: &InitOptions) -> Result<()> {
+    if let Some(format) = opts.object_format {
+        check_object_format(format, version())?;
+    }
+
+    let output = init_command(target, opts)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to execute git init")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", format_error("Init failed", &stderr));
+    }
+
+    Ok(())
+}
+
+/// Fails if the installed git is known to be too old for `--object-format`
+pub fn check_object_format(format: ObjectFormat, found: Option<GitVersion>) -> Result<()> {
+    if let Some(found) = found
+        && found < OBJECT_FORMAT_MIN_VERSION
+    {
+        bail!(
+            "--object-format={format} requires git {OBJECT_FORMAT_MIN_VERSION} or newer (found {found})"
+        );
+    }
+    Ok(())
+}
+
+/// Builds the `git init` invocation for the given options
+fn init_command(target: &Path, opts: &InitOptions) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("init");
+    if opts.bare {
+        cmd.arg("--bare");
+    }
+    if let Some(branch) = &opts.initial_branch {
+        cmd.arg(format!("--initial-branch={branch}"));
+    }
+    if let Some(dir) = &opts.template {
+        cmd.arg(format!("--template={}", dir.display()));
+    }
+    if let Some(format) = opts.object_format {
+        cmd.arg(format!("--object-format={format}"));
+    }
+    cmd.arg(target);
+    cmd
+}
+
+/// Options for `clone`, mapped onto `git clone` flags
+#[derive(Debug, Default, Clone)]
+pub struct CloneOptions {
+    /// Create a shallow clone with this many commits
+    pub depth: Option<u32>,
+    /// Check out this branch instead of the remote's `HEAD`
+    pub branch: Option<String>,
+    pub bare: bool,
+    pub mirror: bool,
+    pub recurse_submodules: bool,
+    /// Extra environment variables for the git process (e.g. `GIT_SSH_COMMAND`)
+    pub env: Vec<(String, String)>,
+}
+
+/// Clones `url` into `target`, showing git's progress stages on a spinner
+pub fn clone(url: &str, target: &Path, opts: &CloneOptions) -> Result<()> {
+    let spinner = crate::term::spinner("Cloning repository...");
+
+    let mut child = clone_command(url, target, opts)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git clone")?;
+
+    // Channel to collect stderr for error reporting
+    let (tx, rx) = mpsc::channel();
+
+    // Read stderr in a separate thread to avoid blocking
+    // Git uses \r for progress updates (same-line overwrites), so we read raw and split on \r or \n
+    let mut stderr = child.stderr.take().expect("stderr was piped");
+    let spinner_clone = spinner.clone();
+    let reader_thread = thread::spawn(move || {
+        let mut all_output = String::new();
+        let mut buf = [0u8; 256];
+        let mut line_buf = String::new();
+
+        while let Ok(n) = stderr.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let chunk = String::from_utf8_lossy(&buf[..n]);
+            all_output.push_str(&chunk);
+
+            for c in chunk.chars() {
+                if c == '\r' || c == '\n' {
+                    if !line_buf.is_empty() {
+                        // Parse git progress output and update spinner
+                        if let Some((stage, percent)) = parse_git_progress(&line_buf) {
+                            let msg = match percent {
+                                Some(p) => format!("Cloning repository [{stage}: {p}%]..."),
+                                None => format!("Cloning repository [{stage}]..."),
+                            };
+                            spinner_clone.set_message(msg);
+                        }
+                        line_buf.clear();
+                    }
+                } else {
+                    line_buf.push(c);
+                }
+            }
+        }
+
+        let _ = tx.send(all_output);
+    });
+
+    let status = child.wait().context("Failed to wait for git clone")?;
+
+    let _ = reader_thread.join();
+    let stderr_output = rx.recv().unwrap_or_default();
+
+    spinner.finish_and_clear();
+
+    if !status.success() {
+        bail!("{}", format_error("Clone failed", &stderr_output));
+    }
+
+    Ok(())
+}
+
+/// Builds the `git clone` invocation for the given options
+fn clone_command(url: &str, target: &Path, opts: &CloneOptions) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--progress"]);
+    if let Some(depth) = opts.depth {
+        cmd.arg(format!("--depth={depth}"));
+    }
+    if let Some(branch) = &opts.branch {
+        cmd.args(["--branch", branch]);
+    }
+    if opts.mirror {
+        cmd.arg("--mirror");
+    } else if opts.bare {
+        cmd.arg("--bare");
+    }
+    if opts.recurse_submodules {
+        cmd.arg("--recurse-submodules");
+    }
+    cmd.envs(opts.env.iter().map(|(k, v)| (k, v)));
+    cmd.arg(url).arg(target);
+    cmd
+}
+
+/// Parses git progress output to extract the current stage and optional percentage
+fn parse_git_progress(line: &str) -> Option<(&str, Option<u8>)> {
+    // Strip optional "remote:" prefix, then parse "Stage: NN%" format
+    let line = line
+        .trim()
+        .strip_prefix("remote:")
+        .unwrap_or(line.trim())
+        .trim();
+
+    let colon_pos = line.find(':')?;
+    let stage = line[..colon_pos].trim();
+
+    if is_progress_stage(stage) {
+        let percent = extract_percent(&line[colon_pos + 1..]);
+        Some((stage, percent))
+    } else {
+        None
+    }
+}
+
+/// Checks if the given string is a recognized git progress stage
+fn is_progress_stage(stage: &str) -> bool {
+    matches!(
+        stage,
+        "Cloning into"
+            | "Enumerating objects"
+            | "Counting objects"
+            | "Compressing objects"
+            | "Receiving objects"
+            | "Resolving deltas"
+            | "Updating files"
+    )
+}
+
+/// Extracts percentage from a string like " 45% (55/123)" or "100% (50/50), done."
+fn extract_percent(s: &str) -> Option<u8> {
+    let s = s.trim();
+    let percent_pos = s.find('%')?;
+    let num_str = s[..percent_pos].trim();
+    num_str.parse().ok()
 }
 
 #[cfg(test)]
@@ -179,4 +1121,701 @@ mod tests {
         assert!(result.contains("line2"));
         assert!(result.contains("line3"));
     }
+
+    #[test]
+    fn test_read_only_git_flag_before_subcommand() {
+        let mut cmd = read_only_git(Path::new("/repo"));
+        cmd.args(["status", "--porcelain"]);
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "--no-optional-locks",
+                "-C",
+                "/repo",
+                "status",
+                "--porcelain"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_version_plain() {
+        assert_eq!(
+            parse_version("git version 2.39.2\n"),
+            Some(GitVersion::new(2, 39, 2))
+        );
+    }
+
+    #[test]
+    fn test_parse_version_vendor_suffixes() {
+        assert_eq!(
+            parse_version("git version 2.39.3 (Apple Git-145)"),
+            Some(GitVersion::new(2, 39, 3))
+        );
+        assert_eq!(
+            parse_version("git version 2.45.1.windows.1"),
+            Some(GitVersion::new(2, 45, 1))
+        );
+        assert_eq!(
+            parse_version("git version 2.43.0-rc1"),
+            Some(GitVersion::new(2, 43, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_version_missing_patch() {
+        assert_eq!(
+            parse_version("git version 2.50"),
+            Some(GitVersion::new(2, 50, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_version_invalid() {
+        assert_eq!(parse_version(""), None);
+        assert_eq!(parse_version("hub version 2.14.2"), None);
+        assert_eq!(parse_version("git version abc"), None);
+    }
+
+    #[test]
+    fn test_git_version_ordering() {
+        assert!(GitVersion::new(2, 9, 5) < STATUS_V2_MIN_VERSION);
+        assert!(GitVersion::new(2, 36, 0) >= HASCONFIG_MIN_VERSION);
+        assert!(GitVersion::new(3, 0, 0) > HASCONFIG_MIN_VERSION);
+    }
+
+    #[test]
+    fn test_env_flag() {
+        assert!(!env_flag(None));
+        assert!(!env_flag(Some("")));
+        assert!(!env_flag(Some("0")));
+        assert!(!env_flag(Some("false")));
+        assert!(env_flag(Some("1")));
+        assert!(env_flag(Some("true")));
+    }
+
+    #[test]
+    fn test_is_bare_repository_layout() {
+        let dir = std::env::temp_dir().join("yarm-test-bare-layout");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("objects")).unwrap();
+        assert!(!is_bare_repository(&dir));
+
+        std::fs::create_dir_all(dir.join("refs")).unwrap();
+        std::fs::write(dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        assert!(is_bare_repository(&dir));
+        assert!(is_repository(&dir));
+    }
+
+    #[test]
+    fn test_parse_status_v2_clean_with_upstream() {
+        let output = "# branch.oid abc123\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n";
+        let status = parse_status_v2(output);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert!(!status.dirty);
+        assert_eq!((status.ahead, status.behind), (2, 1));
+    }
+
+    #[test]
+    fn test_parse_status_v2_dirty_detached() {
+        let output = "# branch.oid abc123\n# branch.head (detached)\n? new.txt\n";
+        let status = parse_status_v2(output);
+        assert_eq!(status.branch.as_deref(), Some("HEAD"));
+        assert!(status.dirty);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+    }
+
+    #[test]
+    fn test_batch_status_temp_repos() {
+        let root = std::env::temp_dir().join("yarm-test-batch-status");
+        let _ = std::fs::remove_dir_all(&root);
+
+        let clean = root.join("clean");
+        let dirty = root.join("dirty");
+        let missing = root.join("missing");
+        for repo in [&clean, &dirty] {
+            std::fs::create_dir_all(repo).unwrap();
+            let status = Command::new("git")
+                .args(["init", "-q", "-b", "trunk"])
+                .arg(repo)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        std::fs::write(dirty.join("file.txt"), "x").unwrap();
+
+        let done = AtomicUsize::new(0);
+        let repos = vec![clean.clone(), dirty.clone(), missing.clone()];
+        let results = batch_status(&repos, || {
+            done.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(done.load(Ordering::Relaxed), 3);
+        assert_eq!(results[0].path, clean);
+        assert_eq!(results[0].branch.as_deref(), Some("trunk"));
+        assert!(!results[0].dirty);
+        assert_eq!(results[1].path, dirty);
+        assert!(results[1].dirty);
+        assert_eq!(results[2].path, missing);
+        assert_eq!(results[2].branch, None);
+    }
+
+    #[test]
+    fn test_last_commit_time() {
+        let repo = std::env::temp_dir().join("yarm-test-last-commit");
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+
+        git(&["init", "-q"]);
+        assert_eq!(last_commit_time(&repo), None);
+
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        let time = last_commit_time(&repo).unwrap();
+        let age = SystemTime::now().duration_since(time).unwrap_or_default();
+        assert!(age < Duration::from_mins(1));
+    }
+
+    #[test]
+    fn test_worktree_paths() {
+        let root = std::env::temp_dir().join("yarm-test-worktree");
+        let _ = std::fs::remove_dir_all(&root);
+        let main = root.join("main");
+        std::fs::create_dir_all(&main).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&main)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&["worktree", "add", "-q", "../linked"]);
+
+        let linked = root.join("linked");
+        let nested = linked.join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        let canonical = |p: Option<PathBuf>| p.unwrap().canonicalize().unwrap();
+
+        assert_eq!(canonical(toplevel(&nested)), linked.canonicalize().unwrap());
+        assert_eq!(
+            canonical(common_dir(&nested)),
+            main.join(".git").canonicalize().unwrap()
+        );
+        assert_eq!(
+            canonical(git_dir(&linked)),
+            main.join(".git/worktrees/linked").canonicalize().unwrap()
+        );
+        assert_eq!(toplevel(&root), None);
+
+        // Local config written from the worktree lands in the shared repository config
+        set_config(&linked, "user.name", Some("Linked")).unwrap();
+        assert_eq!(get_config(&main, "user.name").as_deref(), Some("Linked"));
+    }
+
+    #[test]
+    fn test_empty_repository() {
+        let root = std::env::temp_dir().join("yarm-test-empty-repo");
+        let _ = std::fs::remove_dir_all(&root);
+        let empty = root.join("empty");
+        let committed = root.join("committed");
+        let bare = root.join("bare.git");
+        for dir in [&empty, &committed] {
+            std::fs::create_dir_all(dir).unwrap();
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["init", "-q"])
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&committed)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(["commit", "-q", "--allow-empty", "-m", "init"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = Command::new("git")
+            .args(["clone", "-q", "--bare"])
+            .arg(&committed)
+            .arg(&bare)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert!(is_empty_repository(&empty));
+        assert!(!is_empty_repository(&committed));
+        assert!(!is_empty_repository(&bare));
+        assert!(!is_empty_repository(&root));
+
+        assert!(!current_branch(&empty).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_parse_remotes_same_urls() {
+        let output =
+            "origin\tgit@github.com:o/r.git (fetch)\norigin\tgit@github.com:o/r.git (push)\n";
+        let remotes = parse_remotes(output);
+        assert_eq!(
+            remotes,
+            vec![Remote {
+                name: "origin".to_string(),
+                fetch_url: "git@github.com:o/r.git".to_string(),
+                push_url: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_remotes_distinct_push_url() {
+        let output = "mirror\thttps://mirror.local/o/r.git (fetch)\n\
+                      mirror\tgit@github.com:o/r.git (push)\n\
+                      origin\thttps://github.com/o/r.git (fetch)\n\
+                      origin\thttps://github.com/o/r.git (push)\n";
+        let remotes = parse_remotes(output);
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes[0].name, "mirror");
+        assert_eq!(remotes[0].fetch_url, "https://mirror.local/o/r.git");
+        assert_eq!(
+            remotes[0].push_url.as_deref(),
+            Some("git@github.com:o/r.git")
+        );
+        assert_eq!(remotes[1].push_url, None);
+    }
+
+    #[test]
+    fn test_parse_symref_head() {
+        let output = "ref: refs/heads/main\tHEAD\n4d7a2146b1c0e0d5f3a8b9c2e1f0a9b8c7d6e5f4\tHEAD\n";
+        assert_eq!(parse_symref_head(output).as_deref(), Some("main"));
+        assert_eq!(
+            parse_symref_head("ref: refs/heads/release/2.x\tHEAD\n").as_deref(),
+            Some("release/2.x")
+        );
+        assert_eq!(
+            parse_symref_head("4d7a2146b1c0e0d5f3a8b9c2e1f0a9b8c7d6e5f4\tHEAD\n"),
+            None
+        );
+        assert_eq!(parse_symref_head(""), None);
+    }
+
+    #[test]
+    fn test_get_config_path_includes_inherited_values() {
+        let repo = std::env::temp_dir().join("yarm-test-config-path");
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["init", "-q"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // A value from an included file is not local, but still in effect
+        let shared = repo.join("shared.gitconfig");
+        std::fs::write(&shared, "[core]\n\thooksPath = ~/hooks\n").unwrap();
+        set_config(&repo, "include.path", Some(&shared.to_string_lossy())).unwrap();
+
+        assert_eq!(get_config(&repo, "core.hooksPath"), None);
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            get_config_path(&repo, "core.hooksPath").map(PathBuf::from),
+            Some(home.join("hooks"))
+        );
+
+        let _ = std::fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_default_branch_from_origin_head() {
+        let repo = std::env::temp_dir().join("yarm-test-default-branch");
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+
+        // No origin remote at all
+        assert_eq!(default_branch(&repo).unwrap(), None);
+
+        git(&[
+            "remote",
+            "add",
+            "origin",
+            "https://example.invalid/repo.git",
+        ]);
+        git(&[
+            "symbolic-ref",
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/trunk",
+        ]);
+        assert_eq!(default_branch(&repo).unwrap().as_deref(), Some("trunk"));
+
+        let _ = std::fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_parse_lfs_size() {
+        assert_eq!(
+            parse_lfs_size("4d7a2146b1 * assets/logo.png (12 B)"),
+            Some(12)
+        );
+        assert_eq!(
+            parse_lfs_size("4d7a2146b1 - data (v2).bin (1.5 MB)"),
+            Some(1_500_000)
+        );
+        assert_eq!(parse_lfs_size("4d7a2146b1 * notes.txt"), None);
+        assert_eq!(parse_lfs_size("4d7a2146b1 * odd (3 parsecs)"), None);
+    }
+
+    #[test]
+    fn test_parse_track_formats() {
+        assert_eq!(parse_track(""), (0, 0, false));
+        assert_eq!(parse_track("[ahead 2]"), (2, 0, false));
+        assert_eq!(parse_track("[behind 5]"), (0, 5, false));
+        assert_eq!(parse_track("[ahead 3, behind 1]"), (3, 1, false));
+        assert_eq!(parse_track("[gone]"), (0, 0, true));
+    }
+
+    #[test]
+    fn test_parse_branches() {
+        let output =
+            "main\torigin/main\t[ahead 1, behind 2]\nfeature\t\t\nold\torigin/old\t[gone]\n";
+        let branches = parse_branches(output);
+        assert_eq!(branches.len(), 3);
+        assert_eq!(
+            branches[0],
+            Branch {
+                name: "main".to_string(),
+                upstream: Some("origin/main".to_string()),
+                ahead: 1,
+                behind: 2,
+                gone: false,
+            }
+        );
+        assert_eq!(branches[1].upstream, None);
+        assert!(branches[2].gone);
+    }
+
+    #[test]
+    fn test_parse_git_url_https() {
+        let parsed = parse_git_url("https://github.com/owner/repo.git").unwrap();
+        assert_eq!(parsed.host, "github.com");
+        assert_eq!(parsed.path, "owner/repo.git");
+    }
+
+    #[test]
+    fn test_parse_git_url_scp_like() {
+        let parsed = parse_git_url("git@gitlab.example.org:group/sub/repo.git").unwrap();
+        assert_eq!(parsed.host, "gitlab.example.org");
+        assert_eq!(parsed.path, "group/sub/repo.git");
+    }
+
+    #[test]
+    fn test_parse_git_url_ssh_with_port() {
+        let parsed = parse_git_url("ssh://git@Git.Example.com:2222/owner/repo").unwrap();
+        assert_eq!(parsed.host, "git.example.com");
+        assert_eq!(parsed.path, "owner/repo");
+    }
+
+    #[test]
+    fn test_parse_git_url_local_paths() {
+        assert_eq!(parse_git_url("file:///srv/git/repo.git"), None);
+        assert_eq!(parse_git_url("/srv/git/repo.git"), None);
+        assert_eq!(parse_git_url("../relative/repo"), None);
+    }
+
+    fn read_config_file(file: &Path, key: &str) -> Option<String> {
+        let output = Command::new("git")
+            .args(["config", "--file"])
+            .arg(file)
+            .arg(key)
+            .output()
+            .unwrap();
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    #[test]
+    fn test_set_config_scoped_file() {
+        let dir = std::env::temp_dir().join("yarm-test-config-file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("profile.gitconfig");
+        let target = ConfigTarget::File(file.clone());
+
+        set_config_scoped(&target, "user.name", Some("Jane")).unwrap();
+        assert_eq!(
+            read_config_file(&file, "user.name").as_deref(),
+            Some("Jane")
+        );
+        assert_eq!(
+            get_config_scoped(&target, "user.name").as_deref(),
+            Some("Jane")
+        );
+
+        set_config_scoped(&target, "user.name", None).unwrap();
+        assert_eq!(read_config_file(&file, "user.name"), None);
+        // Unsetting a missing key is not an error
+        set_config_scoped(&target, "user.name", None).unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_set_config_scoped_local() {
+        let repo = std::env::temp_dir().join("yarm-test-config-local");
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["init", "-q"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert_eq!(
+            ConfigTarget::from_path(&repo),
+            ConfigTarget::Local(repo.clone())
+        );
+        set_config_scoped(
+            &ConfigTarget::Local(repo.clone()),
+            "user.email",
+            Some("j@ex.com"),
+        )
+        .unwrap();
+        assert_eq!(get_config(&repo, "user.email").as_deref(), Some("j@ex.com"));
+        assert_eq!(
+            read_config_file(&repo.join(".git/config"), "user.email").as_deref(),
+            Some("j@ex.com")
+        );
+
+        let _ = std::fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn test_set_config_scoped_global() {
+        let dir = std::env::temp_dir().join("yarm-test-config-global");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("global.gitconfig");
+
+        // Point the global scope at a temp file for this invocation only
+        let mut cmd = ConfigTarget::Global.command();
+        cmd.env("GIT_CONFIG_GLOBAL", &file);
+        run_set_config(cmd, "user.name", Some("Global")).unwrap();
+        assert_eq!(
+            read_config_file(&file, "user.name").as_deref(),
+            Some("Global")
+        );
+
+        let mut cmd = ConfigTarget::Global.command();
+        cmd.env("GIT_CONFIG_GLOBAL", &file);
+        run_set_config(cmd, "user.name", None).unwrap();
+        assert_eq!(read_config_file(&file, "user.name"), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    const SSH_CONFIG: &str = "\
+# Work account
+Host github-work
+    HostName github.com
+    IdentityFile ~/.ssh/id_work
+
+Host gl-* internal
+  HostName=%h.example.com
+
+Host github-work
+    HostName ignored.example.com
+";
+
+    #[test]
+    fn test_resolve_ssh_alias() {
+        assert_eq!(
+            resolve_ssh_alias("git@github-work:owner/repo.git", SSH_CONFIG),
+            "git@github.com:owner/repo.git"
+        );
+        assert_eq!(
+            resolve_ssh_alias("ssh://git@github-work:2222/owner/repo.git", SSH_CONFIG),
+            "ssh://git@github.com:2222/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_ssh_alias("internal:team/tools", SSH_CONFIG),
+            "internal.example.com:team/tools"
+        );
+    }
+
+    #[test]
+    fn test_resolve_ssh_alias_passthrough() {
+        // Unknown aliases, wildcard-only matches, and non-SSH URLs stay as they are
+        for url in [
+            "git@github.com:owner/repo.git",
+            "git@gl-one:owner/repo.git",
+            "https://github-work/owner/repo.git",
+            "./local/path",
+            "/abs/path:with-colon",
+        ] {
+            assert_eq!(resolve_ssh_alias(url, SSH_CONFIG), url);
+        }
+        assert_eq!(
+            resolve_ssh_alias("git@github-work:o/r.git", ""),
+            "git@github-work:o/r.git"
+        );
+    }
+
+    #[test]
+    fn test_init_command_args() {
+        let cmd = init_command(Path::new("/tmp/r"), &InitOptions::default());
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["init", "/tmp/r"]);
+
+        let opts = InitOptions {
+            bare: true,
+            initial_branch: Some("main".to_string()),
+            template: Some(PathBuf::from("/tpl")),
+            object_format: Some(ObjectFormat::Sha256),
+        };
+        let cmd = init_command(Path::new("/tmp/r"), &opts);
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            [
+                "init",
+                "--bare",
+                "--initial-branch=main",
+                "--template=/tpl",
+                "--object-format=sha256",
+                "/tmp/r"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_object_format() {
+        let old = GitVersion::new(2, 28, 0);
+        let err = check_object_format(ObjectFormat::Sha256, Some(old)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--object-format=sha256 requires git 2.29.0 or newer (found 2.28.0)"
+        );
+        assert!(check_object_format(ObjectFormat::Sha256, Some(OBJECT_FORMAT_MIN_VERSION)).is_ok());
+        // An undetected version is left for git itself to reject
+        assert!(check_object_format(ObjectFormat::Sha1, None).is_ok());
+    }
+
+    #[test]
+    fn test_clone_command_args() {
+        let opts = CloneOptions {
+            depth: Some(1),
+            branch: Some("dev".to_string()),
+            recurse_submodules: true,
+            env: vec![("GIT_SSH_COMMAND".to_string(), "ssh -i key".to_string())],
+            ..CloneOptions::default()
+        };
+        let cmd = clone_command("https://ex.com/r.git", Path::new("/tmp/r"), &opts);
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "clone",
+                "--progress",
+                "--depth=1",
+                "--branch",
+                "dev",
+                "--recurse-submodules",
+                "https://ex.com/r.git",
+                "/tmp/r"
+            ]
+        );
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert_eq!(
+            envs,
+            [(
+                std::ffi::OsStr::new("GIT_SSH_COMMAND"),
+                Some(std::ffi::OsStr::new("ssh -i key"))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_clone_command_mirror_over_bare() {
+        let opts = CloneOptions {
+            bare: true,
+            mirror: true,
+            ..CloneOptions::default()
+        };
+        let cmd = clone_command("/src", Path::new("dst.git"), &opts);
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, ["clone", "--progress", "--mirror", "/src", "dst.git"]);
+    }
+
+    #[test]
+    fn test_parse_git_progress_remote_stage() {
+        assert_eq!(
+            parse_git_progress("remote: Enumerating objects: 123, done."),
+            Some(("Enumerating objects", None))
+        );
+    }
+
+    #[test]
+    fn test_parse_git_progress_direct_stage_with_percent() {
+        assert_eq!(
+            parse_git_progress("Receiving objects:  45% (55/123)"),
+            Some(("Receiving objects", Some(45)))
+        );
+    }
+
+    #[test]
+    fn test_parse_git_progress_resolving_deltas_complete() {
+        assert_eq!(
+            parse_git_progress("Resolving deltas: 100% (50/50), done."),
+            Some(("Resolving deltas", Some(100)))
+        );
+    }
+
+    #[test]
+    fn test_parse_git_progress_remote_with_percent() {
+        assert_eq!(
+            parse_git_progress("remote: Counting objects: 75% (90/120)"),
+            Some(("Counting objects", Some(75)))
+        );
+    }
+
+    #[test]
+    fn test_parse_git_progress_non_stage_line() {
+        assert_eq!(parse_git_progress("fatal: repository not found"), None);
+    }
+
+    #[test]
+    fn test_parse_git_progress_empty_line() {
+        assert_eq!(parse_git_progress(""), None);
+    }
 }
@@ -4,8 +4,16 @@ use clap_complete::{Shell, generate};
 use std::io;
 use std::path::PathBuf;
 use std::process;
+use std::time::{Duration, SystemTime};
 
+use commands::apply::Scope;
+use commands::find::GroupBy;
+use commands::profiles::{ListOptions, NewProfile, ProfileFilter};
+use commands::scan::Output as ScanOutput;
+use commands::stat::Layout;
 use console::style;
+use git::ObjectFormat;
+use profile::ProfileSelection;
 use term::SilentExit;
 
 mod commands;
@@ -19,9 +27,38 @@ mod term;
 #[derive(Parser)]
 #[command(name = "yarm")]
 #[command(version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    /// Never pipe long output through a pager
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Print less: no pager and no hints (confirmations are still asked)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print diagnostic details to stderr
+    #[arg(long, global = true)]
+    debug: bool,
+
+    /// Refuse operations that need network access (also enabled by `YARM_OFFLINE`)
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Skip the automatic rescan when the state is outdated (also enabled by `YARM_NO_AUTO_RESCAN`)
+    #[arg(long, global = true)]
+    no_auto_rescan: bool,
+
+    /// Print paths as clickable links on terminals that support them (OSC 8)
+    #[arg(long, global = true)]
+    hyperlinks: bool,
+
+    /// Print times as absolute local timestamps instead of relative ones ("3 hours ago")
+    #[arg(long, global = true)]
+    absolute_time: bool,
 }
 
 #[derive(Subcommand)]
@@ -35,6 +72,18 @@ enum Command {
         /// Use named profile instead of interactive selection
         #[arg(short, long)]
         profile: Option<String>,
+        /// Clone into a pool (the configured default pool if no name is given)
+        #[arg(long, value_name = "POOL", num_args = 0..=1, default_missing_value = "")]
+        into: Option<String>,
+        /// Create a bare mirror clone (target defaults to <repo>.git)
+        #[arg(long)]
+        mirror: bool,
+        /// If the target already is a clone of this URL, skip cloning and only apply a profile
+        #[arg(long)]
+        apply_to_existing: bool,
+        /// Clone even if a scanned repository already has this origin
+        #[arg(long)]
+        force: bool,
     },
 
     /// Initialize a new repository with profile selection
@@ -42,6 +91,18 @@ enum Command {
         /// Use named profile instead of interactive selection
         #[arg(short, long)]
         profile: Option<String>,
+        /// Template directory passed to git init (overrides `init_template` config)
+        #[arg(long, value_name = "DIR")]
+        template: Option<String>,
+        /// Write a .gitignore from this template in the configured `gitignore_dir`
+        #[arg(long, value_name = "TEMPLATE")]
+        with_gitignore: Option<String>,
+        /// Create an initial commit (with the .gitignore, if any) using the applied profile
+        #[arg(long)]
+        initial_commit: bool,
+        /// Object hash algorithm of the new repository (needs git 2.29 or newer)
+        #[arg(long, value_enum, value_name = "FORMAT")]
+        object_format: Option<ObjectFormat>,
     },
 
     /// Apply a profile to an existing repository
@@ -54,6 +115,40 @@ enum Command {
         /// Apply to all repositories in a pool
         #[arg(short = 'P', long)]
         pool: Option<String>,
+        /// With --pool, choose each repository's profile from `profiles.map` path rules
+        /// (unmatched repositories get the selected profile)
+        #[arg(long, requires = "pool")]
+        auto_map: bool,
+        /// With --pool, confirm each repository individually (--yes skips all confirmations)
+        #[arg(long, requires = "pool")]
+        confirm_each: bool,
+        /// With --pool, restore every modified repository if applying to any of them fails
+        #[arg(long, requires = "pool")]
+        atomic: bool,
+        /// Resolve the profile from includeIf rules or the default profile, never prompt
+        #[arg(long, conflicts_with = "profile")]
+        auto: bool,
+        /// Use the Nth profile (1-based) in the order shown by `profiles --show`
+        #[arg(long, value_name = "N", conflicts_with_all = ["profile", "auto"])]
+        profile_index: Option<usize>,
+        /// Remove the repository's local identity config instead of applying a profile
+        #[arg(long, conflicts_with_all = ["profile", "pool", "auto", "profile_index"])]
+        revert: bool,
+        /// Write the profile to the global git config instead of a repository
+        #[arg(long, conflicts_with_all = ["name", "pool", "revert"])]
+        global: bool,
+        /// Pick the repository from a menu of scanned repositories
+        #[arg(short, long, conflicts_with_all = ["name", "pool", "global"])]
+        interactive: bool,
+        /// Only verify that the targets use the profile, exiting 1 if any does not (writes nothing)
+        #[arg(
+            long,
+            conflicts_with_all = ["revert", "auto_map", "confirm_each", "atomic"]
+        )]
+        check: bool,
+        /// Don't ask before overwriting or removing existing identity config
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Manage git identity profiles
@@ -63,6 +158,108 @@ enum Command {
         /// Print profile details without interactive menu
         #[arg(short, long)]
         show: bool,
+        /// Create a profile from a repository's local identity (current directory if omitted)
+        #[arg(
+            long,
+            value_name = "REPO",
+            num_args = 0..=1,
+            default_missing_value = ".",
+            conflicts_with_all = ["name", "show"]
+        )]
+        capture: Option<String>,
+        /// With --show, print tab-separated lines: name, source, user.name, user.email, signingkey, gpgsign
+        #[arg(long, requires = "show")]
+        porcelain: bool,
+        /// Set a profile key non-interactively (repeatable, empty value unsets)
+        #[arg(
+            long = "set",
+            value_name = "KEY=VALUE",
+            requires = "name",
+            conflicts_with = "show"
+        )]
+        settings: Vec<String>,
+        /// Create a profile non-interactively at ~/.gitconfig-<NAME> (requires --name)
+        #[arg(
+            long,
+            value_name = "NAME",
+            requires = "user_name",
+            conflicts_with_all = ["name", "show", "capture"]
+        )]
+        create: Option<String>,
+        /// User name for --create
+        #[arg(long = "name", value_name = "USER_NAME", requires = "create")]
+        user_name: Option<String>,
+        /// Email address for --create
+        #[arg(long, requires = "create")]
+        email: Option<String>,
+        /// Signing key for --create
+        #[arg(long, requires = "create")]
+        signing_key: Option<String>,
+        /// Signing format for --create
+        #[arg(
+            long,
+            requires = "signing_key",
+            value_parser = ["openpgp", "ssh", "x509"]
+        )]
+        format: Option<String>,
+        /// Sign commits and tags (for --create)
+        #[arg(long, requires = "signing_key")]
+        sign: bool,
+        /// With --show, only list profiles that have a signing key
+        #[arg(long, requires = "show", conflicts_with = "name")]
+        with_signing: bool,
+        /// With --show, only list profiles with an email address in this domain
+        #[arg(
+            long,
+            value_name = "DOMAIN",
+            requires = "show",
+            conflicts_with = "name"
+        )]
+        email_domain: Option<String>,
+        /// With --show, group profiles by the directory of their config file
+        #[arg(long, requires = "show", conflicts_with_all = ["name", "porcelain"])]
+        tree: bool,
+        /// Open the profile's gitconfig file in $EDITOR
+        #[arg(
+            long,
+            requires = "name",
+            conflicts_with_all = ["show", "settings"]
+        )]
+        edit: bool,
+        /// Apply the configured default profile (`profiles.default`) to the global git config
+        #[arg(
+            long,
+            conflicts_with_all = ["name", "show", "capture", "create"]
+        )]
+        apply_default: bool,
+        /// Download a gitconfig file over HTTPS (e.g. a gist) and install it as a new profile
+        #[arg(
+            long,
+            value_name = "URL",
+            conflicts_with_all = ["name", "show", "capture", "create", "apply_default"]
+        )]
+        import_url: Option<String>,
+        /// List the scanned repositories whose local user.email matches each profile
+        #[arg(
+            long,
+            conflicts_with_all = ["name", "show", "capture", "create", "apply_default", "import_url"]
+        )]
+        usage: bool,
+        /// Skip the confirmation prompt for --apply-default
+        #[arg(short, long, requires = "apply_default")]
+        yes: bool,
+    },
+
+    /// Pin a repository as a favorite, preferred by `find` when a name is ambiguous
+    Pin {
+        /// Repository name or path
+        repo: String,
+    },
+
+    /// Remove a repository from the pinned favorites
+    Unpin {
+        /// Pinned repository name or path
+        repo: String,
     },
 
     /// Print the full path of a scanned repository or pool
@@ -70,24 +267,99 @@ enum Command {
         /// Repository name or path fragment to match
         repo: Option<String>,
         /// Find a repository pool by name instead of a repository
-        #[arg(short = 'P', long)]
+        /// (the configured default pool if no name is given)
+        #[arg(short = 'P', long, num_args = 0..=1, default_missing_value = "")]
         pool: Option<String>,
+        /// Print every known repository
+        #[arg(long, conflicts_with_all = ["repo", "pool"])]
+        all: bool,
+        /// Print the pinned repositories
+        #[arg(long, conflicts_with_all = ["repo", "pool", "all"])]
+        pinned: bool,
+        /// Print repositories as a JSON array of {name, path, pool} objects
+        #[arg(long, requires = "all")]
+        json: bool,
+        /// Only print repositories whose local user.email matches
+        #[arg(long, requires = "all", conflicts_with = "profile")]
+        email: Option<String>,
+        /// Only print repositories whose local user.email matches this profile's
+        #[arg(short, long, requires = "all")]
+        profile: Option<String>,
+        /// Group the repositories by pool or by origin host (reads each repository's remotes)
+        #[arg(long, value_enum, value_name = "KEY", requires = "all")]
+        group_by: Option<GroupBy>,
+        /// Print how many repositories match instead of a path
+        #[arg(long, requires = "repo", conflicts_with = "pool")]
+        count: bool,
+        /// Match the repository's origin remote URL instead of its path (e.g. owner/repo)
+        #[arg(long, requires = "repo", conflicts_with = "pool")]
+        remote: bool,
     },
 
     /// Show information about a repository
     Stat {
         /// Repository name or path (defaults to current directory)
         repo: Option<String>,
+        /// List all local branches with their upstream and ahead/behind counts
+        #[arg(short, long)]
+        branches: bool,
+        /// Print only these fields, in order (comma-separated, e.g. branch,dirty,size)
+        #[arg(long, value_delimiter = ',', conflicts_with = "branches")]
+        fields: Option<Vec<String>>,
+        /// Print a single line from a template, e.g. '{branch} {dirty}'
+        #[arg(long, conflicts_with_all = ["branches", "fields"])]
+        format: Option<String>,
+        /// Skip the size calculation (implied when --fields or --format shows no size field)
+        #[arg(long)]
+        no_size: bool,
+        /// Pick the repository from a menu of scanned repositories
+        #[arg(short, long, conflicts_with = "repo")]
+        interactive: bool,
     },
 
     /// Scan repository pools for git repositories
-    Scan,
+    Scan {
+        /// List repositories added or removed since the last scan
+        #[arg(short, long)]
+        verbose: bool,
+        /// Also find repositories nested inside other repositories' working trees
+        #[arg(long)]
+        recurse_nested: bool,
+        /// Print the found repository paths only, one per line
+        #[arg(long, conflicts_with = "verbose")]
+        porcelain: bool,
+        /// Print only the number of repositories found
+        #[arg(long, conflicts_with_all = ["verbose", "porcelain"])]
+        count_only: bool,
+        /// Report what the scan finds without updating the state
+        #[arg(long)]
+        dry_run: bool,
+    },
 
     /// Show repository pool status
     Status {
         /// List all repositories in each pool
         #[arg(short, long)]
         full: bool,
+        /// Sort pools alphabetically instead of configuration order
+        #[arg(long)]
+        sort: bool,
+        /// Only include repositories with a commit within this window (e.g. 7d, 2w, 3mo)
+        #[arg(long, value_name = "DURATION")]
+        active_since: Option<String>,
+        /// Re-render the status view periodically until Ctrl-C
+        #[arg(short, long)]
+        watch: bool,
+        /// Seconds between refreshes in watch mode
+        #[arg(long, value_name = "SECONDS", default_value_t = 5, requires = "watch", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+
+    /// Show version information (--verbose adds git, config, and state details)
+    Version {
+        /// Also report the git version, config and state paths, and repository count
+        #[arg(short, long)]
+        verbose: bool,
     },
 
     /// Generate shell completions
@@ -95,11 +367,20 @@ enum Command {
         /// Shell to generate completions for
         #[arg(value_enum)]
         shell: Shell,
+        /// Write the completions and the `ye` function to the shell's usual locations instead
+        #[arg(long)]
+        install: bool,
     },
 
     /// Output repository names for shell completion
     #[command(hide = true)]
-    CompleteRepoNames,
+    CompleteRepoNames {
+        /// Only output names starting with this prefix (the word being completed)
+        prefix: Option<String>,
+        /// Output at most this many names
+        #[arg(long, value_name = "N")]
+        max_results: Option<usize>,
+    },
 
     /// Output pool basenames for shell completion
     #[command(hide = true)]
@@ -125,7 +406,7 @@ _ye_complete() {\n\
   if [[ \"$prev\" == \"--pool\" || \"$prev\" == \"-P\" ]]; then\n\
     COMPREPLY=($(compgen -W \"$(command yarm complete-pool-names 2>/dev/null)\" -- \"$cur\"))\n\
   elif [[ \"$cur\" != -* ]]; then\n\
-    COMPREPLY=($(compgen -W \"$(command yarm complete-repo-names 2>/dev/null)\" -- \"$cur\"))\n\
+    COMPREPLY=($(compgen -W \"$(command yarm complete-repo-names -- \"$cur\" 2>/dev/null)\" -- \"$cur\"))\n\
   fi\n\
 }\n\
 complete -F _ye_complete ye\n"
@@ -148,7 +429,7 @@ _ye() {\n\
     pools=(${(f)\"$(command yarm complete-pool-names 2>/dev/null)\"})\n\
     compadd -a pools\n\
   else\n\
-    repos=(${(f)\"$(command yarm complete-repo-names 2>/dev/null)\"})\n\
+    repos=(${(f)\"$(command yarm complete-repo-names -- \"${words[CURRENT]}\" 2>/dev/null)\"})\n\
     compadd -a repos\n\
   fi\n\
 }\n\
@@ -167,15 +448,34 @@ end\n\
 \n\
 complete -c ye -f\n\
 complete -c ye -s P -l pool -xa '(command yarm complete-pool-names 2>/dev/null)'\n\
-complete -c ye -n 'not __fish_seen_option -P pool' -xa '(command yarm complete-repo-names 2>/dev/null)'\n"
+complete -c ye -n 'not __fish_seen_option -P pool' -xa '(command yarm complete-repo-names -- (commandline -ct) 2>/dev/null)'\n"
                 .to_string()
         }
         Shell::PowerShell => {
-            "\nfunction ye { Write-Host; $d = yarm find @args; if ($LASTEXITCODE -eq 0) { Set-Location $d; Write-Host \"  ✓ navigated to $($d -replace [regex]::Escape($HOME), '~')\" -ForegroundColor Green }; Write-Host }\n"
+            "\nfunction ye { Write-Host; $d = yarm find @args; if ($LASTEXITCODE -eq 0) { Set-Location $d; Write-Host \"  ✓ navigated to $($d -replace [regex]::Escape($HOME), '~')\" -ForegroundColor Green }; Write-Host }\n\
+\n\
+Register-ArgumentCompleter -Native -CommandName ye -ScriptBlock {\n\
+  param($wordToComplete, $commandAst, $cursorPosition)\n\
+  $prev = $commandAst.CommandElements | Where-Object { $_.Extent.EndOffset -lt $cursorPosition } | Select-Object -Last 1\n\
+  if (\"$prev\" -in '-P', '--pool') { $names = yarm complete-pool-names 2>$null }\n\
+  elseif ($wordToComplete -notlike '-*') { $names = yarm complete-repo-names 2>$null }\n\
+  $names | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n\
+    [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n\
+  }\n\
+}\n"
                 .to_string()
         }
         Shell::Elvish => {
-            "\nfn ye {|@args| echo >&2; var dir = (yarm find $@args); cd $dir; echo '  ✓ navigated to '(str:replace $E:HOME '~' $dir) >&2; echo >&2 }\n".to_string()
+            "\nfn ye {|@args| echo >&2; var dir = (yarm find $@args); cd $dir; echo '  ✓ navigated to '(str:replace $E:HOME '~' $dir) >&2; echo >&2 }\n\
+\n\
+set edit:completion:arg-completer[ye] = {|@words|\n\
+  if (and (> (count $words) 2) (has-value [-P --pool] $words[-2])) {\n\
+    yarm complete-pool-names 2>/dev/null\n\
+  } elif (not (str:has-prefix $words[-1] -)) {\n\
+    yarm complete-repo-names 2>/dev/null\n\
+  }\n\
+}\n"
+                .to_string()
         }
         _ => String::new(),
     }
@@ -191,13 +491,23 @@ fn main() {
     }
 }
 
-fn should_auto_rescan(command: &Command) -> bool {
+fn should_auto_rescan(command: &Command, no_auto_rescan: bool) -> bool {
+    if no_auto_rescan || git::env_flag(std::env::var("YARM_NO_AUTO_RESCAN").ok().as_deref()) {
+        return false;
+    }
+
     !matches!(
         command,
-        Command::Scan
+        Command::Scan { .. }
+            | Command::Version { .. }
             | Command::Completions { .. }
-            | Command::CompleteRepoNames
+            | Command::CompleteRepoNames { .. }
             | Command::CompletePoolNames
+            | Command::Profiles {
+                porcelain: true,
+                ..
+            }
+            | Command::Find { json: true, .. }
     )
 }
 
@@ -216,58 +526,332 @@ fn try_auto_rescan() -> Result<()> {
         style("↻").cyan(),
         style("State outdated, rescanning...").dim()
     );
-    commands::scan::run()
+    commands::scan::run_auto()
+}
+
+fn checks_staleness(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Find { .. } | Command::Status { .. } | Command::Apply { pool: Some(_), .. }
+    )
+}
+
+fn warn_if_stale() -> Result<()> {
+    let config = config::load()?;
+    let Some(stale_after) = config.repositories.stale_after.as_deref() else {
+        return Ok(());
+    };
+    // A malformed value is reported once by Config::validate and otherwise ignored
+    let Ok(threshold) = commands::status::parse_since(stale_after) else {
+        return Ok(());
+    };
+
+    if let Some(age) = state::age()
+        && is_stale(age, threshold)
+    {
+        let scanned = SystemTime::now() - age;
+        term::eprint_hint(format!(
+            "Last scan was {}, run `yarm scan` to refresh",
+            term::format_time(scanned)
+        ));
+    }
+    Ok(())
+}
+
+fn is_stale(age: Duration, threshold: Duration) -> bool {
+    age > threshold
 }
 
+#[allow(clippy::too_many_lines)]
 fn run() -> Result<()> {
     let cli = Cli::parse();
+    term::set_debug(cli.debug);
+    git::set_offline(cli.offline);
+    term::set_hyperlinks(cli.hyperlinks);
+    term::set_absolute_time(cli.absolute_time);
+    term::set_quiet(cli.quiet);
+    let allow_pager = !cli.no_pager && !cli.quiet;
 
-    if should_auto_rescan(&cli.command) {
+    if should_auto_rescan(&cli.command, cli.no_auto_rescan) {
         try_auto_rescan()?;
     }
+    if checks_staleness(&cli.command) {
+        warn_if_stale()?;
+    }
 
     match cli.command {
-        Command::Clone { url, path, profile } => {
-            commands::clone::run(&url, path, profile.as_deref())?;
+        Command::Clone {
+            url,
+            path,
+            profile,
+            into,
+            mirror,
+            apply_to_existing,
+            force,
+        } => {
+            commands::clone::run(
+                &url,
+                path,
+                profile.as_deref(),
+                into.as_deref(),
+                mirror,
+                apply_to_existing,
+                force,
+            )?;
             println!();
         }
-        Command::Init { profile } => {
-            commands::init::run(profile.as_deref())?;
+        Command::Init {
+            profile,
+            template,
+            with_gitignore,
+            initial_commit,
+            object_format,
+        } => {
+            commands::init::run(
+                profile.as_deref(),
+                template.as_deref(),
+                with_gitignore.as_deref(),
+                initial_commit,
+                object_format,
+            )?;
             println!();
         }
         Command::Apply {
             name,
             profile,
             pool,
+            auto_map,
+            confirm_each,
+            atomic,
+            auto,
+            profile_index,
+            revert,
+            global,
+            interactive,
+            check,
+            yes,
         } => {
-            commands::apply::run(name.as_deref(), profile.as_deref(), pool.as_deref())?;
+            let name = if interactive {
+                let Some(path) = commands::pick_repo()? else {
+                    return Ok(());
+                };
+                Some(path.display().to_string())
+            } else {
+                name
+            };
+            let selection = if auto {
+                ProfileSelection::Auto
+            } else if let Some(index) = profile_index {
+                ProfileSelection::Index(index)
+            } else {
+                profile.as_deref().into()
+            };
+            let scope = if global {
+                Scope::Global
+            } else if let Some(pool) = pool.as_deref() {
+                Scope::Pool {
+                    name: pool,
+                    auto_map,
+                    confirm_each: confirm_each && !yes,
+                    atomic,
+                }
+            } else {
+                Scope::Repo(name.as_deref())
+            };
+            if check {
+                commands::apply::check(scope, selection)?;
+            } else {
+                commands::apply::run(scope, selection, revert, yes)?;
+            }
             println!();
         }
-        Command::Profiles { name, show } => {
-            commands::profiles::run(name.as_deref(), show)?;
-            println!();
-        }
-        Command::Find { repo, pool } => {
-            commands::find::run(repo.as_deref(), pool.as_deref())?;
+        Command::Profiles {
+            name,
+            show,
+            capture,
+            porcelain,
+            settings,
+            create,
+            user_name,
+            email,
+            signing_key,
+            format,
+            sign,
+            with_signing,
+            email_domain,
+            tree,
+            edit,
+            apply_default,
+            import_url,
+            usage,
+            yes,
+        } => {
+            if let Some(name) = name.as_deref().filter(|_| edit) {
+                commands::profiles::open_in_editor(name)?;
+                println!();
+                return Ok(());
+            }
+            if apply_default {
+                commands::profiles::apply_default(yes)?;
+                println!();
+                return Ok(());
+            }
+            if usage {
+                commands::profiles::show_usage(allow_pager)?;
+                println!();
+                return Ok(());
+            }
+            if let Some(url) = import_url {
+                commands::profiles::import_url(&url)?;
+                println!();
+                return Ok(());
+            }
+
+            let create = create.map(|name| NewProfile {
+                name,
+                user_name: user_name.unwrap_or_default(),
+                user_email: email,
+                signing_key,
+                gpg_format: format,
+                sign,
+            });
+            commands::profiles::run(
+                name.as_deref(),
+                show,
+                capture.as_deref(),
+                &settings,
+                porcelain,
+                create,
+                &ListOptions {
+                    filter: ProfileFilter {
+                        with_signing,
+                        email_domain,
+                    },
+                    tree,
+                },
+                allow_pager,
+            )?;
+            if !porcelain {
+                println!();
+            }
         }
-        Command::Stat { repo } => {
-            commands::stat::run(repo)?;
+        Command::Pin { repo } => {
+            commands::pin::pin(&repo)?;
             println!();
         }
-        Command::Scan => {
-            commands::scan::run()?;
+        Command::Unpin { repo } => {
+            commands::pin::unpin(&repo)?;
             println!();
         }
-        Command::Status { full } => {
-            commands::status::run(full)?;
+        Command::Find {
+            repo,
+            pool,
+            all,
+            json,
+            email,
+            profile,
+            count,
+            remote,
+            group_by,
+            pinned,
+        } => {
+            if pinned {
+                commands::find::run_pinned()?;
+            } else if all {
+                commands::find::run_all(json, email.as_deref(), profile.as_deref(), group_by)?;
+            } else {
+                commands::find::run(repo.as_deref(), pool.as_deref(), count, remote)?;
+            }
+        }
+        Command::Stat {
+            repo,
+            branches,
+            fields,
+            format,
+            no_size,
+            interactive,
+        } => {
+            let repo = if interactive {
+                let Some(path) = commands::pick_repo()? else {
+                    return Ok(());
+                };
+                Some(path.display().to_string())
+            } else {
+                repo
+            };
+            let layout = match (fields, format) {
+                (Some(fields), _) => Layout::Fields(fields),
+                (None, Some(format)) => Layout::Format(format),
+                (None, None) => Layout::Full,
+            };
+            commands::stat::run(repo, branches, &layout, no_size)?;
+            if matches!(layout, Layout::Full) {
+                println!();
+            }
+        }
+        Command::Scan {
+            verbose,
+            recurse_nested,
+            porcelain,
+            count_only,
+            dry_run,
+        } => {
+            let output = if porcelain {
+                ScanOutput::Porcelain
+            } else if count_only {
+                ScanOutput::Count
+            } else {
+                ScanOutput::Full
+            };
+            commands::scan::run(verbose, recurse_nested, output, dry_run)?;
+            if output == ScanOutput::Full {
+                println!();
+            }
+        }
+        Command::Status {
+            full,
+            sort,
+            active_since,
+            watch,
+            interval,
+        } => {
+            if watch {
+                commands::status::watch(
+                    full,
+                    sort,
+                    active_since.as_deref(),
+                    Duration::from_secs(interval),
+                )?;
+            } else {
+                commands::status::run(full, sort, active_since.as_deref(), allow_pager)?;
+            }
             println!();
         }
-        Command::Completions { shell } => {
-            generate(shell, &mut Cli::command(), "yarm", &mut io::stdout());
-            print!("{}", shell_functions(shell));
+        Command::Version { verbose } => {
+            commands::version::run(verbose)?;
+            if verbose {
+                println!();
+            }
         }
-        Command::CompleteRepoNames => {
-            commands::find::complete_repo_names()?;
+        Command::Completions { shell, install } => {
+            if install {
+                let mut completion = Vec::new();
+                generate(shell, &mut Cli::command(), "yarm", &mut completion);
+                commands::completions::install(
+                    shell,
+                    String::from_utf8(completion)?,
+                    shell_functions(shell),
+                )?;
+            } else {
+                generate(shell, &mut Cli::command(), "yarm", &mut io::stdout());
+                print!("{}", shell_functions(shell));
+            }
+        }
+        Command::CompleteRepoNames {
+            prefix,
+            max_results,
+        } => {
+            commands::find::complete_repo_names(prefix.as_deref(), max_results)?;
         }
         Command::CompletePoolNames => {
             commands::find::complete_pool_names()?;
@@ -276,3 +860,81 @@ fn run() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale_only_beyond_threshold() {
+        let day = Duration::from_hours(24);
+        assert!(!is_stale(Duration::from_hours(23), day));
+        assert!(!is_stale(day, day));
+        assert!(is_stale(Duration::from_hours(25), day));
+    }
+
+    #[test]
+    fn test_apply_default_conflicts_with_profile_actions() {
+        assert!(Cli::try_parse_from(["yarm", "profiles", "--apply-default", "--yes"]).is_ok());
+        assert!(Cli::try_parse_from(["yarm", "profiles", "work", "--apply-default"]).is_err());
+        assert!(Cli::try_parse_from(["yarm", "profiles", "--show", "--apply-default"]).is_err());
+        assert!(Cli::try_parse_from(["yarm", "profiles", "--yes"]).is_err());
+    }
+
+    #[test]
+    fn test_no_auto_rescan_skips_rescan() {
+        let cli = Cli::parse_from(["yarm", "find", "api"]);
+        assert!(!cli.no_auto_rescan);
+        assert!(!should_auto_rescan(&cli.command, true));
+
+        let cli = Cli::parse_from(["yarm", "find", "api", "--no-auto-rescan"]);
+        assert!(cli.no_auto_rescan);
+    }
+
+    #[test]
+    fn test_quiet_does_not_skip_confirmations() {
+        let cli = Cli::parse_from(["yarm", "apply", "-p", "work", "--quiet"]);
+        assert!(cli.quiet);
+        assert!(matches!(cli.command, Command::Apply { yes: false, .. }));
+
+        let cli = Cli::parse_from(["yarm", "-q", "status", "--full"]);
+        assert!(cli.quiet);
+    }
+
+    #[test]
+    fn test_interactive_conflicts_with_explicit_targets() {
+        assert!(Cli::try_parse_from(["yarm", "stat", "-i"]).is_ok());
+        assert!(Cli::try_parse_from(["yarm", "stat", "-i", "api"]).is_err());
+        assert!(Cli::try_parse_from(["yarm", "apply", "-i", "-p", "work"]).is_ok());
+        assert!(Cli::try_parse_from(["yarm", "apply", "-i", "-P", "work"]).is_err());
+        assert!(Cli::try_parse_from(["yarm", "apply", "-i", "--global"]).is_err());
+    }
+
+    #[test]
+    fn test_checks_staleness_for_pool_apply_only() {
+        let pooled = Cli::parse_from(["yarm", "apply", "-P", "work"]);
+        assert!(checks_staleness(&pooled.command));
+        let single = Cli::parse_from(["yarm", "apply", "repo"]);
+        assert!(!checks_staleness(&single.command));
+        let status = Cli::parse_from(["yarm", "status"]);
+        assert!(checks_staleness(&status.command));
+    }
+
+    #[test]
+    fn test_shell_functions_powershell_completer() {
+        let script = shell_functions(Shell::PowerShell);
+        assert!(script.contains("function ye"));
+        assert!(script.contains("Register-ArgumentCompleter -Native -CommandName ye"));
+        assert!(script.contains("yarm complete-pool-names"));
+        assert!(script.contains("yarm complete-repo-names"));
+    }
+
+    #[test]
+    fn test_shell_functions_elvish_completer() {
+        let script = shell_functions(Shell::Elvish);
+        assert!(script.contains("fn ye"));
+        assert!(script.contains("set edit:completion:arg-completer[ye]"));
+        assert!(script.contains("yarm complete-pool-names"));
+        assert!(script.contains("yarm complete-repo-names"));
+    }
+}
@@ -1,13 +1,16 @@
-use anyhow::{Context, Result};
-use console::Term;
+use anyhow::{Context, Result, bail};
+use console::style;
+use globset::GlobBuilder;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 
 use crate::config::expand_tilde;
 use crate::git;
-use crate::term::{MenuLevel, format_home_path, is_cancelled};
+use crate::term::{
+    MenuLevel, clear_last_line, eprint_warning, format_home_path, is_cancelled, is_interactive,
+};
 
 /// Error message when no profiles are found
 pub const NO_PROFILES_ERROR: &str =
@@ -31,6 +34,25 @@ impl ProfileContext {
     }
 }
 
+/// How a profile is chosen when resolving one for a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSelection<'a> {
+    /// Interactive menu, with includeIf/default matches promoted to the top
+    Interactive,
+    /// Explicitly named profile (`--profile`)
+    Named(&'a str),
+    /// 1-based position in the discovered profile order (`--profile-index`)
+    Index(usize),
+    /// Resolved from includeIf rules or the configured default, never prompts (`--auto`)
+    Auto,
+}
+
+impl<'a> From<Option<&'a str>> for ProfileSelection<'a> {
+    fn from(name: Option<&'a str>) -> Self {
+        name.map_or(Self::Interactive, Self::Named)
+    }
+}
+
 /// An includeIf rule parsed from a gitconfig file
 #[derive(Debug, Clone)]
 struct IncludeIfRule {
@@ -38,6 +60,8 @@ struct IncludeIfRule {
     condition: String,
     /// The included config file path
     target_path: PathBuf,
+    /// The config file declaring the rule
+    origin: PathBuf,
 }
 
 impl IncludeIfRule {
@@ -55,44 +79,28 @@ impl IncludeIfRule {
         false
     }
 
-    /// Matches gitdir: patterns against the target path
+    /// Matches gitdir: patterns against the target's git directory like git does:
+    /// the pattern is a glob over the `.git` path, where a trailing `/` matches
+    /// everything below and a relative pattern matches at any depth.
     fn matches_gitdir(pattern: &str, context: &ProfileContext, case_insensitive: bool) -> bool {
         let Some(target) = &context.target_path else {
             return false;
         };
 
-        let target = match target.canonicalize() {
-            Ok(p) => p,
-            Err(_) => target.clone(),
-        };
-
-        let pattern_path = expand_tilde(pattern);
-
-        let pattern_normalized = match pattern_path.canonicalize() {
-            Ok(p) => p,
-            Err(_) => pattern_path,
-        };
-
-        let target_str = target.to_string_lossy();
-        let pattern_str = pattern_normalized.to_string_lossy();
-
-        let (target_cmp, pattern_cmp) = if case_insensitive {
-            (target_str.to_lowercase(), pattern_str.to_lowercase())
-        } else {
-            (target_str.to_string(), pattern_str.to_string())
+        let Ok(glob) = GlobBuilder::new(&gitdir_glob(pattern))
+            .literal_separator(true)
+            .case_insensitive(case_insensitive)
+            .build()
+        else {
+            return false;
         };
+        let matcher = glob.compile_matcher();
 
-        if pattern.ends_with('/') || pattern.ends_with("/**") {
-            // Directory prefix match
-            let prefix = pattern_cmp.trim_end_matches('/').trim_end_matches("**");
-            target_cmp.starts_with(prefix)
-        } else if pattern.contains('*') {
-            // Glob pattern - simple wildcard matching
-            glob_match(&pattern_cmp, &target_cmp)
-        } else {
-            // Exact match
-            target_cmp == pattern_cmp
-        }
+        // Like git, try the path as given and then with symlinks resolved
+        matcher.is_match(git_dir_of(target))
+            || target
+                .canonicalize()
+                .is_ok_and(|canonical| matcher.is_match(git_dir_of(&canonical)))
     }
 
     /// Matches hasconfig:remote.*.url: patterns against the clone URL
@@ -105,6 +113,43 @@ impl IncludeIfRule {
     }
 }
 
+/// Rewrites a gitdir: pattern the way git does before matching:
+/// `~/` is expanded, relative patterns get a `**/` prefix, and a trailing `/` gets `**` appended.
+fn gitdir_glob(pattern: &str) -> String {
+    let mut glob = expand_tilde(pattern).to_string_lossy().into_owned();
+    if !Path::new(&glob).is_absolute() {
+        glob.insert_str(0, "**/");
+    }
+    if glob.ends_with('/') {
+        glob.push_str("**");
+    }
+    glob
+}
+
+/// Returns the git directory of a repository (or clone target): `.git` inside
+/// a working tree, or the directory itself for bare repositories.
+fn git_dir_of(repo: &Path) -> PathBuf {
+    if git::is_bare_repository(repo) {
+        repo.to_path_buf()
+    } else {
+        repo.join(".git")
+    }
+}
+
+/// Resolves a `gitdir:./` pattern relative to the directory of the config file containing it.
+fn resolve_relative_gitdir(condition: &str, config_file: &Path) -> String {
+    for prefix in ["gitdir:", "gitdir/i:"] {
+        if let Some(rest) = condition
+            .strip_prefix(prefix)
+            .and_then(|p| p.strip_prefix("./"))
+        {
+            let base = config_file.parent().unwrap_or(Path::new(""));
+            return format!("{prefix}{}/{rest}", base.display());
+        }
+    }
+    condition.to_string()
+}
+
 /// Simple glob matching supporting * and **
 fn glob_match(pattern: &str, text: &str) -> bool {
     let pattern_parts: Vec<&str> = pattern.split('*').collect();
@@ -154,6 +199,16 @@ fn parse_include_if_rules() -> Vec<IncludeIfRule> {
         }
     }
 
+    if rules
+        .iter()
+        .any(|rule| rule.condition.starts_with("hasconfig:"))
+    {
+        git::warn_if_older(
+            git::HASCONFIG_MIN_VERSION,
+            "includeIf \"hasconfig:\" conditions",
+        );
+    }
+
     rules
 }
 
@@ -184,8 +239,9 @@ fn parse_include_if_from_file(path: &Path) -> Vec<IncludeIfRule> {
                 .map(str::trim)
         {
             rules.push(IncludeIfRule {
-                condition: condition.clone(),
+                condition: resolve_relative_gitdir(condition, path),
                 target_path: expand_tilde(path_value),
+                origin: path.to_path_buf(),
             });
         }
     }
@@ -212,10 +268,26 @@ pub struct Profile {
     pub gpg_format: Option<String>,
     /// Git tag.gpgsign value
     pub tag_gpg_sign: Option<bool>,
+    /// Git core.sshCommand value, used to authenticate clones with the profile's SSH key
+    pub ssh_command: Option<String>,
     /// Whether this profile is the configured yarm default
     pub is_default: bool,
+    /// Whether this profile provides the identity git currently resolves to
+    pub is_current: bool,
+    /// Other files defining the same identity, collapsed into this profile by `profiles.dedupe`
+    pub duplicate_sources: Vec<PathBuf>,
 }
 
+/// Git config keys that make up a profile
+pub const PROFILE_KEYS: [&str; 6] = [
+    "user.name",
+    "user.email",
+    "user.signingkey",
+    "gpg.format",
+    "commit.gpgsign",
+    "tag.gpgsign",
+];
+
 /// A profile field with its display label and value
 pub struct ProfileField<'a> {
     pub label: &'static str,
@@ -271,6 +343,106 @@ impl Profile {
     pub fn display_option(&self) -> String {
         format!("{} ({})", self.name, format_home_path(&self.source))
     }
+
+    /// Returns the configured value for one of [`PROFILE_KEYS`] as written to gitconfig
+    pub fn config_value(&self, key: &str) -> Option<String> {
+        let bool_value = |v: bool| v.to_string();
+        match key {
+            "user.name" => self.user_name.clone(),
+            "user.email" => self.user_email.clone(),
+            "user.signingkey" => self.signing_key.clone(),
+            "gpg.format" => self.gpg_format.clone(),
+            "commit.gpgsign" => self.gpg_sign.map(bool_value),
+            "tag.gpgsign" => self.tag_gpg_sign.map(bool_value),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a `key=value` profile setting. An empty value means the key should be unset.
+/// Returns the normalized key and value after validating both.
+pub fn parse_setting(setting: &str) -> Result<(&'static str, Option<String>)> {
+    let Some((key, value)) = setting.split_once('=') else {
+        bail!("Invalid setting '{setting}', expected key=value");
+    };
+
+    let key = key.trim().to_lowercase();
+    let Some(key) = PROFILE_KEYS.into_iter().find(|k| *k == key) else {
+        bail!(
+            "Unsupported key '{key}'. Supported keys: {}",
+            PROFILE_KEYS.join(", ")
+        );
+    };
+
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok((key, None));
+    }
+
+    let value = match key {
+        "user.email" if !is_valid_email(value) => bail!("Invalid email address '{value}'"),
+        "gpg.format" if !["openpgp", "ssh", "x509"].contains(&value) => {
+            bail!("Invalid signing format '{value}', expected openpgp, ssh, or x509")
+        }
+        "commit.gpgsign" | "tag.gpgsign" => match parse_bool(value) {
+            Some(b) => b.to_string(),
+            None => bail!("Invalid boolean '{value}' for {key}"),
+        },
+        _ => value.to_string(),
+    };
+
+    Ok((key, Some(value)))
+}
+
+/// Where a profile's gitconfig file lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileScope {
+    /// System-wide gitconfig (e.g. `/etc/gitconfig`), read-only for users
+    System,
+    /// The user's main `~/.gitconfig`
+    Global,
+    /// A repository's `.git/config`
+    Local,
+    /// A standalone profile file managed by the user
+    Custom,
+    /// Values without a file, e.g. from `git -c` or `GIT_CONFIG_PARAMETERS`, which cannot be edited
+    NonFile,
+}
+
+/// Classifies a gitconfig path by scope.
+/// Compares path components rather than strings, so both `/` and `\` separators work.
+pub fn profile_scope(path: &Path) -> ProfileScope {
+    let file_name = path.file_name();
+    let parent = path.parent().unwrap_or(Path::new(""));
+    if non_file_origin(path).is_some() {
+        ProfileScope::NonFile
+    } else if is_system_config(path) {
+        ProfileScope::System
+    } else if file_name.is_some_and(|n| n == ".gitconfig") {
+        ProfileScope::Global
+    } else if file_name.is_some_and(|n| n == "config")
+        && parent.file_name().is_some_and(|n| n == ".git")
+    {
+        ProfileScope::Local
+    } else {
+        ProfileScope::Custom
+    }
+}
+
+/// Whether `path` is a system-wide config: anything under `/etc`, or the
+/// `$(prefix)/etc/gitconfig` git reads with `--system` (e.g. Homebrew's or Git for Windows')
+fn is_system_config(path: &Path) -> bool {
+    let mut components = path.components();
+    let under_root_etc = matches!(
+        (components.next(), components.next()),
+        (Some(Component::RootDir), Some(Component::Normal(dir))) if dir == "etc"
+    );
+    under_root_etc
+        || (path.file_name().is_some_and(|n| n == "gitconfig")
+            && path
+                .parent()
+                .and_then(Path::file_name)
+                .is_some_and(|n| n == "etc"))
 }
 
 /// Discovers git identity profiles from gitconfig files.
@@ -285,6 +457,7 @@ impl Profile {
 pub fn discover_profiles() -> Result<Vec<Profile>> {
     let config = crate::config::load()?;
     let extra_paths = config.profile_paths();
+    let name_strip = &config.profiles.name_strip;
 
     let mut git_profiles = Vec::new();
     let mut additional_profiles = Vec::new();
@@ -301,24 +474,24 @@ pub fn discover_profiles() -> Result<Vec<Profile>> {
     if output.status.success() {
         let stdout =
             String::from_utf8(output.stdout).context("Invalid UTF-8 in git config output")?;
-        for profile in parse_git_config_output(&stdout) {
+        for profile in parse_git_config_output(&stdout, name_strip) {
             seen_sources.insert(profile.source.clone());
             git_profiles.push(profile);
         }
     }
 
-    for path in find_gitconfig_files(&extra_paths) {
+    for path in find_gitconfig_files(&extra_paths, config.profiles.recursive) {
         if seen_sources.contains(&path) {
             continue;
         }
-        if let Some(profile) = parse_gitconfig_file(&path) {
+        if let Some(profile) = parse_gitconfig_file(&path, name_strip) {
             seen_sources.insert(path);
             additional_profiles.push(profile);
         }
     }
 
-    git_profiles.sort_by(|a, b| a.name.cmp(&b.name));
-    additional_profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    git_profiles.sort_by(compare_profiles);
+    additional_profiles.sort_by(compare_profiles);
 
     let current_idx = current_email.as_ref().and_then(|email| {
         git_profiles
@@ -330,7 +503,9 @@ pub fn discover_profiles() -> Result<Vec<Profile>> {
 
     // Add current profile first if found
     if let Some(idx) = current_idx {
-        profiles.push(git_profiles.remove(idx));
+        let mut current = git_profiles.remove(idx);
+        current.is_current = true;
+        profiles.push(current);
     }
 
     profiles.extend(git_profiles);
@@ -342,9 +517,57 @@ pub fn discover_profiles() -> Result<Vec<Profile>> {
         p.is_default = true;
     }
 
+    if config.profiles.dedupe {
+        profiles = dedupe_profiles(profiles);
+    }
+
     Ok(profiles)
 }
 
+/// Collapses profiles with the same `user.name`, `user.email`, and `user.signingkey`.
+/// The configured default wins, otherwise the most specific source (a standalone file over
+/// `~/.gitconfig` over the system config); the other sources are kept in `duplicate_sources`.
+/// The surviving profile takes the list position of the first of its group.
+fn dedupe_profiles(profiles: Vec<Profile>) -> Vec<Profile> {
+    let mut kept: Vec<Profile> = Vec::new();
+
+    for profile in profiles {
+        let Some(existing) = kept.iter_mut().find(|k| {
+            k.user_name == profile.user_name
+                && k.user_email == profile.user_email
+                && k.signing_key == profile.signing_key
+        }) else {
+            kept.push(profile);
+            continue;
+        };
+
+        let mut dropped = if dedupe_rank(&profile) > dedupe_rank(existing) {
+            std::mem::replace(existing, profile)
+        } else {
+            profile
+        };
+        existing.duplicate_sources.push(dropped.source);
+        existing
+            .duplicate_sources
+            .append(&mut dropped.duplicate_sources);
+        existing.is_current |= dropped.is_current;
+    }
+
+    kept
+}
+
+/// Orders duplicate profiles by which one `dedupe_profiles` keeps
+fn dedupe_rank(profile: &Profile) -> (bool, u8) {
+    let specificity = match profile_scope(&profile.source) {
+        ProfileScope::NonFile => 0,
+        ProfileScope::System => 1,
+        ProfileScope::Global => 2,
+        ProfileScope::Local => 3,
+        ProfileScope::Custom => 4,
+    };
+    (profile.is_default, specificity)
+}
+
 /// Formats a profile for display
 fn format_profile_display(profile: &Profile) -> String {
     let mut parts = Vec::new();
@@ -368,7 +591,10 @@ fn format_profile_display(profile: &Profile) -> String {
     }
 
     let source_display = format_home_path(&profile.source);
-    parts.push(format!("({source_display})"));
+    match profile.duplicate_sources.len() {
+        0 => parts.push(format!("({source_display})")),
+        n => parts.push(format!("({source_display}, +{n} identical)")),
+    }
 
     parts.join(" ")
 }
@@ -378,32 +604,135 @@ fn format_profile_display(profile: &Profile) -> String {
 /// Profiles matching includeIf rules for the given context are promoted to the top.
 /// Returns `Ok(None)` if the user cancels the interactive selection.
 pub fn resolve_profile_with_context(
-    profile_name: Option<&str>,
+    selection: ProfileSelection<'_>,
     context: &ProfileContext,
 ) -> Result<Option<Profile>> {
     let config = crate::config::load()?;
     let profiles = discover_profiles()?;
+    resolve_from(
+        profiles,
+        selection,
+        context,
+        config.profiles.default.as_deref(),
+    )
+}
 
+/// Resolves a selection against discovered profiles. Only [`ProfileSelection::Interactive`]
+/// touches the terminal; named, indexed, and automatic selections never prompt.
+fn resolve_from(
+    profiles: Vec<Profile>,
+    selection: ProfileSelection<'_>,
+    context: &ProfileContext,
+    default_profile: Option<&str>,
+) -> Result<Option<Profile>> {
     if profiles.is_empty() {
         anyhow::bail!(NO_PROFILES_ERROR);
     }
 
-    let profiles =
-        reorder_profiles_by_context(profiles, context, config.profiles.default.as_deref());
+    match selection {
+        ProfileSelection::Named(name) => find_profile_by_name(&profiles, name).map(Some),
+        ProfileSelection::Index(index) => find_profile_by_index(&profiles, index).map(Some),
+        ProfileSelection::Auto => {
+            let rules = parse_include_if_rules();
+            auto_select_profile(&profiles, context, &rules, default_profile).map(Some)
+        }
+        ProfileSelection::Interactive => {
+            if !is_interactive() {
+                anyhow::bail!("No terminal for interactive profile selection; pass --profile");
+            }
+            let (profiles, promotion) =
+                reorder_profiles_by_context(profiles, context, default_profile);
+            select_profile(profiles, promotion.as_ref())
+        }
+    }
+}
 
-    match profile_name {
-        Some(name) => find_profile_by_name(&profiles, name).map(Some),
-        None => select_profile(profiles),
+/// Selects a profile without prompting, from includeIf rules or the configured default.
+///
+/// Errors if the matching rules point to more than one profile, or if no rule
+/// matches and no default profile is configured.
+fn auto_select_profile(
+    profiles: &[Profile],
+    context: &ProfileContext,
+    rules: &[IncludeIfRule],
+    default_profile: Option<&str>,
+) -> Result<Profile> {
+    let matching_rules: Vec<_> = rules.iter().filter(|rule| rule.matches(context)).collect();
+
+    let mut matched: Vec<&Profile> = Vec::new();
+    for rule in &matching_rules {
+        if let Some(profile) = profiles
+            .iter()
+            .find(|p| same_file(&p.source, &rule.target_path))
+            && !matched.contains(&profile)
+        {
+            matched.push(profile);
+        }
+    }
+
+    match matched.len() {
+        1 => Ok(matched[0].clone()),
+        0 => match default_profile {
+            Some(name) => find_profile_by_name(profiles, name),
+            None => anyhow::bail!(
+                "No includeIf rule matches and no default profile is configured; use --profile"
+            ),
+        },
+        _ => anyhow::bail!(
+            "Multiple includeIf rules match, cannot choose a profile automatically:\n{}",
+            matching_rules
+                .iter()
+                .map(|rule| format!(
+                    "    {} -> {}",
+                    rule.condition,
+                    format_home_path(&rule.target_path)
+                ))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+/// Compares two paths after resolving symlinks, falling back to the raw paths.
+fn same_file(a: &Path, b: &Path) -> bool {
+    let a = a.canonicalize().unwrap_or_else(|_| a.to_path_buf());
+    let b = b.canonicalize().unwrap_or_else(|_| b.to_path_buf());
+    a == b
+}
+
+/// Why a profile leads the interactive selection list
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Promotion {
+    /// An includeIf rule for the target matched, declared in `origin`
+    IncludeIf { condition: String, origin: PathBuf },
+    /// The configured `profiles.default`
+    Default,
+    /// The identity git currently resolves to
+    Current,
+}
+
+impl std::fmt::Display for Promotion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IncludeIf { condition, origin } => write!(
+                f,
+                "matched includeIf {condition} from {}",
+                format_home_path(origin)
+            ),
+            Self::Default => write!(f, "yarm default"),
+            Self::Current => write!(f, "current identity"),
+        }
     }
 }
 
 /// Reorders profiles so those matching includeIf rules come first.
 /// Falls back to promoting the configured default profile if no rules match.
+/// Also returns why the first profile leads, if it was promoted for a reason.
 fn reorder_profiles_by_context(
     profiles: Vec<Profile>,
     context: &ProfileContext,
     default_profile: Option<&str>,
-) -> Vec<Profile> {
+) -> (Vec<Profile>, Option<Promotion>) {
     if context.target_path.is_some() || context.clone_url.is_some() {
         let rules = parse_include_if_rules();
         return reorder_profiles_by_rules(profiles, context, &rules, default_profile);
@@ -419,11 +748,12 @@ fn reorder_profiles_by_rules(
     context: &ProfileContext,
     rules: &[IncludeIfRule],
     default_profile: Option<&str>,
-) -> Vec<Profile> {
+) -> (Vec<Profile>, Option<Promotion>) {
     if !rules.is_empty() {
-        let matching_sources: HashSet<PathBuf> = rules
+        let matching_rules: Vec<&IncludeIfRule> =
+            rules.iter().filter(|rule| rule.matches(context)).collect();
+        let matching_sources: HashSet<PathBuf> = matching_rules
             .iter()
-            .filter(|rule| rule.matches(context))
             .map(|rule| rule.target_path.clone())
             .collect();
 
@@ -432,16 +762,9 @@ fn reorder_profiles_by_rules(
             let mut non_matching = Vec::new();
 
             for profile in profiles {
-                let source_canonical = profile
-                    .source
-                    .canonicalize()
-                    .unwrap_or_else(|_| profile.source.clone());
-                let matches = matching_sources.iter().any(|rule_target| {
-                    let target_canonical = rule_target
-                        .canonicalize()
-                        .unwrap_or_else(|_| rule_target.clone());
-                    source_canonical == target_canonical
-                });
+                let matches = matching_sources
+                    .iter()
+                    .any(|rule_target| same_file(&profile.source, rule_target));
 
                 if matches {
                     matching.push(profile);
@@ -450,8 +773,17 @@ fn reorder_profiles_by_rules(
                 }
             }
 
+            let promotion = matching.first().and_then(|first| {
+                matching_rules
+                    .iter()
+                    .find(|rule| same_file(&first.source, &rule.target_path))
+                    .map(|rule| Promotion::IncludeIf {
+                        condition: rule.condition.clone(),
+                        origin: rule.origin.clone(),
+                    })
+            });
             matching.extend(non_matching);
-            return matching;
+            return (matching, promotion);
         }
     }
 
@@ -459,28 +791,52 @@ fn reorder_profiles_by_rules(
 }
 
 /// Promotes the configured default profile to the top of the list.
-fn promote_default(mut profiles: Vec<Profile>, default_name: Option<&str>) -> Vec<Profile> {
-    let Some(name) = default_name else {
-        return profiles;
-    };
-
-    if let Some(idx) = profiles.iter().position(|p| p.name == name) {
+/// Without a default, the current identity (listed first by discovery) keeps the lead.
+fn promote_default(
+    mut profiles: Vec<Profile>,
+    default_name: Option<&str>,
+) -> (Vec<Profile>, Option<Promotion>) {
+    if let Some(name) = default_name
+        && let Some(idx) = profiles.iter().position(|p| p.name == name)
+    {
         let default = profiles.remove(idx);
         profiles.insert(0, default);
+        return (profiles, Some(Promotion::Default));
     }
 
-    profiles
+    let promotion = profiles
+        .first()
+        .is_some_and(|p| p.is_current)
+        .then_some(Promotion::Current);
+    (profiles, promotion)
 }
 
-/// Interactive profile selection
+/// Interactive profile selection, preceded by why the first profile leads (if known)
 /// Returns `Ok(None)` if the user cancels.
-fn select_profile(profiles: Vec<Profile>) -> Result<Option<Profile>> {
+fn select_profile(
+    profiles: Vec<Profile>,
+    promotion: Option<&Promotion>,
+) -> Result<Option<Profile>> {
     let options: Vec<String> = profiles.iter().map(format_profile_display).collect();
 
-    let selection = match MenuLevel::Sub
+    if let (Some(promotion), Some(first)) = (promotion, profiles.first()) {
+        println!(
+            "  {}",
+            style(format!("'{}' listed first: {promotion}", first.name)).dim()
+        );
+    }
+
+    let result = MenuLevel::Sub
         .select_filterable("Select profile:", options.clone())
-        .prompt()
-    {
+        .prompt();
+
+    // Clear the answered (or cancelled) prompt and the promotion note in every case
+    clear_last_line();
+    if promotion.is_some() {
+        clear_last_line();
+    }
+
+    let selection = match result {
         Ok(s) => s,
         Err(e) if is_cancelled(&e) => return Ok(None),
         Err(e) => return Err(e).context("Profile selection failed"),
@@ -491,12 +847,63 @@ fn select_profile(profiles: Vec<Profile>) -> Result<Option<Profile>> {
         .position(|s| s == &selection)
         .ok_or_else(|| anyhow::anyhow!("Failed to find selected profile"))?;
 
-    let selected = profiles.into_iter().nth(selected_idx).unwrap();
+    Ok(profiles.into_iter().nth(selected_idx))
+}
 
-    let term = Term::stdout();
-    let _ = term.clear_last_lines(1);
+/// Fails early if a named or indexed selection doesn't resolve to a profile,
+/// so commands can reject it before printing or changing anything.
+pub fn validate_selection(selection: ProfileSelection<'_>) -> Result<()> {
+    match selection {
+        ProfileSelection::Named(_) | ProfileSelection::Index(_) => {
+            check_selection(&discover_profiles()?, selection)
+        }
+        ProfileSelection::Interactive | ProfileSelection::Auto => Ok(()),
+    }
+}
 
-    Ok(Some(selected))
+fn check_selection(profiles: &[Profile], selection: ProfileSelection<'_>) -> Result<()> {
+    match selection {
+        ProfileSelection::Named(name) => find_profile_by_name(profiles, name).map(drop),
+        ProfileSelection::Index(index) => find_profile_by_index(profiles, index).map(drop),
+        ProfileSelection::Interactive | ProfileSelection::Auto => Ok(()),
+    }
+}
+
+/// Finds the profile providing `email` (compared case-insensitively).
+/// A repository's own `.git/config` is discovered as a profile too when running inside it,
+/// so local profiles never count as a match.
+pub fn profile_for_email<'a>(profiles: &'a [Profile], email: &str) -> Option<&'a Profile> {
+    profiles
+        .iter()
+        .filter(|p| profile_scope(&p.source) != ProfileScope::Local)
+        .find(|p| {
+            p.user_email
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(email))
+        })
+}
+
+/// Returns the profile a repository names for itself, used when no profile is given:
+/// the one in its `.yarm-profile` file, or else the one pinned by its `.yarm` marker.
+/// A `.yarm-profile` naming an unknown profile is reported and ignored;
+/// errors from discovering the profiles to look it up in are returned.
+pub fn repo_profile_name(repo: &Path) -> Result<Option<String>> {
+    if let Some(name) = crate::config::read_profile_file(repo) {
+        let profiles = discover_profiles()?;
+        if let Some(profile) = profile_named_in_file(&profiles, &name) {
+            return Ok(Some(profile));
+        }
+        eprint_warning(format!(
+            "Profile '{name}' from {} not found",
+            format_home_path(&repo.join(crate::config::PROFILE_FILE))
+        ));
+    }
+    Ok(crate::state::pinned_profile(repo))
+}
+
+/// Resolves the name from a `.yarm-profile` file to a discovered profile's name
+fn profile_named_in_file(profiles: &[Profile], name: &str) -> Option<String> {
+    find_profile_by_name(profiles, name).ok().map(|p| p.name)
 }
 
 /// Finds a profile by name with fallback matching
@@ -537,46 +944,171 @@ pub fn find_profile_by_name(profiles: &[Profile], name: &str) -> Result<Profile>
     )
 }
 
+/// Finds a profile by its 1-based position in the order returned by [`discover_profiles`].
+pub fn find_profile_by_index(profiles: &[Profile], index: usize) -> Result<Profile> {
+    if let Some(profile) = index.checked_sub(1).and_then(|i| profiles.get(i)) {
+        return Ok(profile.clone());
+    }
+
+    anyhow::bail!(
+        "Profile index {index} out of range. Available profiles: {}",
+        profiles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("{}. {}", i + 1, p.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
 /// Applies profile settings to a repository
 pub fn apply_profile(repo_path: &Path, profile: &Profile) -> Result<()> {
-    let git_dir = repo_path.join(".git");
-    if !git_dir.exists() {
+    if !git::is_repository(repo_path) {
         anyhow::bail!("Not a git repository: {}", repo_path.display());
     }
 
-    if let Some(ref name) = profile.user_name {
-        git::set_config(repo_path, "user.name", Some(name))?;
+    apply_profile_to(&git::ConfigTarget::Local(repo_path.to_path_buf()), profile)
+}
+
+/// Writes profile settings to an explicit config target, e.g. the global config
+pub fn apply_profile_to(target: &git::ConfigTarget, profile: &Profile) -> Result<()> {
+    for (key, value) in profile_settings(profile)? {
+        git::set_config_scoped(target, key, Some(&value))?;
     }
 
-    if let Some(ref email) = profile.user_email {
-        git::set_config(repo_path, "user.email", Some(email))?;
+    Ok(())
+}
+
+/// A config value that applying a profile would change
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub key: &'static str,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+/// Returns the repository's local config values that [`apply_profile`] would change
+pub fn pending_changes(repo_path: &Path, profile: &Profile) -> Result<Vec<ConfigChange>> {
+    pending_changes_in(&git::ConfigTarget::Local(repo_path.to_path_buf()), profile)
+}
+
+/// Returns the values in `target` that [`apply_profile_to`] would change
+pub fn pending_changes_in(
+    target: &git::ConfigTarget,
+    profile: &Profile,
+) -> Result<Vec<ConfigChange>> {
+    Ok(settings_changes(target, profile_settings(profile)?))
+}
+
+/// Returns the `settings` whose values differ from those in `target`
+fn settings_changes(
+    target: &git::ConfigTarget,
+    settings: Vec<(&'static str, String)>,
+) -> Vec<ConfigChange> {
+    settings
+        .into_iter()
+        .filter_map(|(key, new)| {
+            let old = git::get_config_scoped(target, key);
+            (old.as_deref() != Some(new.as_str())).then_some(ConfigChange { key, old, new })
+        })
+        .collect()
+}
+
+/// Returns the config values written when applying a profile, honoring the yarm configuration
+fn profile_settings(profile: &Profile) -> Result<Vec<(&'static str, String)>> {
+    let force_unsigned = crate::config::load()?.profiles.disable_signing_without_key;
+    Ok(applied_settings(profile, force_unsigned))
+}
+
+/// Returns the config values written when applying a profile.
+/// With `force_unsigned`, a profile without a signing key disables commit signing
+/// instead of inheriting `commit.gpgsign` from the global config.
+fn applied_settings(profile: &Profile, force_unsigned: bool) -> Vec<(&'static str, String)> {
+    PROFILE_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = profile.config_value(key).or_else(|| {
+                (force_unsigned && *key == "commit.gpgsign" && profile.signing_key.is_none())
+                    .then(|| "false".to_string())
+            });
+            value.map(|v| (*key, v))
+        })
+        .collect()
+}
+
+/// Removes all profile keys from a repository's local config, the inverse of [`apply_profile`].
+/// Returns the keys that were set before and are no longer set afterwards.
+pub fn revert_profile(repo_path: &Path) -> Result<Vec<&'static str>> {
+    if !git::is_repository(repo_path) {
+        anyhow::bail!("Not a git repository: {}", repo_path.display());
     }
 
-    if let Some(ref key) = profile.signing_key {
-        git::set_config(repo_path, "user.signingkey", Some(key))?;
+    let configured: Vec<&'static str> = PROFILE_KEYS
+        .into_iter()
+        .filter(|key| git::get_config(repo_path, key).is_some())
+        .collect();
+
+    for key in &configured {
+        git::set_config(repo_path, key, None)?;
     }
 
-    if let Some(ref format) = profile.gpg_format {
-        git::set_config(repo_path, "gpg.format", Some(format))?;
+    Ok(configured
+        .into_iter()
+        .filter(|key| git::get_config(repo_path, key).is_none())
+        .collect())
+}
+
+/// Local values of all profile keys in a repository, `None` for unset keys
+pub type LocalValues = Vec<(&'static str, Option<String>)>;
+
+/// Records the repository's local profile key values so they can be put back with
+/// [`restore_local_values`]
+pub fn local_values(repo_path: &Path) -> LocalValues {
+    PROFILE_KEYS
+        .into_iter()
+        .map(|key| (key, git::get_config(repo_path, key)))
+        .collect()
+}
+
+/// Restores values recorded by [`local_values`], unsetting keys that were not set before
+pub fn restore_local_values(repo_path: &Path, values: &LocalValues) -> Result<()> {
+    for (key, value) in values {
+        if git::get_config(repo_path, key) != *value {
+            git::set_config(repo_path, key, value.as_deref())?;
+        }
     }
 
-    if let Some(gpg_sign) = profile.gpg_sign {
-        git::set_config(
-            repo_path,
-            "commit.gpgsign",
-            Some(if gpg_sign { "true" } else { "false" }),
-        )?;
+    Ok(())
+}
+
+/// Reads the identity configured locally in a repository, ignoring inherited config.
+/// Returns `None` if the repository has no local `user.name` or `user.email`.
+pub fn local_profile(repo_path: &Path) -> Option<Profile> {
+    let mut fields = ProfileFields::default();
+    for key in PROFILE_KEYS {
+        if let Some(value) = git::get_config(repo_path, key) {
+            fields.apply(key, value);
+        }
     }
 
-    if let Some(tag_gpg_sign) = profile.tag_gpg_sign {
-        git::set_config(
-            repo_path,
-            "tag.gpgsign",
-            Some(if tag_gpg_sign { "true" } else { "false" }),
-        )?;
+    if !fields.has_user_config() {
+        return None;
     }
 
-    Ok(())
+    Some(fields.into_profile(repo_path.join(".git/config"), &[]))
+}
+
+/// Performs a basic sanity check of an email address (`local@domain`, no whitespace).
+pub fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.is_empty()
+        && !domain.contains('@')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
 }
 
 /// Gets a git config value for the current context
@@ -591,8 +1123,11 @@ fn get_current_git_config(key: &str) -> Option<String> {
         .filter(|s| !s.is_empty())
 }
 
+/// Maximum directory depth searched below `~/.config/git` and recursive custom directories
+const MAX_PROFILE_DEPTH: usize = 4;
+
 /// Finds gitconfig files in common locations and custom directories
-fn find_gitconfig_files(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
+fn find_gitconfig_files(extra_dirs: &[PathBuf], recursive: bool) -> Vec<PathBuf> {
     let mut files = Vec::new();
 
     if let Some(home) = dirs::home_dir() {
@@ -607,34 +1142,56 @@ fn find_gitconfig_files(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
             }
         }
 
-        let config_git = home.join(".config/git");
-        if let Ok(entries) = fs::read_dir(&config_git) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if let Some(name) = path.file_name().and_then(|n| n.to_str())
-                    && name.ends_with(".gitconfig")
-                    && name != "config"
-                {
-                    files.push(path);
-                }
-            }
-        }
+        collect_config_files(
+            &home.join(".config/git"),
+            MAX_PROFILE_DEPTH,
+            &is_config_git_profile,
+            &mut files,
+        );
     }
 
+    let depth = if recursive { MAX_PROFILE_DEPTH } else { 0 };
     for dir in extra_dirs {
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    files.push(path);
-                }
-            }
-        }
+        collect_config_files(dir, depth, &|_| true, &mut files);
     }
 
     files
 }
 
+/// Whether a file below `~/.config/git` is a profile (`*.gitconfig`, never git's own `config`)
+fn is_config_git_profile(name: &str) -> bool {
+    name.ends_with(".gitconfig") && name != "config"
+}
+
+/// Collects files accepted by `accept`, descending at most `depth` directory levels.
+/// Symlinked directories are not followed.
+fn collect_config_files(
+    dir: &Path,
+    depth: usize,
+    accept: &dyn Fn(&str) -> bool,
+    files: &mut Vec<PathBuf>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            if depth > 0 {
+                collect_config_files(&path, depth - 1, accept, files);
+            }
+        } else if path.is_file()
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(accept)
+        {
+            files.push(path);
+        }
+    }
+}
+
 /// Parses a single gitconfig file using git
 /// Accumulates git config key-value pairs into profile fields.
 #[derive(Default)]
@@ -645,6 +1202,7 @@ struct ProfileFields {
     gpg_sign: Option<bool>,
     gpg_format: Option<String>,
     tag_gpg_sign: Option<bool>,
+    ssh_command: Option<String>,
 }
 
 impl ProfileFields {
@@ -656,6 +1214,7 @@ impl ProfileFields {
             "commit.gpgsign" => self.gpg_sign = parse_bool(&value),
             "gpg.format" => self.gpg_format = Some(value),
             "tag.gpgsign" => self.tag_gpg_sign = parse_bool(&value),
+            "core.sshcommand" => self.ssh_command = Some(value),
             _ => {}
         }
     }
@@ -664,8 +1223,8 @@ impl ProfileFields {
         self.user_name.is_some() || self.user_email.is_some()
     }
 
-    fn into_profile(self, source: PathBuf) -> Profile {
-        let name = derive_profile_name(&source);
+    fn into_profile(self, source: PathBuf, name_strip: &[String]) -> Profile {
+        let name = derive_profile_name(&source, name_strip);
         Profile {
             name,
             source,
@@ -675,12 +1234,22 @@ impl ProfileFields {
             gpg_sign: self.gpg_sign,
             gpg_format: self.gpg_format,
             tag_gpg_sign: self.tag_gpg_sign,
+            ssh_command: self.ssh_command,
             is_default: false,
+            is_current: false,
+            duplicate_sources: Vec::new(),
         }
     }
 }
 
-fn parse_gitconfig_file(path: &Path) -> Option<Profile> {
+/// Re-reads a single profile file, e.g. after it was edited outside yarm.
+/// Returns `None` if the file no longer defines `user.name` or `user.email`.
+pub fn reload_profile(path: &Path) -> Result<Option<Profile>> {
+    let config = crate::config::load()?;
+    Ok(parse_gitconfig_file(path, &config.profiles.name_strip))
+}
+
+fn parse_gitconfig_file(path: &Path, name_strip: &[String]) -> Option<Profile> {
     let output = Command::new("git")
         .args(["config", "--file", &path.to_string_lossy(), "--list"])
         .output()
@@ -703,11 +1272,11 @@ fn parse_gitconfig_file(path: &Path) -> Option<Profile> {
         return None;
     }
 
-    Some(fields.into_profile(path.to_path_buf()))
+    Some(fields.into_profile(path.to_path_buf(), name_strip))
 }
 
 /// Parses the output of `git config --list --show-origin`
-fn parse_git_config_output(output: &str) -> Vec<Profile> {
+fn parse_git_config_output(output: &str, name_strip: &[String]) -> Vec<Profile> {
     let mut entries_by_file: HashMap<PathBuf, Vec<(String, String)>> = HashMap::new();
 
     for line in output.lines() {
@@ -731,30 +1300,56 @@ fn parse_git_config_output(output: &str) -> Vec<Profile> {
             continue;
         }
 
-        profiles.push(fields.into_profile(source));
+        profiles.push(fields.into_profile(source, name_strip));
     }
 
-    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles.sort_by(compare_profiles);
 
     profiles
 }
 
+/// Orders profiles by name, then by source path so profiles sharing a derived name
+/// keep the same order across runs
+fn compare_profiles(a: &Profile, b: &Profile) -> std::cmp::Ordering {
+    a.name.cmp(&b.name).then_with(|| a.source.cmp(&b.source))
+}
+
+/// Origin kinds `git config --show-origin` reports for values that do not come from a file
+const NON_FILE_ORIGINS: [&str; 4] = ["command line", "blob", "submodule-blob", "standard input"];
+
+/// Returns the origin kind (e.g. `command line`) if `source` is a non-file origin
+/// as reported by `git config --show-origin` rather than a file path
+pub fn non_file_origin(source: &Path) -> Option<&str> {
+    let (kind, _) = source.to_str()?.split_once(':')?;
+    NON_FILE_ORIGINS.contains(&kind).then_some(kind)
+}
+
 /// Parses a single line from git config --show-origin output.
+/// Values from non-file origins keep the origin itself (e.g. `command line:`) as their source.
 ///
 /// Format: `file:/path/to/file<TAB>key=value`
 fn parse_config_line(line: &str) -> Option<(PathBuf, String, String)> {
     let (origin, rest) = line.split_once('\t')?;
 
-    let path_str = origin.strip_prefix("file:")?;
-    let source = PathBuf::from(path_str);
+    let source = match origin.strip_prefix("file:") {
+        Some(path_str) => PathBuf::from(path_str),
+        None => Some(PathBuf::from(origin)).filter(|o| non_file_origin(o).is_some())?,
+    };
 
     let (key, value) = rest.split_once('=')?;
 
     Some((source, key.to_string(), value.to_string()))
 }
 
-/// Derives a profile name from a gitconfig file path
-fn derive_profile_name(path: &Path) -> String {
+/// Derives a profile name from a gitconfig file path.
+/// After the built-in patterns, each `name_strip` entry is removed as a prefix and a suffix
+/// (from `profiles.name_strip`), unless that would leave the name empty.
+/// Names are derived once at discovery, so lookups by name see the same result.
+fn derive_profile_name(path: &Path, name_strip: &[String]) -> String {
+    if let Some(kind) = non_file_origin(path) {
+        return kind.replace(' ', "-");
+    }
+
     let file_name = path
         .file_name()
         .and_then(|n| n.to_str())
@@ -777,9 +1372,15 @@ fn derive_profile_name(path: &Path) -> String {
         }
         name => {
             // Strip common extensions to get profile name
-            name.trim_end_matches(".gitconfig")
-                .trim_end_matches(".git")
-                .to_string()
+            let mut name = name.trim_end_matches(".gitconfig").trim_end_matches(".git");
+            for pattern in name_strip.iter().filter(|p| !p.is_empty()) {
+                let stripped = name.strip_prefix(pattern.as_str()).unwrap_or(name);
+                let stripped = stripped.strip_suffix(pattern.as_str()).unwrap_or(stripped);
+                if !stripped.is_empty() {
+                    name = stripped;
+                }
+            }
+            name.to_string()
         }
     }
 }
@@ -831,7 +1432,7 @@ mod tests {
     #[test]
     fn test_derive_profile_name_gitconfig() {
         assert_eq!(
-            derive_profile_name(Path::new("/Users/test/.gitconfig")),
+            derive_profile_name(Path::new("/Users/test/.gitconfig"), &[]),
             "global"
         );
     }
@@ -839,7 +1440,7 @@ mod tests {
     #[test]
     fn test_derive_profile_name_named() {
         assert_eq!(
-            derive_profile_name(Path::new("/Users/test/.config/git/work.gitconfig")),
+            derive_profile_name(Path::new("/Users/test/.config/git/work.gitconfig"), &[]),
             "work"
         );
     }
@@ -847,7 +1448,7 @@ mod tests {
     #[test]
     fn test_derive_profile_name_local() {
         assert_eq!(
-            derive_profile_name(Path::new("/project/.git/config")),
+            derive_profile_name(Path::new("/project/.git/config"), &[]),
             "local"
         );
     }
@@ -860,7 +1461,7 @@ file:/Users/test/.config/git/work.gitconfig	user.name=Work User
 file:/Users/test/.config/git/work.gitconfig	user.email=work@company.com
 file:/Users/test/.config/git/work.gitconfig	commit.gpgsign=true";
 
-        let profiles = parse_git_config_output(output);
+        let profiles = parse_git_config_output(output, &[]);
 
         assert_eq!(profiles.len(), 2);
 
@@ -878,12 +1479,68 @@ file:/Users/test/.config/git/work.gitconfig	commit.gpgsign=true";
         assert_eq!(profiles[1].gpg_sign, Some(true));
     }
 
+    #[test]
+    fn test_parse_git_config_output_orders_same_name_by_source() {
+        let output = r"file:/Users/test/z/work.gitconfig	user.email=z@company.com
+file:/Users/test/a/work.gitconfig	user.email=a@company.com
+file:/Users/test/m/work.gitconfig	user.email=m@company.com";
+
+        for _ in 0..10 {
+            let profiles = parse_git_config_output(output, &[]);
+            let sources: Vec<_> = profiles.iter().map(|p| p.source.clone()).collect();
+            assert!(profiles.iter().all(|p| p.name == "work"));
+            assert_eq!(
+                sources,
+                [
+                    PathBuf::from("/Users/test/a/work.gitconfig"),
+                    PathBuf::from("/Users/test/m/work.gitconfig"),
+                    PathBuf::from("/Users/test/z/work.gitconfig"),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_config_line_non_file_origins() {
+        let (source, key, value) =
+            parse_config_line("command line:\tuser.email=ci@example.com").unwrap();
+        assert_eq!(source, PathBuf::from("command line:"));
+        assert_eq!(key, "user.email");
+        assert_eq!(value, "ci@example.com");
+
+        let (source, ..) = parse_config_line("blob:HEAD:.gitconfig\tuser.name=Blob").unwrap();
+        assert_eq!(source, PathBuf::from("blob:HEAD:.gitconfig"));
+
+        assert!(parse_config_line("unknown:thing\tuser.name=X").is_none());
+    }
+
+    #[test]
+    fn test_parse_git_config_output_mixed_origins() {
+        let output = "file:/Users/test/.gitconfig\tuser.name=Default User
+file:/Users/test/.gitconfig\tuser.email=default@example.com
+command line:\tuser.name=CI Bot
+command line:\tuser.email=ci@example.com
+blob:HEAD:.gitconfig\tuser.email=blob@example.com";
+
+        let profiles = parse_git_config_output(output, &[]);
+        let names: Vec<_> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["blob", "command-line", "global"]);
+
+        let command_line = &profiles[1];
+        assert_eq!(command_line.source, PathBuf::from("command line:"));
+        assert_eq!(command_line.user_name.as_deref(), Some("CI Bot"));
+        assert_eq!(command_line.user_email.as_deref(), Some("ci@example.com"));
+        assert_eq!(profile_scope(&command_line.source), ProfileScope::NonFile);
+        assert_eq!(profile_scope(&profiles[0].source), ProfileScope::NonFile);
+        assert_eq!(profile_scope(&profiles[2].source), ProfileScope::Global);
+    }
+
     #[test]
     fn test_parse_git_config_output_skips_files_without_user_config() {
         let output = r"file:/Users/test/.gitconfig	core.editor=vim
 file:/Users/test/.gitconfig	core.pager=less";
 
-        let profiles = parse_git_config_output(output);
+        let profiles = parse_git_config_output(output, &[]);
         assert!(profiles.is_empty());
     }
 
@@ -892,7 +1549,7 @@ file:/Users/test/.gitconfig	core.pager=less";
         let output = r"file:/Users/test/.gitconfig	user.name=First
 file:/Users/test/.gitconfig	user.name=Second";
 
-        let profiles = parse_git_config_output(output);
+        let profiles = parse_git_config_output(output, &[]);
         assert_eq!(profiles.len(), 1);
         assert_eq!(profiles[0].user_name, Some("Second".to_string()));
     }
@@ -914,6 +1571,50 @@ file:/Users/test/.gitconfig	user.name=Second";
         assert_eq!(parse_bool("invalid"), None);
     }
 
+    #[test]
+    fn test_is_valid_email() {
+        assert!(is_valid_email("john@doe.dev"));
+        assert!(is_valid_email("batman@users.noreply.github.com"));
+        assert!(is_valid_email("admin@localhost"));
+
+        assert!(!is_valid_email(""));
+        assert!(!is_valid_email("john"));
+        assert!(!is_valid_email("@doe.dev"));
+        assert!(!is_valid_email("john@"));
+        assert!(!is_valid_email("john@doe@dev"));
+        assert!(!is_valid_email("john doe@doe.dev"));
+        assert!(!is_valid_email("john@.dev"));
+    }
+
+    #[test]
+    fn test_parse_setting_valid() {
+        assert_eq!(
+            parse_setting("user.email=john@doe.dev").unwrap(),
+            ("user.email", Some("john@doe.dev".to_string()))
+        );
+        assert_eq!(
+            parse_setting("User.SigningKey=ABC").unwrap(),
+            ("user.signingkey", Some("ABC".to_string()))
+        );
+        assert_eq!(
+            parse_setting("commit.gpgsign=yes").unwrap(),
+            ("commit.gpgsign", Some("true".to_string()))
+        );
+        assert_eq!(
+            parse_setting("user.signingkey=").unwrap(),
+            ("user.signingkey", None)
+        );
+    }
+
+    #[test]
+    fn test_parse_setting_invalid() {
+        assert!(parse_setting("user.email").is_err());
+        assert!(parse_setting("core.editor=vim").is_err());
+        assert!(parse_setting("user.email=not-an-email").is_err());
+        assert!(parse_setting("gpg.format=pgp").is_err());
+        assert!(parse_setting("tag.gpgsign=maybe").is_err());
+    }
+
     #[test]
     fn test_profile_config_summary() {
         let profile = Profile {
@@ -926,6 +1627,9 @@ file:/Users/test/.gitconfig	user.name=Second";
             gpg_format: None,
             tag_gpg_sign: None,
             is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
         };
 
         assert_eq!(
@@ -946,6 +1650,9 @@ file:/Users/test/.gitconfig	user.name=Second";
             gpg_format: Some("ssh".to_string()),
             tag_gpg_sign: Some(true),
             is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
         };
 
         assert_eq!(
@@ -985,6 +1692,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: "hasconfig:remote.*.url:*github.com/mycompany/*".to_string(),
             target_path: PathBuf::from("/test"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
 
         let matching_context = ProfileContext {
@@ -1057,6 +1765,16 @@ file:/Users/test/.gitconfig	user.name=Second";
         ]
     }
 
+    #[test]
+    fn test_profile_named_in_file() {
+        let profiles = sample_profiles();
+        assert_eq!(
+            profile_named_in_file(&profiles, "oss").as_deref(),
+            Some(".gitconfig-oss")
+        );
+        assert_eq!(profile_named_in_file(&profiles, "nonexistent"), None);
+    }
+
     #[test]
     fn test_find_profile_by_name_exact() {
         let profiles = sample_profiles();
@@ -1095,6 +1813,30 @@ file:/Users/test/.gitconfig	user.name=Second";
         assert!(err.contains("not found"));
     }
 
+    #[test]
+    fn test_find_profile_by_index() {
+        let profiles = sample_profiles();
+        assert_eq!(
+            find_profile_by_index(&profiles, 1).unwrap().name,
+            profiles[0].name
+        );
+        assert_eq!(
+            find_profile_by_index(&profiles, 2).unwrap().name,
+            profiles[1].name
+        );
+    }
+
+    #[test]
+    fn test_find_profile_by_index_out_of_range() {
+        let profiles = sample_profiles();
+        assert!(find_profile_by_index(&profiles, 0).is_err());
+        let err = find_profile_by_index(&profiles, profiles.len() + 1)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("out of range"));
+        assert!(err.contains("1. work"));
+    }
+
     // --- promote_default ---
 
     #[test]
@@ -1104,10 +1846,11 @@ file:/Users/test/.gitconfig	user.name=Second";
             test_profile("beta", Some("B"), Some("b@ex.com")),
             test_profile("gamma", Some("C"), Some("c@ex.com")),
         ];
-        let result = promote_default(profiles, Some("gamma"));
+        let (result, promotion) = promote_default(profiles, Some("gamma"));
         assert_eq!(result[0].name, "gamma");
         assert_eq!(result[1].name, "alpha");
         assert_eq!(result[2].name, "beta");
+        assert_eq!(promotion, Some(Promotion::Default));
     }
 
     #[test]
@@ -1116,9 +1859,10 @@ file:/Users/test/.gitconfig	user.name=Second";
             test_profile("alpha", Some("A"), Some("a@ex.com")),
             test_profile("beta", Some("B"), Some("b@ex.com")),
         ];
-        let result = promote_default(profiles, None);
+        let (result, promotion) = promote_default(profiles, None);
         assert_eq!(result[0].name, "alpha");
         assert_eq!(result[1].name, "beta");
+        assert_eq!(promotion, None);
     }
 
     #[test]
@@ -1127,9 +1871,10 @@ file:/Users/test/.gitconfig	user.name=Second";
             test_profile("alpha", Some("A"), Some("a@ex.com")),
             test_profile("beta", Some("B"), Some("b@ex.com")),
         ];
-        let result = promote_default(profiles, Some("nonexistent"));
+        let (result, promotion) = promote_default(profiles, Some("nonexistent"));
         assert_eq!(result[0].name, "alpha");
         assert_eq!(result[1].name, "beta");
+        assert_eq!(promotion, None);
     }
 
     #[test]
@@ -1138,9 +1883,21 @@ file:/Users/test/.gitconfig	user.name=Second";
             test_profile("alpha", Some("A"), Some("a@ex.com")),
             test_profile("beta", Some("B"), Some("b@ex.com")),
         ];
-        let result = promote_default(profiles, Some("alpha"));
+        let (result, promotion) = promote_default(profiles, Some("alpha"));
         assert_eq!(result[0].name, "alpha");
         assert_eq!(result[1].name, "beta");
+        assert_eq!(promotion, Some(Promotion::Default));
+    }
+
+    #[test]
+    fn test_promote_default_keeps_current_identity_first() {
+        let mut current = test_profile("alpha", Some("A"), Some("a@ex.com"));
+        current.is_current = true;
+        let profiles = vec![current, test_profile("beta", Some("B"), Some("b@ex.com"))];
+        let (result, promotion) = promote_default(profiles, None);
+        assert_eq!(result[0].name, "alpha");
+        assert_eq!(promotion, Some(Promotion::Current));
+        assert_eq!(promotion.unwrap().to_string(), "current identity");
     }
 
     // --- derive_profile_name edge cases ---
@@ -1148,7 +1905,7 @@ file:/Users/test/.gitconfig	user.name=Second";
     #[test]
     fn test_derive_profile_name_gitconfig_dash_prefix() {
         assert_eq!(
-            derive_profile_name(Path::new("/home/user/.gitconfig-work")),
+            derive_profile_name(Path::new("/home/user/.gitconfig-work"), &[]),
             ".gitconfig-work"
         );
     }
@@ -1156,7 +1913,7 @@ file:/Users/test/.gitconfig	user.name=Second";
     #[test]
     fn test_derive_profile_name_gitconfig_dot_prefix() {
         assert_eq!(
-            derive_profile_name(Path::new("/home/user/.gitconfig.personal")),
+            derive_profile_name(Path::new("/home/user/.gitconfig.personal"), &[]),
             ".gitconfig.personal"
         );
     }
@@ -1164,15 +1921,46 @@ file:/Users/test/.gitconfig	user.name=Second";
     #[test]
     fn test_derive_profile_name_xdg_config() {
         assert_eq!(
-            derive_profile_name(Path::new("/home/user/.config/git/config")),
+            derive_profile_name(Path::new("/home/user/.config/git/config"), &[]),
             "global"
         );
     }
 
+    #[test]
+    fn test_derive_profile_name_custom_strip() {
+        let strip = vec![".conf".to_string(), "git-identity-".to_string()];
+        assert_eq!(
+            derive_profile_name(Path::new("/home/user/git-identity-work.conf"), &strip),
+            "work"
+        );
+        // Built-in patterns still apply before custom ones
+        assert_eq!(
+            derive_profile_name(Path::new("/home/user/git-identity-oss.gitconfig"), &strip),
+            "oss"
+        );
+        assert_eq!(
+            derive_profile_name(Path::new("/project/.git/config"), &strip),
+            "local"
+        );
+    }
+
+    #[test]
+    fn test_derive_profile_name_strip_keeps_nonempty() {
+        let strip = vec![".conf".to_string(), String::new()];
+        assert_eq!(
+            derive_profile_name(Path::new("/home/user/.conf"), &strip),
+            ".conf"
+        );
+        assert_eq!(
+            derive_profile_name(Path::new("/home/user/team.conf"), &strip),
+            "team"
+        );
+    }
+
     #[test]
     fn test_derive_profile_name_bare_file() {
         assert_eq!(
-            derive_profile_name(Path::new("/custom/profiles/myprofile")),
+            derive_profile_name(Path::new("/custom/profiles/myprofile"), &[]),
             "myprofile"
         );
     }
@@ -1188,6 +1976,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: format!("gitdir:{}/", tmp.join("work").display()),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: Some(sub),
@@ -1205,6 +1994,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: format!("gitdir:{}/", tmp.join("work").display()),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: Some(sub),
@@ -1222,6 +2012,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: format!("gitdir/i:{}/", tmp.join("work").display()),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: Some(sub),
@@ -1235,6 +2026,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: "gitdir:/some/path/".to_string(),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: None,
@@ -1248,6 +2040,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: "hasconfig:remote.*.url:*github.com*".to_string(),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: None,
@@ -1261,6 +2054,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: "onbranch:main".to_string(),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: Some(PathBuf::from("/some/path")),
@@ -1281,6 +2075,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rule = IncludeIfRule {
             condition: format!("gitdir:{}/**", work_canonical.display()),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         };
         let ctx = ProfileContext {
             target_path: Some(sub),
@@ -1289,6 +2084,80 @@ file:/Users/test/.gitconfig	user.name=Second";
         assert!(rule.matches(&ctx));
     }
 
+    fn gitdir_rule(pattern: &str) -> IncludeIfRule {
+        IncludeIfRule {
+            condition: format!("gitdir:{pattern}"),
+            target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
+        }
+    }
+
+    fn repo_context(path: &str) -> ProfileContext {
+        ProfileContext {
+            target_path: Some(PathBuf::from(path)),
+            clone_url: None,
+        }
+    }
+
+    #[test]
+    fn test_gitdir_glob_rewrites() {
+        assert_eq!(gitdir_glob("/work/"), "/work/**");
+        assert_eq!(gitdir_glob("/work/**"), "/work/**");
+        assert_eq!(gitdir_glob("/work/repo/.git"), "/work/repo/.git");
+        assert_eq!(gitdir_glob("work/"), "**/work/**");
+    }
+
+    #[test]
+    fn test_matches_gitdir_git_semantics() {
+        // Examples from git-config(1), matched against the repository's .git directory
+        let repo = repo_context("/srv/work/project");
+
+        // A trailing slash matches everything below the directory
+        assert!(gitdir_rule("/srv/work/").matches(&repo));
+        // Without it, the pattern must match the .git directory itself
+        assert!(!gitdir_rule("/srv/work").matches(&repo));
+        assert!(!gitdir_rule("/srv/work/project").matches(&repo));
+        assert!(gitdir_rule("/srv/work/project/.git").matches(&repo));
+        // Relative patterns match at any depth
+        assert!(gitdir_rule("work/").matches(&repo));
+        assert!(gitdir_rule("project/.git").matches(&repo));
+        assert!(!gitdir_rule("other/").matches(&repo));
+        // A single * does not cross directory boundaries, ** does
+        assert!(gitdir_rule("/srv/*/project/.git").matches(&repo));
+        assert!(!gitdir_rule("/srv/*/.git").matches(&repo));
+        assert!(gitdir_rule("/srv/**/.git").matches(&repo));
+    }
+
+    #[test]
+    fn test_matches_gitdir_tilde() {
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        let repo = ProfileContext {
+            target_path: Some(home.join("yarm-test-nonexistent/work/repo")),
+            clone_url: None,
+        };
+        assert!(gitdir_rule("~/yarm-test-nonexistent/work/").matches(&repo));
+        assert!(!gitdir_rule("~/yarm-test-nonexistent/other/").matches(&repo));
+    }
+
+    #[test]
+    fn test_resolve_relative_gitdir() {
+        let config = Path::new("/home/user/.config/git/config");
+        assert_eq!(
+            resolve_relative_gitdir("gitdir:./work/", config),
+            "gitdir:/home/user/.config/git/work/"
+        );
+        assert_eq!(
+            resolve_relative_gitdir("gitdir/i:./Work/", config),
+            "gitdir/i:/home/user/.config/git/Work/"
+        );
+        assert_eq!(
+            resolve_relative_gitdir("gitdir:~/work/", config),
+            "gitdir:~/work/"
+        );
+    }
+
     // --- parse_include_if_from_file ---
 
     #[test]
@@ -1379,6 +2248,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rules = vec![IncludeIfRule {
             condition: "hasconfig:remote.*.url:*company.com*".to_string(),
             target_path: PathBuf::from("/home/user/.gitconfig-work"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         }];
 
         let context = ProfileContext {
@@ -1386,9 +2256,24 @@ file:/Users/test/.gitconfig	user.name=Second";
             clone_url: Some("https://company.com/repo.git".to_string()),
         };
 
-        let result = reorder_profiles_by_rules(profiles, &context, &rules, None);
+        let (result, promotion) = reorder_profiles_by_rules(profiles, &context, &rules, None);
         assert_eq!(result[0].name, "work");
         assert_eq!(result[1].name, "personal");
+        let promotion = promotion.unwrap();
+        assert_eq!(
+            promotion,
+            Promotion::IncludeIf {
+                condition: "hasconfig:remote.*.url:*company.com*".to_string(),
+                origin: PathBuf::from("/home/user/.gitconfig"),
+            }
+        );
+        assert_eq!(
+            promotion.to_string(),
+            format!(
+                "matched includeIf hasconfig:remote.*.url:*company.com* from {}",
+                format_home_path(Path::new("/home/user/.gitconfig"))
+            )
+        );
     }
 
     #[test]
@@ -1401,6 +2286,7 @@ file:/Users/test/.gitconfig	user.name=Second";
         let rules = vec![IncludeIfRule {
             condition: "hasconfig:remote.*.url:*nomatch*".to_string(),
             target_path: PathBuf::from("/dummy"),
+            origin: PathBuf::from("/home/user/.gitconfig"),
         }];
 
         let context = ProfileContext {
@@ -1408,9 +2294,11 @@ file:/Users/test/.gitconfig	user.name=Second";
             clone_url: Some("https://github.com/user/repo.git".to_string()),
         };
 
-        let result = reorder_profiles_by_rules(profiles, &context, &rules, Some("beta"));
+        let (result, promotion) =
+            reorder_profiles_by_rules(profiles, &context, &rules, Some("beta"));
         assert_eq!(result[0].name, "beta");
         assert_eq!(result[1].name, "alpha");
+        assert_eq!(promotion, Some(Promotion::Default));
     }
 
     #[test]
@@ -1425,9 +2313,108 @@ file:/Users/test/.gitconfig	user.name=Second";
             clone_url: None,
         };
 
-        let result = reorder_profiles_by_rules(profiles, &context, &[], Some("beta"));
+        let (result, promotion) = reorder_profiles_by_rules(profiles, &context, &[], Some("beta"));
         assert_eq!(result[0].name, "beta");
         assert_eq!(result[1].name, "alpha");
+        assert_eq!(promotion, Some(Promotion::Default));
+    }
+
+    // --- resolve_from ---
+
+    #[test]
+    fn test_resolve_named_without_terminal() {
+        let ctx = url_context("https://company.com/repo.git");
+
+        let named = resolve_from(
+            sample_profiles(),
+            ProfileSelection::Named("work"),
+            &ctx,
+            None,
+        );
+        assert_eq!(named.unwrap().unwrap().name, "work");
+        let indexed = resolve_from(sample_profiles(), ProfileSelection::Index(1), &ctx, None);
+        assert!(indexed.unwrap().is_some());
+
+        // Under a non-TTY harness, interactive selection fails instead of prompting
+        if !is_interactive() {
+            let err = resolve_from(sample_profiles(), ProfileSelection::Interactive, &ctx, None)
+                .unwrap_err();
+            assert!(err.to_string().contains("--profile"));
+        }
+    }
+
+    // --- auto_select_profile ---
+
+    fn url_rule(pattern: &str, target: &str) -> IncludeIfRule {
+        IncludeIfRule {
+            condition: format!("hasconfig:remote.*.url:{pattern}"),
+            target_path: PathBuf::from(target),
+            origin: PathBuf::from("/home/user/.gitconfig"),
+        }
+    }
+
+    fn url_context(url: &str) -> ProfileContext {
+        ProfileContext {
+            target_path: None,
+            clone_url: Some(url.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_auto_select_single_rule_match() {
+        let profiles = sample_profiles();
+        let rules = vec![url_rule("*company.com*", "/home/user/.gitconfig-work")];
+        let ctx = url_context("https://company.com/repo.git");
+
+        let selected = auto_select_profile(&profiles, &ctx, &rules, None).unwrap();
+        assert_eq!(selected.name, "work");
+    }
+
+    #[test]
+    fn test_auto_select_ambiguous_rules_error() {
+        let profiles = sample_profiles();
+        let rules = vec![
+            url_rule("*company.com*", "/home/user/.gitconfig-work"),
+            url_rule("*.com/*", "/home/user/.gitconfig-oss"),
+        ];
+        let ctx = url_context("https://company.com/repo.git");
+
+        let err = auto_select_profile(&profiles, &ctx, &rules, Some("work"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Multiple includeIf rules match"));
+        assert!(err.contains("*company.com*"));
+        assert!(err.contains("*.com/*"));
+    }
+
+    #[test]
+    fn test_auto_select_rules_to_same_profile_not_ambiguous() {
+        let profiles = sample_profiles();
+        let rules = vec![
+            url_rule("*company.com*", "/home/user/.gitconfig-work"),
+            url_rule("https://*", "/home/user/.gitconfig-work"),
+        ];
+        let ctx = url_context("https://company.com/repo.git");
+
+        let selected = auto_select_profile(&profiles, &ctx, &rules, None).unwrap();
+        assert_eq!(selected.name, "work");
+    }
+
+    #[test]
+    fn test_auto_select_falls_back_to_default() {
+        let profiles = sample_profiles();
+        let ctx = url_context("https://github.com/user/repo.git");
+
+        let selected = auto_select_profile(&profiles, &ctx, &[], Some("global")).unwrap();
+        assert_eq!(selected.name, "global");
+    }
+
+    #[test]
+    fn test_auto_select_no_match_no_default_error() {
+        let profiles = sample_profiles();
+        let ctx = url_context("https://github.com/user/repo.git");
+
+        assert!(auto_select_profile(&profiles, &ctx, &[], None).is_err());
     }
 
     // --- test helpers ---
@@ -1443,6 +2430,9 @@ file:/Users/test/.gitconfig	user.name=Second";
             gpg_format: None,
             tag_gpg_sign: None,
             is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
         }
     }
 
@@ -1462,9 +2452,302 @@ file:/Users/test/.gitconfig	user.name=Second";
             gpg_format: None,
             tag_gpg_sign: None,
             is_default: false,
+            is_current: false,
+            ssh_command: None,
+            duplicate_sources: Vec::new(),
         }
     }
 
+    #[test]
+    fn test_applied_settings_force_unsigned() {
+        let profile = test_profile("oss", Some("Jane"), Some("jane@example.com"));
+        assert_eq!(
+            applied_settings(&profile, false),
+            vec![
+                ("user.name", "Jane".to_string()),
+                ("user.email", "jane@example.com".to_string())
+            ]
+        );
+        assert!(
+            applied_settings(&profile, true).contains(&("commit.gpgsign", "false".to_string()))
+        );
+
+        // A signing key or an explicit gpgsign value is left alone
+        let mut signed = profile.clone();
+        signed.signing_key = Some("ABC123".to_string());
+        assert!(
+            !applied_settings(&signed, true)
+                .iter()
+                .any(|(k, _)| *k == "commit.gpgsign")
+        );
+        let mut explicit = profile;
+        explicit.gpg_sign = Some(true);
+        assert!(
+            applied_settings(&explicit, true).contains(&("commit.gpgsign", "true".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_revert_profile_reports_cleared_keys() {
+        let repo = tempdir("revert-profile");
+        let status = Command::new("git")
+            .args(["init", "-q"])
+            .arg(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        git::set_config(&repo, "user.name", Some("Work")).unwrap();
+        git::set_config(&repo, "commit.gpgsign", Some("true")).unwrap();
+
+        let cleared = revert_profile(&repo).unwrap();
+        assert_eq!(cleared, vec!["user.name", "commit.gpgsign"]);
+        assert_eq!(git::get_config(&repo, "user.name"), None);
+
+        assert!(revert_profile(&repo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_selection() {
+        let profiles = vec![test_profile("work", Some("Work"), None)];
+
+        assert!(check_selection(&profiles, ProfileSelection::Named("work")).is_ok());
+        assert!(check_selection(&profiles, ProfileSelection::Index(1)).is_ok());
+        assert!(check_selection(&profiles, ProfileSelection::Interactive).is_ok());
+
+        let err = check_selection(&profiles, ProfileSelection::Named("wrok")).unwrap_err();
+        assert!(err.to_string().contains("Available profiles: work"));
+        assert!(check_selection(&profiles, ProfileSelection::Index(2)).is_err());
+    }
+
+    #[test]
+    fn test_pending_changes() {
+        let repo = tempdir("pending-changes");
+        let status = Command::new("git")
+            .args(["init", "-q"])
+            .arg(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        git::set_config(&repo, "user.name", Some("Old")).unwrap();
+        git::set_config(&repo, "user.email", Some("same@example.com")).unwrap();
+
+        // Independent of the host's disable_signing_without_key setting
+        let target = git::ConfigTarget::Local(repo.clone());
+        let profile = test_profile("work", Some("New"), Some("same@example.com"));
+        let changes = settings_changes(&target, applied_settings(&profile, false));
+        assert_eq!(
+            changes,
+            vec![ConfigChange {
+                key: "user.name",
+                old: Some("Old".to_string()),
+                new: "New".to_string(),
+            }]
+        );
+
+        apply_profile(&repo, &profile).unwrap();
+        assert!(settings_changes(&target, applied_settings(&profile, false)).is_empty());
+    }
+
+    #[test]
+    fn test_profile_scope() {
+        let scope = |p: &str| profile_scope(Path::new(p));
+        assert_eq!(scope("/etc/gitconfig"), ProfileScope::System);
+        assert_eq!(scope("/usr/local/etc/gitconfig"), ProfileScope::System);
+        assert_eq!(scope("/home/user/.gitconfig"), ProfileScope::Global);
+        assert_eq!(scope("/home/user/project/.git/config"), ProfileScope::Local);
+        // `git config --show-origin` reports the repository config relative to it
+        assert_eq!(scope(".git/config"), ProfileScope::Local);
+        assert_eq!(scope("/home/user/.gitconfig-work"), ProfileScope::Custom);
+        assert_eq!(
+            scope("/home/user/.config/git/work.gitconfig"),
+            ProfileScope::Custom
+        );
+        // Only whole components count
+        assert_eq!(scope("/home/user/etcetera.gitconfig"), ProfileScope::Custom);
+        assert_eq!(scope("/home/user/my.gitconfig"), ProfileScope::Custom);
+        assert_eq!(scope("/home/user/not.git/config"), ProfileScope::Custom);
+        assert_eq!(scope("/home/user/.gitconfig/config"), ProfileScope::Custom);
+        // User files in a directory named etc are not system config
+        assert_eq!(
+            scope("/home/user/dotfiles/etc/work.gitconfig"),
+            ProfileScope::Custom
+        );
+        assert_eq!(scope("etc/work.gitconfig"), ProfileScope::Custom);
+        assert_eq!(scope("/etc/git/work.gitconfig"), ProfileScope::System);
+        assert_eq!(scope("/opt/homebrew/etc/gitconfig"), ProfileScope::System);
+    }
+
+    #[test]
+    fn test_profile_scope_from_components() {
+        let path = |parts: &[&str]| parts.iter().collect::<PathBuf>();
+        assert_eq!(
+            profile_scope(&path(&["usr", "etc", "gitconfig"])),
+            ProfileScope::System
+        );
+        assert_eq!(
+            profile_scope(&path(&["home", "user", ".gitconfig"])),
+            ProfileScope::Global
+        );
+        assert_eq!(
+            profile_scope(&path(&["project", ".git", "config"])),
+            ProfileScope::Local
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_profile_scope_windows_separators() {
+        let scope = |p: &str| profile_scope(Path::new(p));
+        assert_eq!(
+            scope(r"C:\Program Files\Git\etc\gitconfig"),
+            ProfileScope::System
+        );
+        assert_eq!(scope(r"C:\Users\u\.gitconfig"), ProfileScope::Global);
+        assert_eq!(
+            scope(r"C:\Users\u\project\.git\config"),
+            ProfileScope::Local
+        );
+        assert_eq!(scope(r"C:/Users/u/.gitconfig-work"), ProfileScope::Custom);
+        assert_eq!(
+            derive_profile_name(Path::new(r"C:\Users\u\.config\git\work.gitconfig"), &[]),
+            "work"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_profiles() {
+        let global = test_profile_with_source(
+            "default",
+            "/home/u/.gitconfig",
+            Some("Me"),
+            Some("me@ex.com"),
+        );
+        let included = test_profile_with_source(
+            "work",
+            "/home/u/.gitconfig-work",
+            Some("Me"),
+            Some("me@ex.com"),
+        );
+        let mut signed = test_profile_with_source(
+            "signed",
+            "/home/u/.config/git/signed.gitconfig",
+            Some("Me"),
+            Some("me@ex.com"),
+        );
+        signed.signing_key = Some("ABC123".to_string());
+        let other_name = test_profile_with_source(
+            "alias",
+            "/home/u/.gitconfig-alias",
+            Some("M."),
+            Some("me@ex.com"),
+        );
+
+        let mut current = global;
+        current.is_current = true;
+        let result = dedupe_profiles(vec![current, signed, included, other_name]);
+
+        let names: Vec<_> = result.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["work", "signed", "alias"]);
+        assert_eq!(
+            result[0].duplicate_sources,
+            [PathBuf::from("/home/u/.gitconfig")]
+        );
+        assert!(result[0].is_current);
+        assert!(result[1].duplicate_sources.is_empty());
+    }
+
+    #[test]
+    fn test_dedupe_profiles_keeps_default() {
+        let mut global = test_profile_with_source(
+            "default",
+            "/home/u/.gitconfig",
+            Some("Me"),
+            Some("me@ex.com"),
+        );
+        global.is_default = true;
+        let included = test_profile_with_source(
+            "work",
+            "/home/u/.gitconfig-work",
+            Some("Me"),
+            Some("me@ex.com"),
+        );
+
+        let result = dedupe_profiles(vec![global, included]);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "default");
+        assert_eq!(
+            result[0].duplicate_sources,
+            [PathBuf::from("/home/u/.gitconfig-work")]
+        );
+    }
+
+    #[test]
+    fn test_restore_local_values() {
+        let repo = tempdir("restore-local-values");
+        let status = Command::new("git")
+            .args(["init", "-q"])
+            .arg(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        git::set_config(&repo, "user.name", Some("Old")).unwrap();
+
+        let recorded = local_values(&repo);
+        git::set_config(&repo, "user.name", Some("New")).unwrap();
+        git::set_config(&repo, "user.email", Some("new@example.com")).unwrap();
+
+        restore_local_values(&repo, &recorded).unwrap();
+        assert_eq!(git::get_config(&repo, "user.name").as_deref(), Some("Old"));
+        assert_eq!(git::get_config(&repo, "user.email"), None);
+    }
+
+    #[test]
+    fn test_collect_config_files_nested() {
+        let dir = tempdir("collect-nested");
+        let nested = dir.join("identities/acme");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("work.gitconfig"), "").unwrap();
+        std::fs::write(dir.join("oss.gitconfig"), "").unwrap();
+        std::fs::write(dir.join("config"), "").unwrap();
+        std::fs::write(dir.join("ignore"), "").unwrap();
+
+        let mut files = Vec::new();
+        collect_config_files(&dir, MAX_PROFILE_DEPTH, &is_config_git_profile, &mut files);
+        files.sort();
+        assert_eq!(
+            files,
+            vec![nested.join("work.gitconfig"), dir.join("oss.gitconfig")]
+        );
+
+        let mut top_level = Vec::new();
+        collect_config_files(&dir, 0, &|_| true, &mut top_level);
+        top_level.sort();
+        assert_eq!(
+            top_level,
+            vec![
+                dir.join("config"),
+                dir.join("ignore"),
+                dir.join("oss.gitconfig")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_config_files_depth_limit() {
+        let dir = tempdir("collect-depth");
+        let deep = dir.join("a/b/c");
+        std::fs::create_dir_all(&deep).unwrap();
+        std::fs::write(deep.join("deep.gitconfig"), "").unwrap();
+
+        let mut files = Vec::new();
+        collect_config_files(&dir, 2, &is_config_git_profile, &mut files);
+        assert!(files.is_empty());
+
+        collect_config_files(&dir, 3, &is_config_git_profile, &mut files);
+        assert_eq!(files, vec![deep.join("deep.gitconfig")]);
+    }
+
     fn tempdir(name: &str) -> PathBuf {
         let dir = std::env::temp_dir().join(format!("yarm-test-{name}"));
         let _ = std::fs::remove_dir_all(&dir);
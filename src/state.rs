@@ -1,19 +1,37 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
 /// Bump this when the state format or scan logic changes in a way that
-/// invalidates previously persisted data. Old state files with a
-/// different version are silently discarded.
-const STATE_VERSION: u32 = 3;
+/// invalidates previously persisted data. Older state files are migrated
+/// by [`decode`] where possible and discarded otherwise.
+pub const STATE_VERSION: u32 = 4;
 
+/// On-disk wrapper. The payload is decoded according to `version`,
+/// so older layouts can be read and migrated.
 #[derive(Debug, Serialize, Deserialize)]
 struct StateEnvelope {
     version: u32,
-    state: State,
+    payload: Vec<u8>,
+}
+
+/// Layout of state files up to version 3, with the state embedded in the envelope
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyEnvelope {
+    version: u32,
+    state: LegacyState,
+}
+
+/// State layout up to version 3, before the payload was versioned separately
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyState {
+    repositories: Vec<PathBuf>,
+    last_scan: Option<u64>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -21,6 +39,19 @@ pub struct State {
     pub repositories: Vec<PathBuf>,
     #[serde(default)]
     pub last_scan: Option<u64>,
+    /// Profiles pinned by `.yarm` marker files, by repository path
+    #[serde(default)]
+    pub pinned_profiles: BTreeMap<PathBuf, String>,
+    /// Origin remote URLs read by `find --remote`, by repository path (empty without a remote).
+    /// Not carried over by `scan`, so a rescan refreshes them.
+    #[serde(default)]
+    pub remote_urls: BTreeMap<PathBuf, String>,
+    /// Repositories without any commits at the time of the last scan
+    #[serde(default)]
+    pub empty_repos: BTreeSet<PathBuf>,
+    /// Repositories pinned as favorites with `yarm pin`, in the order they were pinned
+    #[serde(default)]
+    pub pinned_repos: Vec<PathBuf>,
 }
 
 impl State {
@@ -32,6 +63,11 @@ impl State {
             .map(|d| d.as_secs());
     }
 
+    /// Returns the profile pinned for a repository by its `.yarm` marker, if any.
+    pub fn pinned_profile(&self, repo: &Path) -> Option<&str> {
+        self.pinned_profiles.get(repo).map(String::as_str)
+    }
+
     /// Returns the last scan time as a `SystemTime`, if available.
     pub fn last_scan_time(&self) -> Option<SystemTime> {
         self.last_scan
@@ -42,61 +78,134 @@ impl State {
 /// Checks whether the persisted state file exists and has the current version.
 /// Returns `false` if the file is missing, unreadable, or has a different version.
 pub fn version_matches() -> bool {
-    let Some(path) = state_path() else {
-        return false;
-    };
+    state_path().is_some_and(|path| version_matches_at(&path))
+}
 
-    let Ok(bytes) = fs::read(&path) else {
-        return false;
-    };
+/// Checks the state file at `path` against [`STATE_VERSION`], reading only the envelope.
+fn version_matches_at(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|bytes| stored_version(&bytes) == Some(STATE_VERSION))
+}
 
-    matches!(
-        bitcode::deserialize::<StateEnvelope>(&bytes),
-        Ok(envelope) if envelope.version == STATE_VERSION
-    )
+/// Returns the version the state file was written with, or `None` if there is none.
+pub fn file_version() -> Option<u32> {
+    stored_version(&fs::read(state_path()?).ok()?)
+}
+
+/// Returns how long ago the last scan happened, or `None` if there was none.
+pub fn age() -> Option<Duration> {
+    load().ok()?.last_scan_time()?.elapsed().ok()
 }
 
 /// Loads the yarm state from `~/.local/share/yarm/state.bin`.
-/// Returns a default state if the file does not exist, cannot be decoded,
-/// or was written by a different state version.
+/// Older state versions are migrated. Returns a default state if the file
+/// does not exist or cannot be decoded into any known version.
 pub fn load() -> Result<State> {
     let Some(path) = state_path() else {
         return Ok(State::default());
     };
+    load_from(&path)
+}
 
+/// Loads the yarm state from `path`, like [`load`]
+pub fn load_from(path: &Path) -> Result<State> {
     if !path.exists() {
         return Ok(State::default());
     }
 
-    let bytes = fs::read(&path).context("Failed to read yarm state file")?;
-    match bitcode::deserialize::<StateEnvelope>(&bytes) {
-        Ok(envelope) if envelope.version == STATE_VERSION => Ok(envelope.state),
-        _ => {
-            let _ = fs::remove_file(&path);
-            Ok(State::default())
-        }
+    let bytes = fs::read(path).context("Failed to read yarm state file")?;
+    if let Some(state) = decode(&bytes) {
+        Ok(state)
+    } else {
+        let _ = fs::remove_file(path);
+        Ok(State::default())
     }
 }
 
+/// Returns the version a state file was written with, if it is recognizable.
+fn stored_version(bytes: &[u8]) -> Option<u32> {
+    if let Ok(envelope) = bitcode::deserialize::<StateEnvelope>(bytes) {
+        return Some(envelope.version);
+    }
+    bitcode::deserialize::<LegacyEnvelope>(bytes)
+        .ok()
+        .map(|legacy| legacy.version)
+}
+
+/// Decodes a state file of the current or an older version.
+/// Returns `None` for unknown or incompatible versions.
+fn decode(bytes: &[u8]) -> Option<State> {
+    if let Ok(envelope) = bitcode::deserialize::<StateEnvelope>(bytes) {
+        return match envelope.version {
+            STATE_VERSION => bitcode::deserialize(&envelope.payload).ok(),
+            _ => None,
+        };
+    }
+
+    let legacy = bitcode::deserialize::<LegacyEnvelope>(bytes).ok()?;
+    match legacy.version {
+        2 | 3 => Some(migrate_v3(legacy.state)),
+        _ => None,
+    }
+}
+
+/// Maps the state of versions 2 and 3 to the current layout, keeping the tracked
+/// repositories and last scan time; everything recorded since starts out empty.
+fn migrate_v3(legacy: LegacyState) -> State {
+    State {
+        repositories: legacy.repositories,
+        last_scan: legacy.last_scan,
+        ..State::default()
+    }
+}
+
+/// Encodes state with the current version.
+fn encode(state: &State) -> Result<Vec<u8>> {
+    let envelope = StateEnvelope {
+        version: STATE_VERSION,
+        payload: bitcode::serialize(state).context("Failed to encode yarm state")?,
+    };
+    bitcode::serialize(&envelope).context("Failed to encode yarm state")
+}
+
 /// Saves the yarm state to `~/.local/share/yarm/state.bin`.
 pub fn save(state: &State) -> Result<()> {
     let Some(path) = state_path() else {
         anyhow::bail!("Could not determine data directory");
     };
+    save_to(&path, state)
+}
 
+/// Saves the yarm state to `path`, like [`save`]
+pub fn save_to(path: &Path, state: &State) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).context("Failed to create yarm data directory")?;
     }
 
-    let envelope = StateEnvelope {
-        version: STATE_VERSION,
-        state: State {
-            repositories: state.repositories.clone(),
-            last_scan: state.last_scan,
-        },
-    };
-    let bytes = bitcode::serialize(&envelope).context("Failed to encode yarm state")?;
-    fs::write(&path, bytes).context("Failed to write yarm state file")
+    let bytes = encode(state)?;
+    write_atomic(path, &bytes).context("Failed to write yarm state file")
+}
+
+/// Writes `bytes` to a temporary file next to `path` and renames it into place,
+/// so readers never observe a partially written file. Concurrent writers are
+/// serialized by a best-effort advisory lock; without it the last rename wins.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let lock = fs::File::create(path.with_extension("lock"));
+    if let Ok(lock) = &lock {
+        let _ = lock.lock();
+    }
+
+    let temp = path.with_extension(format!(
+        "tmp.{}.{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&temp, bytes).and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
 }
 
 /// Adds a repository path to the state if not already present.
@@ -111,8 +220,19 @@ pub fn register_repo(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Returns the profile pinned for a directory, from state or directly from its `.yarm` marker.
+pub fn pinned_profile(path: &Path) -> Option<String> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Ok(state) = load()
+        && let Some(profile) = state.pinned_profile(&canonical)
+    {
+        return Some(profile.to_string());
+    }
+    crate::config::read_marker(path)?.profile
+}
+
 /// Returns the path to the yarm state file.
-fn state_path() -> Option<PathBuf> {
+pub fn state_path() -> Option<PathBuf> {
     dirs::data_dir().map(|d| d.join("yarm/state.bin"))
 }
 
@@ -120,6 +240,17 @@ fn state_path() -> Option<PathBuf> {
 mod tests {
     use super::*;
 
+    fn legacy_bytes(version: u32, repositories: &[&str]) -> Vec<u8> {
+        let envelope = LegacyEnvelope {
+            version,
+            state: LegacyState {
+                repositories: repositories.iter().map(PathBuf::from).collect(),
+                last_scan: Some(1_700_000_000),
+            },
+        };
+        bitcode::serialize(&envelope).unwrap()
+    }
+
     #[test]
     fn test_state_roundtrip() {
         let state = State {
@@ -130,53 +261,157 @@ mod tests {
             ..State::default()
         };
 
-        let envelope = StateEnvelope {
-            version: STATE_VERSION,
-            state,
-        };
-
-        let bytes = bitcode::serialize(&envelope).unwrap();
-        let decoded: StateEnvelope = bitcode::deserialize(&bytes).unwrap();
+        let bytes = encode(&state).unwrap();
+        assert_eq!(stored_version(&bytes), Some(STATE_VERSION));
 
-        assert_eq!(decoded.version, STATE_VERSION);
-        assert_eq!(decoded.state.repositories.len(), 2);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.repositories.len(), 2);
         assert_eq!(
-            decoded.state.repositories[0],
+            decoded.repositories[0],
             PathBuf::from("/home/user/projects/repo-a")
         );
         assert_eq!(
-            decoded.state.repositories[1],
+            decoded.repositories[1],
             PathBuf::from("/home/user/work/repo-b")
         );
     }
 
     #[test]
     fn test_empty_state_roundtrip() {
-        let envelope = StateEnvelope {
-            version: STATE_VERSION,
-            state: State::default(),
-        };
+        let bytes = encode(&State::default()).unwrap();
+        let decoded = decode(&bytes).unwrap();
+
+        assert!(decoded.repositories.is_empty());
+        assert!(decoded.last_scan.is_none());
+    }
+
+    #[test]
+    fn test_legacy_versions_migrated() {
+        for version in [2, 3] {
+            let bytes = legacy_bytes(version, &["/some/repo", "/other/repo"]);
+            assert_eq!(stored_version(&bytes), Some(version));
+
+            let state = decode(&bytes).unwrap();
+            assert_eq!(
+                state.repositories,
+                vec![PathBuf::from("/some/repo"), PathBuf::from("/other/repo")]
+            );
+            assert_eq!(state.last_scan, Some(1_700_000_000));
+            assert!(state.pinned_profiles.is_empty());
+            assert!(state.remote_urls.is_empty());
+            assert!(state.empty_repos.is_empty());
+            assert!(state.pinned_repos.is_empty());
+        }
+
+        let state = decode(&legacy_bytes(3, &[])).unwrap();
+        assert!(state.repositories.is_empty());
+        let state = decode(&legacy_bytes(3, &["/a"])).unwrap();
+        assert_eq!(state.repositories, vec![PathBuf::from("/a")]);
+    }
 
-        let bytes = bitcode::serialize(&envelope).unwrap();
-        let decoded: StateEnvelope = bitcode::deserialize(&bytes).unwrap();
+    #[test]
+    fn test_pinned_profiles_roundtrip() {
+        let mut state = State::default();
+        state
+            .pinned_profiles
+            .insert(PathBuf::from("/some/repo"), "work".to_string());
 
-        assert_eq!(decoded.version, STATE_VERSION);
-        assert!(decoded.state.repositories.is_empty());
+        let decoded = decode(&encode(&state).unwrap()).unwrap();
+        assert_eq!(
+            decoded.pinned_profile(Path::new("/some/repo")),
+            Some("work")
+        );
+        assert_eq!(decoded.pinned_profile(Path::new("/other/repo")), None);
+    }
+
+    #[test]
+    fn test_unknown_versions_rejected() {
+        assert!(decode(&legacy_bytes(1, &["/some/repo"])).is_none());
+
+        let future = StateEnvelope {
+            version: STATE_VERSION + 1,
+            payload: Vec::new(),
+        };
+        assert!(decode(&bitcode::serialize(&future).unwrap()).is_none());
+        assert!(decode(b"not a state file").is_none());
     }
 
     #[test]
-    fn test_old_version_rejected() {
+    fn test_version_matches_at() {
+        let dir = std::env::temp_dir().join("yarm-test-state-version");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let current = dir.join("current.bin");
+        fs::write(&current, encode(&State::default()).unwrap()).unwrap();
+        assert!(version_matches_at(&current));
+
+        let outdated = dir.join("outdated.bin");
+        fs::write(&outdated, legacy_bytes(3, &["/some/repo"])).unwrap();
+        assert!(!version_matches_at(&outdated));
+
+        let future = dir.join("future.bin");
         let envelope = StateEnvelope {
-            version: STATE_VERSION - 1,
-            state: State {
-                repositories: vec![PathBuf::from("/some/repo")],
-                ..State::default()
-            },
+            version: STATE_VERSION + 1,
+            payload: Vec::new(),
         };
+        fs::write(&future, bitcode::serialize(&envelope).unwrap()).unwrap();
+        assert!(!version_matches_at(&future));
+
+        let corrupt = dir.join("corrupt.bin");
+        fs::write(&corrupt, b"not a state file").unwrap();
+        assert!(!version_matches_at(&corrupt));
+
+        assert!(!version_matches_at(&dir.join("missing.bin")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_atomic_concurrent_writers() {
+        let dir = std::env::temp_dir().join("yarm-test-state-atomic");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.bin");
+
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let path = &path;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        let state = State {
+                            repositories: (0..=i)
+                                .map(|n| PathBuf::from(format!("/repo/{t}/{n}")))
+                                .collect(),
+                            ..State::default()
+                        };
+                        write_atomic(path, &encode(&state).unwrap()).unwrap();
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    if let Ok(bytes) = fs::read(&path) {
+                        assert!(decode(&bytes).is_some());
+                    }
+                }
+            });
+        });
 
-        let bytes = bitcode::serialize(&envelope).unwrap();
-        let decoded: StateEnvelope = bitcode::deserialize(&bytes).unwrap();
+        let state = decode(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(state.repositories.len(), 25);
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains("tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
 
-        assert_ne!(decoded.version, STATE_VERSION);
+        let _ = fs::remove_dir_all(&dir);
     }
 }
@@ -1,11 +1,15 @@
 use std::fmt;
 use std::fmt::Display;
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write};
 use std::path::Path;
-use std::time::{Duration, SystemTime};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use console::{StyledObject, Term, style};
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use inquire::ui::{RenderConfig, Styled};
 use inquire::{Confirm, InquireError, Select, Text};
 
@@ -54,9 +58,99 @@ pub fn print_warning(message: impl Display) {
 
 /// Prints a dimmed hint message (e.g., "hint: Run yarm scan to discover repositories")
 pub fn print_hint(message: impl Display) {
+    if QUIET.load(Ordering::Relaxed) {
+        return;
+    }
     println!("  {} {}", style("hint:").dim(), message);
 }
 
+/// Whether times are printed as absolute timestamps (`--absolute-time`)
+static ABSOLUTE_TIME: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables absolute timestamps for this invocation
+pub fn set_absolute_time(enabled: bool) {
+    ABSOLUTE_TIME.store(enabled, Ordering::Relaxed);
+}
+
+/// Formats a `SystemTime` for display: relative by default (see [`format_elapsed`]),
+/// or as an absolute timestamp (see [`format_timestamp`]) with `--absolute-time`.
+pub fn format_time(time: SystemTime) -> String {
+    if ABSOLUTE_TIME.load(Ordering::Relaxed) {
+        format_timestamp(time)
+    } else {
+        format_elapsed(time)
+    }
+}
+
+/// Formats a `SystemTime` as an ISO 8601 local datetime with its UTC offset
+/// (e.g., "2024-05-01T16:03:09+02:00"). Without a local time zone (non-Unix), UTC is shown.
+pub fn format_timestamp(time: SystemTime) -> String {
+    format_timestamp_at(time, local_offset(time))
+}
+
+/// Formats a `SystemTime` as an ISO 8601 datetime `offset` seconds east of UTC.
+/// Times before the Unix epoch are shown as the epoch.
+fn format_timestamp_at(time: SystemTime, offset: i64) -> String {
+    let utc = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let secs = i64::try_from(utc)
+        .unwrap_or(i64::MAX)
+        .saturating_add(offset)
+        .max(0)
+        .unsigned_abs();
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs_of_day = secs % 86_400;
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset_mins = offset.unsigned_abs() / 60;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}{sign}{:02}:{:02}",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        offset_mins / 60,
+        offset_mins % 60
+    )
+}
+
+/// Seconds east of UTC of the local time zone at `time`, including daylight saving time
+#[cfg(unix)]
+fn local_offset(time: SystemTime) -> i64 {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let Ok(secs) = libc::time_t::try_from(secs) else {
+        return 0;
+    };
+    // SAFETY: `tm` is a plain C struct for which all-zero bytes are valid, and
+    // localtime_r only writes to it (it is the reentrant variant of localtime)
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&raw const secs, &raw mut tm) }.is_null() {
+        return 0;
+    }
+    // c_long is only 32 bits wide on some targets
+    #[allow(clippy::useless_conversion)]
+    let offset = i64::from(tm.tm_gmtoff);
+    offset
+}
+
+#[cfg(not(unix))]
+fn local_offset(_time: SystemTime) -> i64 {
+    0
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic
+/// Gregorian calendar, following Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
 /// Formats a `SystemTime` as a human-readable elapsed duration (e.g., "3 hours ago").
 pub fn format_elapsed(time: SystemTime) -> String {
     let Ok(elapsed) = time.elapsed() else {
@@ -95,6 +189,59 @@ pub fn format_home_path(path: &Path) -> String {
     path.display().to_string()
 }
 
+/// Whether hints are suppressed (`--quiet`)
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables quiet output for this invocation
+pub fn set_quiet(enabled: bool) {
+    QUIET.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether paths may be printed as clickable hyperlinks (`--hyperlinks`)
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables hyperlinked paths for this invocation
+pub fn set_hyperlinks(enabled: bool) {
+    HYPERLINKS.store(enabled, Ordering::Relaxed);
+}
+
+/// Makes `text` a clickable link to `path` using an OSC 8 escape sequence.
+/// Only applies with `--hyperlinks` on a color terminal (so `NO_COLOR` also disables it);
+/// otherwise, or if `path` is not absolute, `text` is returned unchanged.
+pub fn link(text: impl Display, path: &Path) -> String {
+    let enabled =
+        HYPERLINKS.load(Ordering::Relaxed) && Term::stdout().is_term() && console::colors_enabled();
+    if enabled {
+        hyperlink(&text.to_string(), path)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Wraps `text` in an OSC 8 hyperlink to the `file://` URI of `path`
+fn hyperlink(text: &str, path: &Path) -> String {
+    match file_uri(path) {
+        Some(uri) => format!("\x1b]8;;{uri}\x1b\\{text}\x1b]8;;\x1b\\"),
+        None => text.to_string(),
+    }
+}
+
+/// Builds a `file://` URI for an absolute path, percent-encoding reserved bytes
+fn file_uri(path: &Path) -> Option<String> {
+    if !path.is_absolute() {
+        return None;
+    }
+    let mut uri = String::from("file://");
+    for byte in path.to_str()?.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+    Some(uri)
+}
+
 /// Prints a warning message to stderr
 pub fn eprint_warning(message: impl Display) {
     eprintln!("  {} {}", icon_warning(), message);
@@ -102,12 +249,226 @@ pub fn eprint_warning(message: impl Display) {
 
 /// Prints a dimmed hint message to stderr
 pub fn eprint_hint(message: impl Display) {
+    if QUIET.load(Ordering::Relaxed) {
+        return;
+    }
     eprintln!("  {} {}", style("hint:").dim(), message);
 }
 
-/// Creates a styled spinner with the given initial message.
+/// Whether debug output (`--debug`) is enabled
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables debug output for this invocation
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+/// Prints a dimmed debug message to stderr if `--debug` is enabled
+pub fn eprint_debug(message: impl Display) {
+    if DEBUG.load(Ordering::Relaxed) {
+        eprintln!("  {} {}", style("debug:").dim(), message);
+    }
+}
+
+/// Default pager invocation when `$PAGER` is not set.
+/// `-R` keeps ANSI colors, `-F` exits for single-screen output, `-X` keeps output on screen.
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Prints lines to stdout, piping them through a pager when stdout is a terminal
+/// and the output is taller than the terminal.
+/// Falls back to direct printing when disabled, when `$PAGER` is empty, or if the pager fails.
+pub fn paged_output(lines: impl IntoIterator<Item = String>, allow_pager: bool) {
+    let lines: Vec<String> = lines.into_iter().collect();
+    let term = Term::stdout();
+
+    let fits = term
+        .size_checked()
+        .is_none_or(|(rows, _)| lines.len() < usize::from(rows));
+
+    if allow_pager
+        && term.is_term()
+        && !fits
+        && let Some(pager) = pager_command(std::env::var("PAGER").ok().as_deref())
+        && write_to_pager(&pager, &lines)
+    {
+        return;
+    }
+
+    for line in &lines {
+        println!("{line}");
+    }
+}
+
+/// Resolves the pager program and arguments from a `$PAGER` value.
+/// Returns `None` if paging is disabled via an empty value.
+fn pager_command(env_value: Option<&str>) -> Option<Vec<String>> {
+    let value = env_value.unwrap_or(DEFAULT_PAGER);
+    let parts: Vec<String> = value.split_whitespace().map(String::from).collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// Spawns the pager and writes lines to its stdin.
+/// Returns `false` if the pager could not be started.
+fn write_to_pager(pager: &[String], lines: &[String]) -> bool {
+    let Ok(mut child) = Command::new(&pager[0])
+        .args(&pager[1..])
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // The user may quit the pager early, closing the pipe
+            if writeln!(stdin, "{line}").is_err() {
+                break;
+            }
+        }
+    }
+
+    let _ = child.wait();
+    true
+}
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Opens a file in the user's editor and waits for it to exit
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = editor_command(
+        std::env::var("VISUAL").ok().as_deref(),
+        std::env::var("EDITOR").ok().as_deref(),
+    );
+    let status = Command::new(&editor[0])
+        .args(&editor[1..])
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor[0]))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {status}", editor[0]);
+    }
+    Ok(())
+}
+
+/// Resolves the editor program and arguments from `$VISUAL`, then `$EDITOR`.
+/// Empty values are skipped; falls back to [`DEFAULT_EDITOR`].
+fn editor_command(visual: Option<&str>, editor: Option<&str>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            value
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_EDITOR.to_string()])
+}
+
+/// Prints a field diff if the value changed
+pub fn print_field_diff(label: &str, old: Option<&str>, new: Option<&str>) {
+    match (old, new) {
+        (Some(o), Some(n)) if o != n => {
+            println!(
+                "    {}: {} {} {}",
+                label,
+                style(o).red(),
+                style("→").dim(),
+                style(n).green()
+            );
+        }
+        (None, Some(n)) => {
+            println!("    {}: {} {}", label, style("+").green(), style(n).green());
+        }
+        (Some(o), None) => {
+            println!("    {}: {} {}", label, style("-").red(), style(o).red());
+        }
+        _ => {} // No change
+    }
+}
+
+/// Minimum width of the label column in a [`Table`], so short tables line up with longer ones.
+const TABLE_LABEL_WIDTH: usize = 16;
+
+/// Label/value rows rendered with an aligned label column.
+pub struct Table {
+    indent: usize,
+    bold_labels: bool,
+    rows: Vec<(String, String)>,
+}
+
+impl Table {
+    /// Creates an empty table with bold labels, indented by four spaces
+    pub fn new() -> Self {
+        Self {
+            indent: 4,
+            bold_labels: true,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Renders labels without styling
+    pub fn plain_labels(mut self) -> Self {
+        self.bold_labels = false;
+        self
+    }
+
+    /// Appends a row. An empty label continues the previous row's value column.
+    pub fn row(&mut self, label: impl Into<String>, value: impl Into<String>) {
+        self.rows.push((label.into(), value.into()));
+    }
+
+    /// Returns the label column width, growing past the minimum for long labels
+    fn label_width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|(label, _)| console::measure_text_width(label) + 2)
+            .max()
+            .unwrap_or(0)
+            .max(TABLE_LABEL_WIDTH)
+    }
+
+    /// Renders the rows as lines ready for printing
+    pub fn lines(&self) -> Vec<String> {
+        let width = self.label_width();
+        self.rows
+            .iter()
+            .map(|(label, value)| {
+                let padding = " ".repeat(width - console::measure_text_width(label));
+                let label = if self.bold_labels {
+                    style(label).bold().to_string()
+                } else {
+                    label.clone()
+                };
+                format!("{}{label}{padding}{value}", " ".repeat(self.indent))
+            })
+            .collect()
+    }
+
+    /// Prints the rendered rows to stdout
+    pub fn print(&self) {
+        for line in self.lines() {
+            println!("{line}");
+        }
+    }
+}
+
+/// Creates a styled spinner with the given initial message, drawn on stderr.
+/// The spinner is hidden when stderr is not a terminal, keeping redirected output clean.
 pub fn spinner(message: &str) -> ProgressBar {
-    let pb = ProgressBar::new_spinner();
+    spinner_for(message, Term::stderr().is_term())
+}
+
+fn spinner_for(message: &str, is_term: bool) -> ProgressBar {
+    if !is_term {
+        let pb = ProgressBar::hidden();
+        pb.set_message(message.to_string());
+        return pb;
+    }
+
+    let pb = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
     pb.set_style(
         ProgressStyle::default_spinner()
             .template("  {spinner:.cyan} {msg}")
@@ -118,6 +479,21 @@ pub fn spinner(message: &str) -> ProgressBar {
     pb
 }
 
+/// Returns `true` if prompts can be shown, i.e. stdin and stderr (where prompts render)
+/// are both terminals.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && Term::stderr().is_term()
+}
+
+/// Clears the last line on stdout, e.g. a prompt's answer. Does nothing when
+/// stdout is not a terminal, so piped output never receives escape sequences.
+pub fn clear_last_line() {
+    let term = Term::stdout();
+    if term.is_term() {
+        let _ = term.clear_last_lines(1);
+    }
+}
+
 /// Manages terminal state for interactive menu sessions.
 /// Handles clearing previous menu output between iterations.
 pub struct MenuSession {
@@ -139,7 +515,7 @@ impl MenuSession {
     /// Clears the previous menu line if this isn't the first iteration,
     /// unless `printed_output` was called after the last prompt.
     pub fn prepare(&mut self) {
-        if self.started && !self.skip_next_clear {
+        if self.started && !self.skip_next_clear && self.term.is_term() {
             let _ = self.term.clear_last_lines(1);
         }
         self.started = true;
@@ -206,6 +582,28 @@ impl MenuLevel {
         SimpleSelect::new(select)
     }
 
+    /// Creates a Select prompt with a leading "back" entry, for sub-menus.
+    /// `default_idx` is the starting cursor position among `options` (the "back" entry is not counted).
+    pub fn select_with_back<'a, T: Display>(
+        &self,
+        message: &'a str,
+        options: Vec<T>,
+        default_idx: usize,
+    ) -> BackSelect<'a, T> {
+        let default_idx = if options.is_empty() {
+            0
+        } else {
+            default_idx + 1
+        };
+        let options = std::iter::once(BackOption::Back)
+            .chain(options.into_iter().map(BackOption::Item))
+            .collect();
+
+        BackSelect {
+            select: self.select_with_default(message, options, default_idx),
+        }
+    }
+
     /// Creates a Select prompt with filtering enabled (for long lists)
     /// Uses case-insensitive substring matching to hide non-matching options.
     /// Shows "(no matches)" placeholder when filter yields no results.
@@ -242,6 +640,43 @@ impl<'a, T: Display> SimpleSelect<'a, T> {
     }
 }
 
+/// Label of the explicit "back" entry in sub-menus
+const BACK_LABEL: &str = "← Back";
+
+/// A menu entry that is either a regular option or the "back" entry
+enum BackOption<T> {
+    Back,
+    Item(T),
+}
+
+impl<T: Display> Display for BackOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Back => write!(f, "{}", style(BACK_LABEL).dim()),
+            Self::Item(item) => item.fmt(f),
+        }
+    }
+}
+
+/// A Select prompt with a leading "back" entry
+pub struct BackSelect<'a, T: Display> {
+    select: SimpleSelect<'a, BackOption<T>>,
+}
+
+impl<T: Display> BackSelect<'_, T> {
+    /// Shows the prompt and returns the selected option, or `None` if "back" was chosen.
+    /// Choosing "back" clears the prompt line like a cancellation.
+    pub fn prompt(self) -> Result<Option<T>, InquireError> {
+        match self.select.prompt()? {
+            BackOption::Back => {
+                let _ = Term::stdout().clear_last_lines(1);
+                Ok(None)
+            }
+            BackOption::Item(item) => Ok(Some(item)),
+        }
+    }
+}
+
 /// Placeholder text shown when no options match the filter (unstyled for comparison)
 const NO_MATCHES_TEXT: &str = "(no matches)";
 
@@ -383,6 +818,40 @@ pub fn prompt_text_with_help(prompt: &str, help: &str) -> Result<Option<String>>
     }
 }
 
+/// Source of yes/no answers, so confirmation loops can run against scripted answers
+pub trait Prompter {
+    /// Returns `Ok(None)` if cancelled, `Ok(Some(bool))` on an answer
+    fn confirm(&mut self, prompt: &str) -> Result<Option<bool>>;
+}
+
+/// Asks on the terminal via [`prompt_confirm`], defaulting to "no"
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn confirm(&mut self, prompt: &str) -> Result<Option<bool>> {
+        prompt_confirm(prompt, false)
+    }
+}
+
+/// Confirms each item individually and returns the accepted ones, in order.
+/// Cancelling a prompt skips that item and every remaining one.
+/// Callers bypass this entirely for `--yes`/`--force` style flags.
+pub fn confirm_each<T>(
+    items: Vec<T>,
+    render: impl Fn(&T) -> String,
+    prompter: &mut impl Prompter,
+) -> Result<Vec<T>> {
+    let mut confirmed = Vec::new();
+    for item in items {
+        match prompter.confirm(&render(&item))? {
+            Some(true) => confirmed.push(item),
+            Some(false) => {}
+            None => break,
+        }
+    }
+    Ok(confirmed)
+}
+
 /// Prompts for a yes/no confirmation.
 /// Returns `Ok(None)` if cancelled, `Ok(Some(bool))` on success.
 pub fn prompt_confirm(prompt: &str, default: bool) -> Result<Option<bool>> {
@@ -401,6 +870,208 @@ pub fn prompt_confirm(prompt: &str, default: bool) -> Result<Option<bool>> {
 mod tests {
     use super::*;
 
+    /// Answers prompts from a script; `None` entries cancel
+    struct ScriptedPrompter {
+        answers: std::vec::IntoIter<Option<bool>>,
+        asked: Vec<String>,
+    }
+
+    impl ScriptedPrompter {
+        fn new(answers: Vec<Option<bool>>) -> Self {
+            Self {
+                answers: answers.into_iter(),
+                asked: Vec::new(),
+            }
+        }
+    }
+
+    impl Prompter for ScriptedPrompter {
+        fn confirm(&mut self, prompt: &str) -> Result<Option<bool>> {
+            self.asked.push(prompt.to_string());
+            Ok(self.answers.next().flatten())
+        }
+    }
+
+    #[test]
+    fn test_confirm_each_filters_by_answer() {
+        let mut prompter = ScriptedPrompter::new(vec![Some(true), Some(false), Some(true)]);
+        let confirmed = confirm_each(
+            vec!["a", "b", "c"],
+            |s| format!("Remove {s}?"),
+            &mut prompter,
+        )
+        .unwrap();
+        assert_eq!(confirmed, ["a", "c"]);
+        assert_eq!(prompter.asked, ["Remove a?", "Remove b?", "Remove c?"]);
+    }
+
+    #[test]
+    fn test_confirm_each_cancel_skips_rest() {
+        let mut prompter = ScriptedPrompter::new(vec![Some(true), None, Some(true)]);
+        let confirmed = confirm_each(vec![1, 2, 3], ToString::to_string, &mut prompter).unwrap();
+        assert_eq!(confirmed, [1]);
+        assert_eq!(prompter.asked.len(), 2);
+    }
+
+    #[test]
+    fn test_spinner_hidden_without_terminal() {
+        let pb = spinner_for("Working...", false);
+        assert!(pb.is_hidden());
+        assert_eq!(pb.message(), "Working...");
+        pb.set_message("Still working...");
+        pb.finish_and_clear();
+        assert!(pb.is_finished());
+    }
+
+    #[test]
+    fn test_table_label_width() {
+        let mut table = Table::new();
+        assert_eq!(table.label_width(), TABLE_LABEL_WIDTH);
+        table.row("Branch:", "main");
+        assert_eq!(table.label_width(), TABLE_LABEL_WIDTH);
+        table.row("A much longer label:", "value");
+        assert_eq!(table.label_width(), 22);
+    }
+
+    #[test]
+    fn test_table_lines() {
+        let mut table = Table::new().plain_labels();
+        table.row("Signing key", "ABC123");
+        table.row("", "continued");
+        assert_eq!(
+            table.lines(),
+            vec![
+                "    Signing key     ABC123".to_string(),
+                "                    continued".to_string(),
+            ]
+        );
+
+        let mut bold = Table::new();
+        bold.row("Status:", style("clean").green().to_string());
+        let line = bold.lines().remove(0);
+        assert_eq!(
+            console::strip_ansi_codes(&line),
+            "    Status:         clean"
+        );
+    }
+
+    #[test]
+    fn test_back_option_display() {
+        let item: BackOption<&str> = BackOption::Item("ssh");
+        assert_eq!(item.to_string(), "ssh");
+
+        let back: BackOption<&str> = BackOption::Back;
+        assert!(console::strip_ansi_codes(&back.to_string()).contains(BACK_LABEL));
+    }
+
+    #[test]
+    fn test_pager_command_default() {
+        assert_eq!(
+            pager_command(None),
+            Some(vec!["less".to_string(), "-FRX".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_pager_command_custom() {
+        assert_eq!(
+            pager_command(Some("more -d")),
+            Some(vec!["more".to_string(), "-d".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_editor_command() {
+        assert_eq!(editor_command(None, None), ["vi"]);
+        assert_eq!(
+            editor_command(None, Some("code --wait")),
+            ["code", "--wait"]
+        );
+        assert_eq!(editor_command(Some("nvim"), Some("nano")), ["nvim"]);
+        assert_eq!(editor_command(Some(" "), Some("nano")), ["nano"]);
+    }
+
+    #[test]
+    fn test_hyperlink_sequence() {
+        assert_eq!(
+            hyperlink("~/work/my repo", Path::new("/home/u/work/my repo")),
+            "\x1b]8;;file:///home/u/work/my%20repo\x1b\\~/work/my repo\x1b]8;;\x1b\\"
+        );
+        // Relative paths have no file URI
+        assert_eq!(hyperlink("repo", Path::new("repo")), "repo");
+    }
+
+    #[test]
+    fn test_link_plain_when_disabled() {
+        // Disabled by default, and tests never write to a terminal
+        assert_eq!(link("~/work", Path::new("/home/u/work")), "~/work");
+    }
+
+    #[test]
+    fn test_pager_command_empty_disables() {
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("   ")), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH, 0),
+            "1970-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH + Duration::from_secs(1_714_572_189), 0),
+            "2024-05-01T14:03:09+00:00"
+        );
+        // Leap day and end of year
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH + Duration::from_hours(264_384), 0),
+            "2000-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH + Duration::from_secs(1_735_689_599), 0),
+            "2024-12-31T23:59:59+00:00"
+        );
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH - Duration::from_mins(1), 0),
+            "1970-01-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_local_offsets() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_714_572_189);
+        assert_eq!(
+            format_timestamp_at(time, 2 * 3600),
+            "2024-05-01T16:03:09+02:00"
+        );
+        assert_eq!(
+            format_timestamp_at(time, -(5 * 3600)),
+            "2024-05-01T09:03:09-05:00"
+        );
+        assert_eq!(
+            format_timestamp_at(time, 5 * 3600 + 45 * 60),
+            "2024-05-01T19:48:09+05:45"
+        );
+        // Crossing midnight into the next day and year
+        assert_eq!(
+            format_timestamp_at(UNIX_EPOCH + Duration::from_secs(1_735_689_599), 3600),
+            "2025-01-01T00:59:59+01:00"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_local_offset_is_plausible() {
+        let offset = local_offset(SystemTime::now());
+        assert!(offset.abs() <= 14 * 3600);
+    }
+
+    #[test]
+    fn test_format_time_relative_by_default() {
+        assert_eq!(format_time(SystemTime::now()), "just now");
+    }
+
     #[test]
     fn test_format_elapsed_just_now() {
         let time = SystemTime::now();
@@ -415,43 +1086,43 @@ mod tests {
 
     #[test]
     fn test_format_elapsed_one_minute() {
-        let time = SystemTime::now() - Duration::from_secs(60);
+        let time = SystemTime::now() - Duration::from_mins(1);
         assert_eq!(format_elapsed(time), "1 minute ago");
     }
 
     #[test]
     fn test_format_elapsed_minutes() {
-        let time = SystemTime::now() - Duration::from_secs(45 * 60);
+        let time = SystemTime::now() - Duration::from_mins(45);
         assert_eq!(format_elapsed(time), "45 minutes ago");
     }
 
     #[test]
     fn test_format_elapsed_one_hour() {
-        let time = SystemTime::now() - Duration::from_secs(3600);
+        let time = SystemTime::now() - Duration::from_hours(1);
         assert_eq!(format_elapsed(time), "1 hour ago");
     }
 
     #[test]
     fn test_format_elapsed_hours() {
-        let time = SystemTime::now() - Duration::from_secs(5 * 3600);
+        let time = SystemTime::now() - Duration::from_hours(5);
         assert_eq!(format_elapsed(time), "5 hours ago");
     }
 
     #[test]
     fn test_format_elapsed_one_day() {
-        let time = SystemTime::now() - Duration::from_secs(24 * 3600);
+        let time = SystemTime::now() - Duration::from_hours(24);
         assert_eq!(format_elapsed(time), "1 day ago");
     }
 
     #[test]
     fn test_format_elapsed_days() {
-        let time = SystemTime::now() - Duration::from_secs(3 * 24 * 3600);
+        let time = SystemTime::now() - Duration::from_hours(3 * 24);
         assert_eq!(format_elapsed(time), "3 days ago");
     }
 
     #[test]
     fn test_format_elapsed_future_time() {
-        let time = SystemTime::now() + Duration::from_secs(3600);
+        let time = SystemTime::now() + Duration::from_hours(1);
         assert_eq!(format_elapsed(time), "just now");
     }
 }